@@ -0,0 +1,17 @@
+-- Per-record sync metadata and deletion tombstones
+
+ALTER TABLE visions ADD COLUMN updated_at TIMESTAMP;
+ALTER TABLE visions ADD COLUMN updated_by TEXT;
+
+ALTER TABLE reality_assessments ADD COLUMN updated_at TIMESTAMP;
+ALTER TABLE reality_assessments ADD COLUMN updated_by TEXT;
+
+ALTER TABLE action_logs ADD COLUMN updated_at TIMESTAMP;
+ALTER TABLE action_logs ADD COLUMN updated_by TEXT;
+
+CREATE TABLE IF NOT EXISTS tombstones (
+    id TEXT PRIMARY KEY,
+    kind TEXT NOT NULL CHECK(kind IN ('Vision', 'Reality', 'Action')),
+    deleted_at TIMESTAMP NOT NULL,
+    deleted_by TEXT
+);
@@ -0,0 +1,3 @@
+-- Order of a vision among its siblings
+
+ALTER TABLE visions ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
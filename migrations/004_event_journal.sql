@@ -0,0 +1,8 @@
+-- Published domain events, for `tensile events` to stream to other processes
+
+CREATE TABLE IF NOT EXISTS event_journal (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    recorded_at TEXT NOT NULL,
+    event TEXT NOT NULL,
+    payload TEXT NOT NULL
+);
@@ -0,0 +1,10 @@
+-- Achievement is recorded by the state alone; `completed` is no longer read
+-- or written. The column stays because tensile binaries from before this
+-- migration still select it; new rows get its default of 0.
+
+UPDATE visions SET state = 'Achieved' WHERE completed = 1;
+
+ALTER TABLE visions ADD COLUMN achieved_at TEXT;
+ALTER TABLE visions ADD COLUMN retrospective TEXT;
+
+UPDATE visions SET achieved_at = COALESCE(updated_at, created_at) WHERE state = 'Achieved';
@@ -0,0 +1,3 @@
+-- What is at stake in a vision, from 1 to 5
+
+ALTER TABLE visions ADD COLUMN stakes INTEGER NOT NULL DEFAULT 3;
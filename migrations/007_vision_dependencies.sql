@@ -0,0 +1,9 @@
+-- "A depends on B": B blocks A until it is achieved
+
+CREATE TABLE IF NOT EXISTS vision_dependencies (
+    vision_id TEXT NOT NULL REFERENCES visions(id) ON DELETE CASCADE,
+    depends_on TEXT NOT NULL REFERENCES visions(id) ON DELETE CASCADE,
+    PRIMARY KEY (vision_id, depends_on)
+);
+
+CREATE INDEX IF NOT EXISTS idx_dependency_depends_on ON vision_dependencies(depends_on);
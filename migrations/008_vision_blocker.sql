@@ -0,0 +1,6 @@
+-- What blocks a blocked vision, and when it may move again
+
+ALTER TABLE visions ADD COLUMN blocked_reason TEXT;
+ALTER TABLE visions ADD COLUMN unblock_when TEXT;
+ALTER TABLE visions ADD COLUMN unblock_on TEXT;
+ALTER TABLE visions ADD COLUMN blocked_at TEXT;
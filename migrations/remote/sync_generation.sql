@@ -0,0 +1,7 @@
+-- Bumped by every upload to a libSQL server, so a device can tell whether
+-- the server changed since it fetched. Only created on the server, outside
+-- the numbered migrations, so it can run again safely.
+
+CREATE TABLE IF NOT EXISTS sync_generation (generation INTEGER NOT NULL);
+INSERT INTO sync_generation (generation)
+SELECT 0 WHERE NOT EXISTS (SELECT 1 FROM sync_generation);
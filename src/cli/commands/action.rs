@@ -1,9 +1,21 @@
 use crate::{
+    cli::{
+        editor::{self, Template},
+        output::{self, timestamp, Confirmation, OutputFormat, Report},
+        parsers::{
+            parse_datetime, parse_end_datetime, short_id, VisionRef, DATETIME_HELP, VISION_HELP,
+        },
+    },
     error::{TensileError, TensileResult},
     models::ActionLog,
-    persistence,
+    service::{
+        resolve_action, ActionAmendment, ActionReview, GroupBy, ReviewPeriod, ReviewQuery, Session,
+    },
 };
+use chrono::{DateTime, Utc};
 use clap::Subcommand;
+use serde::Serialize;
+use std::fmt;
 use uuid::Uuid;
 
 #[derive(Subcommand)]
@@ -11,118 +23,339 @@ pub enum ActionCommands {
     /// Log an action toward a vision
     #[command(visible_alias = "l")]
     Log {
-        vision_id: String,
-        #[arg(trailing_var_arg = true)]
+        #[arg(help = VISION_HELP)]
+        vision_id: VisionRef,
+        entry: Vec<String>,
+        /// Record an attempt that did not work out
+        #[arg(long)]
+        failed: bool,
+        /// When the action was taken (default: now)
+        #[arg(long, value_parser = parse_datetime, help = DATETIME_HELP)]
+        at: Option<DateTime<Utc>>,
+        /// Write the entry in $EDITOR (the default when no text is given)
+        #[arg(long, short)]
+        edit: bool,
+    },
+
+    /// Change the text, outcome or time of a logged action
+    Amend {
+        /// Action id or unique prefix, as shown by `action review`
+        id: String,
+        /// New text for the action
         entry: Vec<String>,
+        /// Mark the action as failed
+        #[arg(long, conflicts_with = "succeeded")]
+        failed: bool,
+        /// Mark the action as successful
+        #[arg(long)]
+        succeeded: bool,
+        #[arg(long, value_parser = parse_datetime, help = DATETIME_HELP)]
+        at: Option<DateTime<Utc>>,
+    },
+
+    /// Delete a logged action
+    #[command(visible_alias = "rm")]
+    Delete {
+        /// Action id or unique prefix, as shown by `action review`
+        id: String,
     },
 
     /// Prompt for today's action
     #[command(visible_alias = "t")]
-    Today { vision_id: String },
+    Today {
+        #[arg(help = VISION_HELP)]
+        vision_id: VisionRef,
+    },
 
-    /// Review actions by period
+    /// Review actions by period or date range
     #[command(visible_alias = "r")]
     Review {
-        #[arg(long, default_value = "daily")]
-        period: String,
-        #[arg(long)]
-        vision: Option<String>,
+        /// Calendar period up to now (default: review.default_period in the
+        /// config)
+        #[arg(long, value_enum, ignore_case = true, conflicts_with_all = ["since", "until"])]
+        period: Option<ReviewPeriod>,
+        /// Start of the range (default: the beginning)
+        #[arg(long, value_parser = parse_datetime, help = DATETIME_HELP)]
+        since: Option<DateTime<Utc>>,
+        /// End of the range; a date includes that whole day (default: now)
+        #[arg(long, value_parser = parse_end_datetime, help = DATETIME_HELP)]
+        until: Option<DateTime<Utc>>,
+        /// Group actions by vision, day or week
+        #[arg(long, value_enum, ignore_case = true)]
+        group_by: Option<GroupBy>,
+        #[arg(long, help = VISION_HELP)]
+        vision: Option<VisionRef>,
     },
 }
 
 impl ActionCommands {
-    pub fn execute(self) -> TensileResult<()> {
+    pub fn execute(self, session: &mut Session, output: OutputFormat) -> TensileResult<String> {
         match self {
-            ActionCommands::Log { vision_id, entry } => log_action(vision_id, entry),
-            ActionCommands::Today { vision_id } => today_action(vision_id),
-            ActionCommands::Review { period, vision } => review_actions(period, vision),
+            ActionCommands::Log {
+                vision_id,
+                entry,
+                failed,
+                at,
+                edit,
+            } => output::render(
+                output,
+                &log_action(session, vision_id, entry, edit, !failed, at)?,
+            ),
+            ActionCommands::Amend {
+                id,
+                entry,
+                failed,
+                succeeded,
+                at,
+            } => {
+                let amendment = ActionAmendment {
+                    entry: (!entry.is_empty()).then(|| entry.join(" ")),
+                    success: match (failed, succeeded) {
+                        (true, _) => Some(false),
+                        (_, true) => Some(true),
+                        _ => None,
+                    },
+                    timestamp: at,
+                };
+                output::render(output, &amend_action(session, id, amendment)?)
+            }
+            ActionCommands::Delete { id } => output::render(output, &delete_action(session, id)?),
+            ActionCommands::Today { vision_id } => {
+                output::render(output, &today_action(session, vision_id)?)
+            }
+            ActionCommands::Review {
+                period,
+                since,
+                until,
+                group_by,
+                vision,
+            } => output::render(
+                output,
+                &review_actions(session, period, since, until, group_by, vision)?,
+            ),
         }
     }
 }
 
-fn log_action(vision_id: String, entry: Vec<String>) -> TensileResult<()> {
-    let uuid = Uuid::parse_str(&vision_id)
-        .map_err(|_| TensileError::Parse(format!("Invalid UUID: {}", vision_id)))?;
+/// The vision to take today's step toward.
+#[derive(Serialize, Debug, Clone)]
+pub struct TodayPrompt {
+    pub vision_id: Uuid,
+    pub title: String,
+}
 
-    let mut db = persistence::load_database()?;
+impl fmt::Display for TodayPrompt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "\nToday's Action for: {}", self.title)?;
+        writeln!(f, "What is your next step toward this vision?")?;
+        writeln!(
+            f,
+            "(Run: tensile action log {} <your action>)",
+            short_id(&self.vision_id)
+        )
+    }
+}
 
-    let vision_exists = db.visions.iter().any(|v| v.id == uuid);
-    if !vision_exists {
-        return Err(TensileError::NotFound(format!(
-            "Vision not found: {}",
-            vision_id
-        )));
+impl Report for TodayPrompt {
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["vision_id", "title"]
     }
 
-    let action = ActionLog::new(uuid, entry.join(" "));
-    db.actions.push(action);
-    persistence::save_database(&db)?;
-    println!("✓ Action logged");
-    Ok(())
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![self.vision_id.to_string(), self.title.clone()]]
+    }
 }
 
-fn today_action(vision_id: String) -> TensileResult<()> {
-    let uuid = Uuid::parse_str(&vision_id)
-        .map_err(|_| TensileError::Parse(format!("Invalid UUID: {}", vision_id)))?;
+impl ActionReview {
+    fn range(&self) -> String {
+        match (&self.period, &self.since, &self.until) {
+            (Some(period), _, _) => period.to_string(),
+            (None, Some(since), Some(until)) => {
+                format!("{} to {}", timestamp(since), timestamp(until))
+            }
+            (None, Some(since), None) => format!("since {}", timestamp(since)),
+            (None, None, Some(until)) => format!("before {}", timestamp(until)),
+            (None, None, None) => "all time".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ActionReview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "\nActions ({}):", self.range())?;
+        writeln!(
+            f,
+            "  Total: {} | Successful: {}",
+            self.total, self.successful
+        )?;
+
+        if self.group_by.is_none() {
+            return self
+                .actions
+                .iter()
+                .try_for_each(|a| write_action(f, "  ", a));
+        }
+        for group in &self.groups {
+            writeln!(
+                f,
+                "\n  {} ({} action{}, {} successful)",
+                group.label,
+                group.total,
+                if group.total == 1 { "" } else { "s" },
+                group.successful
+            )?;
+            for action in &group.actions {
+                write_action(f, "    ", action)?;
+            }
+        }
+        Ok(())
+    }
+}
 
-    let db = persistence::load_database()?;
+fn write_action(f: &mut fmt::Formatter<'_>, indent: &str, action: &ActionLog) -> fmt::Result {
+    let marker = if action.success { "✓" } else { "✗" };
+    writeln!(
+        f,
+        "{}{} [{}] {}  {}",
+        indent,
+        marker,
+        timestamp(&action.timestamp),
+        short_id(&action.id),
+        action.entry
+    )
+}
 
-    let vision = db
-        .visions
-        .iter()
-        .find(|v| v.id == uuid)
-        .ok_or_else(|| TensileError::NotFound(format!("Vision not found: {}", vision_id)))?;
+impl Report for ActionReview {
+    fn headers(&self) -> Vec<&'static str> {
+        let mut headers = vec!["id", "timestamp", "vision_id", "success", "entry"];
+        if self.group_by.is_some() {
+            headers.insert(0, "group");
+        }
+        headers
+    }
 
-    println!("\nToday's Action for: {}", vision.title);
-    println!("What is your next step toward this vision?");
-    println!("(Run: tensile action log {} <your action>)", vision_id);
+    fn rows(&self) -> Vec<Vec<String>> {
+        let row = |a: &ActionLog| {
+            vec![
+                a.id.to_string(),
+                a.timestamp.to_rfc3339(),
+                a.vision_id.to_string(),
+                a.success.to_string(),
+                a.entry.clone(),
+            ]
+        };
 
-    Ok(())
+        if self.group_by.is_none() {
+            return self.actions.iter().map(row).collect();
+        }
+        self.groups
+            .iter()
+            .flat_map(|group| {
+                group.actions.iter().map(move |a| {
+                    let mut cells = vec![group.key.clone()];
+                    cells.extend(row(a));
+                    cells
+                })
+            })
+            .collect()
+    }
 }
 
-fn review_actions(period: String, vision_id: Option<String>) -> TensileResult<()> {
-    let db = persistence::load_database()?;
+fn log_action(
+    session: &mut Session,
+    vision_id: VisionRef,
+    entry: Vec<String>,
+    edit: bool,
+    success: bool,
+    at: Option<DateTime<Utc>>,
+) -> TensileResult<Confirmation> {
+    let id = session.resolve(&vision_id)?;
+    let entry = editor::text_or_edit(entry, edit, || {
+        Template::for_vision(session, id, "Action toward")
+    })?;
+    let action = session.log_action_at(id, entry, success, at.unwrap_or_else(Utc::now))?;
 
-    let filter_uuid = if let Some(id) = vision_id {
-        Some(
-            Uuid::parse_str(&id)
-                .map_err(|_| TensileError::Parse(format!("Invalid UUID: {}", id)))?,
-        )
+    let message = if success {
+        "Action logged"
     } else {
-        None
+        "Failed attempt logged"
     };
+    Ok(Confirmation::new(message).with_id(action.id))
+}
 
-    let now = chrono::Utc::now();
-    let cutoff = match period.as_str() {
-        "daily" => now - chrono::Duration::days(1),
-        "weekly" => now - chrono::Duration::days(7),
-        "monthly" => now - chrono::Duration::days(30),
-        _ => now - chrono::Duration::days(1),
-    };
+fn amend_action(
+    session: &mut Session,
+    id: String,
+    amendment: ActionAmendment,
+) -> TensileResult<Confirmation> {
+    if amendment == ActionAmendment::default() {
+        return Err(TensileError::Validation(
+            "Nothing to change: give new text, --failed, --succeeded or --at".to_string(),
+        ));
+    }
+    let id = resolve_action(session.database(), &id)?;
+    let action = session.amend_action(id, amendment)?;
+    Ok(Confirmation::new(format!("Action amended: {}", describe(&action))).with_id(action.id))
+}
 
-    let mut actions: Vec<_> = db
-        .actions
-        .iter()
-        .filter(|a| {
-            (filter_uuid.is_none() || filter_uuid == Some(a.vision_id)) && a.timestamp > cutoff
-        })
-        .collect();
-
-    actions.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-
-    println!("\nActions ({}):", period);
-    let success_count = actions.iter().filter(|a| a.success).count();
-    println!("  Total: {} | Successful: {}", actions.len(), success_count);
-
-    for action in actions {
-        let marker = if action.success { "✓" } else { "✗" };
-        println!(
-            "  {} [{}] {}",
-            marker,
-            action.timestamp.format("%Y-%m-%d %H:%M"),
-            action.entry
-        );
+fn delete_action(session: &mut Session, id: String) -> TensileResult<Confirmation> {
+    let id = resolve_action(session.database(), &id)?;
+    let action = session.delete_action(id)?;
+    Ok(Confirmation::new(format!("Action deleted: {}", describe(&action))).with_id(action.id))
+}
+
+fn describe(action: &ActionLog) -> String {
+    format!(
+        "{} [{}] {}",
+        if action.success { "✓" } else { "✗" },
+        timestamp(&action.timestamp),
+        action.entry
+    )
+}
+
+fn today_action(session: &Session, vision_id: VisionRef) -> TensileResult<TodayPrompt> {
+    let vision = session.vision(session.resolve(&vision_id)?)?;
+
+    Ok(TodayPrompt {
+        vision_id: vision.id,
+        title: vision.title.clone(),
+    })
+}
+
+fn review_actions(
+    session: &Session,
+    period: Option<ReviewPeriod>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    group_by: Option<GroupBy>,
+    vision_id: Option<VisionRef>,
+) -> TensileResult<ActionReview> {
+    if let (Some(since), Some(until)) = (since, until) {
+        if since >= until {
+            return Err(TensileError::Validation(format!(
+                "--since ({}) must be before --until ({})",
+                timestamp(&since),
+                timestamp(&until)
+            )));
+        }
     }
+    let vision_id = vision_id.map(|id| session.resolve(&id)).transpose()?;
 
-    Ok(())
+    let query = ReviewQuery {
+        since,
+        until,
+        vision_id,
+        group_by,
+    };
+
+    if since.is_some() || until.is_some() {
+        return Ok(session.review_range(&query));
+    }
+    let period = period.unwrap_or(session.default_review_period());
+    let mut review = session.review_range(&ReviewQuery {
+        since: Some(session.period_start(period)),
+        ..query
+    });
+    review.period = Some(period);
+    Ok(review)
 }
@@ -0,0 +1,339 @@
+use super::{template::TemplateCommands, Commands};
+use crate::{
+    cli::{
+        output::{self, OutputFormat, Report},
+        Cli,
+    },
+    error::{TensileError, TensileResult},
+    service::Session,
+};
+use clap::{CommandFactory, Parser};
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+use std::io::Read;
+use std::path::PathBuf;
+
+/// Outcome of one batch line.
+#[derive(Serialize, Debug, Clone)]
+pub struct LineResult {
+    /// Line number in the input, from 1
+    pub line: usize,
+    pub command: String,
+    pub ok: bool,
+    /// The command's JSON output
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Per-line results of a batch, and whether its changes were saved.
+#[derive(Serialize, Debug, Clone)]
+pub struct BatchReport {
+    pub saved: bool,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<LineResult>,
+}
+
+impl fmt::Display for BatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for result in &self.results {
+            match &result.error {
+                None => writeln!(f, "✓ {:>4}  {}", result.line, summary(result))?,
+                Some(error) => {
+                    writeln!(f, "✗ {:>4}  {}", result.line, result.command)?;
+                    writeln!(f, "        {}", error)?;
+                }
+            }
+        }
+
+        let total = self.succeeded + self.failed;
+        if self.saved {
+            writeln!(f, "\nSaved {} of {} commands", self.succeeded, total)
+        } else {
+            writeln!(f, "\nRolled back: nothing was saved")
+        }
+    }
+}
+
+impl Report for BatchReport {
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["line", "ok", "command", "detail"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.results
+            .iter()
+            .map(|r| {
+                vec![
+                    r.line.to_string(),
+                    r.ok.to_string(),
+                    r.command.clone(),
+                    r.error.clone().unwrap_or_else(|| summary(r)),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// The confirmation message of a command that changed data, otherwise the
+/// command itself.
+fn summary(result: &LineResult) -> String {
+    result
+        .result
+        .as_ref()
+        .and_then(|r| r.get("message"))
+        .and_then(Value::as_str)
+        .unwrap_or(&result.command)
+        .to_string()
+}
+
+pub fn batch_command(
+    file: Option<PathBuf>,
+    continue_on_error: bool,
+    output: OutputFormat,
+) -> TensileResult<()> {
+    let input = match file {
+        Some(path) if path.as_os_str() != "-" => std::fs::read_to_string(path)?,
+        _ => {
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input)?;
+            input
+        }
+    };
+
+    let mut session = Session::open()?;
+    let report = run_batch(&mut session, &input, continue_on_error);
+    if report.saved {
+        session.commit()?;
+    }
+    output::emit(output, &report)?;
+
+    match report.results.iter().find(|r| !r.ok) {
+        Some(failed) if !report.saved => Err(TensileError::Validation(format!(
+            "Line {} failed; no changes were saved (use --continue-on-error to apply the rest)",
+            failed.line
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Apply each line of `input` to the session. On the first failure the
+/// batch stops and `saved` is false, unless `continue_on_error` is set, in
+/// which case only the failed line is undone.
+pub fn run_batch(session: &mut Session, input: &str, continue_on_error: bool) -> BatchReport {
+    let mut report = BatchReport {
+        saved: true,
+        succeeded: 0,
+        failed: 0,
+        results: Vec::new(),
+    };
+
+    for (index, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let outcome = parse_line(line)
+            .map_err(|e| e.to_string())
+            .and_then(|command| {
+                // Without `continue_on_error` a failure discards the session
+                let checkpoint = continue_on_error.then(|| session.checkpoint());
+                let outcome = run_command(session, command);
+                if let (Err(_), Some(checkpoint)) = (&outcome, checkpoint) {
+                    session.rollback(checkpoint);
+                }
+                outcome
+            });
+
+        let mut result = LineResult {
+            line: index + 1,
+            command: line.to_string(),
+            ok: outcome.is_ok(),
+            result: None,
+            error: None,
+        };
+        match outcome {
+            Ok(value) => {
+                report.succeeded += 1;
+                result.result = Some(value);
+                report.results.push(result);
+            }
+            Err(error) => {
+                report.failed += 1;
+                result.error = Some(error);
+                report.results.push(result);
+                if !continue_on_error {
+                    report.saved = false;
+                    break;
+                }
+            }
+        }
+    }
+    report
+}
+
+/// Run one parsed line, returning the command's JSON output. Template
+/// changes are saved as they are made, so they cannot be part of a batch.
+fn run_command(session: &mut Session, command: Commands) -> Result<Value, String> {
+    let json = match command {
+        Commands::Template(TemplateCommands::Save { .. } | TemplateCommands::Delete { .. }) => {
+            Err(TensileError::Validation(
+                "template save and delete take effect at once, so they cannot run in a batch"
+                    .to_string(),
+            ))
+        }
+        command => command.apply(session, OutputFormat::Json),
+    };
+    json.and_then(|json| {
+        serde_json::from_str(&json).map_err(|e| TensileError::Serialization(e.to_string()))
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Parse a batch line: a JSON array of arguments, a JSON object naming the
+/// command and its arguments, or a shell-quoted command line. A leading
+/// `tensile` is optional.
+fn parse_line(line: &str) -> TensileResult<Commands> {
+    let mut args = match line.chars().next() {
+        Some('[') => serde_json::from_str::<Vec<String>>(line)
+            .map_err(|e| TensileError::Parse(format!("Expected a JSON array of strings: {}", e)))?,
+        Some('{') => object_args(line)?,
+        _ => shlex::split(line)
+            .ok_or_else(|| TensileError::Parse("Unbalanced quotes".to_string()))?,
+    };
+    if args.first().is_some_and(|a| a == "tensile") {
+        args.remove(0);
+    }
+
+    let cli =
+        Cli::try_parse_from(std::iter::once("tensile".to_string()).chain(args)).map_err(|e| {
+            let message = e.render().to_string();
+            let first = message.lines().next().unwrap_or_default();
+            TensileError::Parse(first.trim_start_matches("error: ").to_string())
+        })?;
+    Ok(cli.command)
+}
+
+/// Arguments from an object such as
+/// `{"command": "action log", "vision_id": "cello", "entry": "Ran 5k"}`.
+/// Keys are argument names as in `--help`, with `-` or `_`; `true` sets a
+/// flag and arrays repeat an argument.
+fn object_args(line: &str) -> TensileResult<Vec<String>> {
+    let object: serde_json::Map<String, Value> = serde_json::from_str(line)
+        .map_err(|e| TensileError::Parse(format!("Invalid JSON: {}", e)))?;
+
+    let path: Vec<String> = object
+        .get("command")
+        .and_then(Value::as_str)
+        .ok_or_else(|| TensileError::Parse("Missing \"command\", e.g. \"action log\"".to_string()))?
+        .split_whitespace()
+        .map(String::from)
+        .collect();
+
+    let mut cmd = Cli::command();
+    cmd.build();
+    for name in &path {
+        cmd = cmd
+            .find_subcommand(name)
+            .cloned()
+            .ok_or_else(|| TensileError::Parse(format!("Unknown command: {}", path.join(" "))))?;
+    }
+
+    let mut flags = Vec::new();
+    let mut positionals: Vec<(usize, Vec<String>)> = Vec::new();
+    for (key, value) in object.iter().filter(|(k, _)| *k != "command") {
+        let id = key.replace('-', "_");
+        let arg = cmd
+            .get_arguments()
+            .find(|a| a.get_id() == id.as_str())
+            .ok_or_else(|| TensileError::Parse(format!("Unknown argument: {}", key)))?;
+
+        let values: Vec<String> = match value {
+            Value::Null | Value::Bool(false) => continue,
+            Value::Array(items) => items.iter().map(scalar).collect(),
+            other => vec![scalar(other)],
+        };
+
+        match (arg.get_index(), arg.get_long()) {
+            (Some(index), _) => positionals.push((index, values)),
+            (None, Some(long)) if !arg.get_action().takes_values() => {
+                flags.push(format!("--{}", long))
+            }
+            (None, Some(long)) => {
+                for value in values {
+                    flags.push(format!("--{}={}", long, value));
+                }
+            }
+            (None, None) => {
+                return Err(TensileError::Parse(format!("Unknown argument: {}", key)));
+            }
+        }
+    }
+    positionals.sort_by_key(|(index, _)| *index);
+
+    let mut args = path;
+    args.extend(flags);
+    args.push("--".to_string());
+    args.extend(positionals.into_iter().flat_map(|(_, values)| values));
+    Ok(args)
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Database;
+
+    #[test]
+    fn objects_become_command_lines() {
+        let args = object_args(
+            r#"{"command": "action log", "vision_id": "cello", "entry": "-5 degrees, still ran", "failed": true, "edit": false}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            args,
+            [
+                "action",
+                "log",
+                "--failed",
+                "--",
+                "cello",
+                "-5 degrees, still ran"
+            ]
+        );
+
+        assert!(object_args(r#"{"command": "action log", "colour": "red"}"#).is_err());
+        assert!(object_args(r#"{"command": "action juggle"}"#).is_err());
+    }
+
+    #[test]
+    fn a_failing_line_rolls_back_the_batch() {
+        let input = r#"
+            # comments and blank lines are skipped
+            vision new "Learn the cello"
+            ["action", "log", "cello", "Scales"]
+            action log knitting "Casting on"
+        "#;
+
+        let mut session = Session::new(Database::default());
+        let report = run_batch(&mut session, input, false);
+        assert!(!report.saved);
+        assert_eq!((report.succeeded, report.failed), (2, 1));
+        assert_eq!(report.results[2].line, 5);
+
+        let mut session = Session::new(Database::default());
+        let report = run_batch(&mut session, input, true);
+        assert!(report.saved);
+        assert_eq!(session.database().actions.len(), 1);
+    }
+}
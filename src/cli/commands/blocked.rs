@@ -0,0 +1,141 @@
+use crate::{
+    cli::{
+        output::{self, OutputFormat, Report},
+        parsers::short_id,
+    },
+    error::TensileResult,
+    service::{BlockedVision, Session},
+};
+use chrono::Local;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use uuid::Uuid;
+
+/// Blocked visions and what blocks them.
+#[derive(Serialize, Debug, Clone)]
+#[serde(transparent)]
+pub struct Blocked {
+    pub visions: Vec<BlockedVision>,
+    #[serde(skip)]
+    titles: HashMap<Uuid, String>,
+}
+
+impl Blocked {
+    fn waiting_on(&self, vision: &BlockedVision) -> String {
+        vision
+            .waiting_on
+            .iter()
+            .map(|id| match self.titles.get(id) {
+                Some(title) => format!("{} [{}]", title, short_id(id)),
+                None => short_id(id),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl fmt::Display for Blocked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "\n⊗ Blocked Visions")?;
+        writeln!(f, "{:─<50}", "")?;
+        if self.visions.is_empty() {
+            return writeln!(f, "  Nothing is blocked.");
+        }
+
+        for vision in &self.visions {
+            let marker = if vision.due { "⏰" } else { "  " };
+            write!(
+                f,
+                "{} {} [{}]",
+                marker,
+                vision.title,
+                short_id(&vision.vision_id)
+            )?;
+            match &vision.blocker {
+                Some(blocker) => {
+                    writeln!(f, " since {}", blocker.since.format("%Y-%m-%d"))?;
+                    writeln!(f, "      Reason: {}", blocker.reason)?;
+                    if let Some(condition) = &blocker.unblock_when {
+                        writeln!(f, "      Unblocks when: {}", condition)?;
+                    }
+                    if let Some(date) = blocker.unblock_on {
+                        writeln!(f, "      Re-evaluate on: {}", date)?;
+                    }
+                }
+                None => writeln!(f)?,
+            }
+            if !vision.waiting_on.is_empty() {
+                writeln!(f, "      Waiting on: {}", self.waiting_on(vision))?;
+            } else if vision.blocker.is_none() {
+                writeln!(f, "      Nothing recorded blocks it")?;
+            }
+            if vision.due {
+                writeln!(
+                    f,
+                    "      Still blocked? If not: tensile state transition {} in_progress",
+                    short_id(&vision.vision_id)
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Report for Blocked {
+    fn headers(&self) -> Vec<&'static str> {
+        vec![
+            "vision_id",
+            "title",
+            "reason",
+            "unblock_when",
+            "unblock_on",
+            "since",
+            "waiting_on",
+            "due",
+        ]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.visions
+            .iter()
+            .map(|v| {
+                let blocker = v.blocker.as_ref();
+                vec![
+                    v.vision_id.to_string(),
+                    v.title.clone(),
+                    blocker.map(|b| b.reason.clone()).unwrap_or_default(),
+                    blocker
+                        .and_then(|b| b.unblock_when.clone())
+                        .unwrap_or_default(),
+                    blocker
+                        .and_then(|b| b.unblock_on)
+                        .map(|d| d.to_string())
+                        .unwrap_or_default(),
+                    blocker.map(|b| b.since.to_rfc3339()).unwrap_or_default(),
+                    v.waiting_on
+                        .iter()
+                        .map(|id| id.to_string())
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                    v.due.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+pub fn blocked_command(session: &Session, output: OutputFormat) -> TensileResult<String> {
+    output::render(output, &blocked(session))
+}
+
+fn blocked(session: &Session) -> Blocked {
+    let visions = session.blocked(Local::now().date_naive());
+    let titles = visions
+        .iter()
+        .flat_map(|v| &v.waiting_on)
+        .filter_map(|id| session.vision(*id).ok())
+        .map(|v| (v.id, v.title.clone()))
+        .collect();
+    Blocked { visions, titles }
+}
@@ -0,0 +1,166 @@
+use crate::{
+    cli::output::{self, Confirmation, OutputFormat, Report},
+    config::{self, Config, Edited},
+    error::{TensileError, TensileResult},
+    service::check_config,
+};
+use clap::Subcommand;
+use serde::Serialize;
+use std::fmt;
+use std::path::PathBuf;
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Show every setting and its current value
+    #[command(visible_alias = "ls")]
+    List,
+
+    /// Show one setting, e.g. `patterns.recent_days`
+    Get { key: String },
+
+    /// Change one setting in config.toml
+    Set {
+        key: String,
+        /// New value, as TOML (`0.8`, `["!", "?"]`) or plain text
+        value: String,
+    },
+
+    /// Remove one setting from config.toml, so it takes its default
+    Unset { key: String },
+}
+
+impl ConfigCommands {
+    pub fn execute(self, output: OutputFormat) -> TensileResult<()> {
+        match self {
+            ConfigCommands::List => output::emit(output, &list_settings()?),
+            ConfigCommands::Get { key } => output::emit(output, &get_setting(key)?),
+            ConfigCommands::Set { key, value } => output::emit(output, &set_setting(key, value)?),
+            ConfigCommands::Unset { key } => output::emit(output, &unset_setting(key)?),
+        }
+    }
+}
+
+/// One setting and its value, written as TOML.
+#[derive(Serialize, Debug, Clone)]
+pub struct Setting {
+    pub key: String,
+    pub value: String,
+}
+
+impl fmt::Display for Setting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.value)
+    }
+}
+
+impl Report for Setting {
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["key", "value"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![self.key.clone(), self.value.clone()]]
+    }
+}
+
+/// The effective configuration and where it is read from.
+#[derive(Serialize, Debug, Clone)]
+pub struct SettingList {
+    pub path: PathBuf,
+    /// Whether the file exists; without it every setting is a default
+    pub exists: bool,
+    /// Why the file is invalid, in which case `settings` are its keys as
+    /// written rather than the effective configuration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub problem: Option<String>,
+    pub settings: Vec<Setting>,
+}
+
+impl fmt::Display for SettingList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let note = if self.exists { "" } else { ", not created yet" };
+        writeln!(f, "\nConfiguration ({}{}):", self.path.display(), note)?;
+        if let Some(problem) = &self.problem {
+            writeln!(f, "  ⚠ {}", problem)?;
+            writeln!(
+                f,
+                "  As written (fix with `tensile config set` or `tensile config unset`):"
+            )?;
+        }
+        for setting in &self.settings {
+            writeln!(f, "  {} = {}", setting.key, setting.value)?;
+        }
+        Ok(())
+    }
+}
+
+impl Report for SettingList {
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["key", "value"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.settings.iter().flat_map(|s| s.rows()).collect()
+    }
+}
+
+fn list_settings() -> TensileResult<SettingList> {
+    let path = Config::path();
+    let (entries, problem) = match Config::load().and_then(|c| check_config(&c).map(|_| c)) {
+        Ok(config) => (config.entries(), None),
+        Err(TensileError::Validation(problem)) => {
+            let text = std::fs::read_to_string(&path)?;
+            (config::raw_entries(&text)?, Some(problem))
+        }
+        Err(e) => return Err(e),
+    };
+
+    Ok(SettingList {
+        exists: path.exists(),
+        path,
+        problem,
+        settings: entries
+            .into_iter()
+            .map(|(key, value)| Setting { key, value })
+            .collect(),
+    })
+}
+
+fn get_setting(key: String) -> TensileResult<Setting> {
+    let value = Config::load()?.get(&key)?;
+    Ok(Setting { key, value })
+}
+
+fn set_setting(key: String, value: String) -> TensileResult<Confirmation> {
+    let message = match Config::set(&key, &value, check_config)? {
+        Edited::Valid(config) => format!("Set {} = {}", key, config.get(&key)?),
+        Edited::StillInvalid(problem) => {
+            warn_still_invalid(&problem);
+            format!("Set {}", key)
+        }
+    };
+    Ok(Confirmation::new(message))
+}
+
+fn unset_setting(key: String) -> TensileResult<Confirmation> {
+    let message = match Config::unset(&key, check_config)? {
+        Edited::Valid(config) => match config.get(&key) {
+            Ok(default) => format!("Unset {} (now {})", key, default),
+            Err(_) => format!("Unset {}", key),
+        },
+        Edited::StillInvalid(problem) => {
+            warn_still_invalid(&problem);
+            format!("Unset {}", key)
+        }
+    };
+    Ok(Confirmation::new(message))
+}
+
+/// The change was written, but the file needs more fixing.
+fn warn_still_invalid(problem: &str) {
+    eprintln!(
+        "⚠ {} is still invalid: {}",
+        Config::path().display(),
+        problem
+    );
+}
@@ -0,0 +1,200 @@
+use crate::{
+    error::{TensileError, TensileResult},
+    persistence::{EventJournal, JournalEntry},
+};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// How often `--follow` checks the journal for new entries.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Entries read from the journal at a time.
+const PAGE: usize = 100;
+
+/// Print journaled events as JSON Lines, then with `follow` keep printing
+/// new ones as any process publishes them, to stdout or to every client of
+/// a Unix socket.
+pub fn events_command(
+    follow: bool,
+    after: Option<i64>,
+    lines: Option<usize>,
+    socket: Option<PathBuf>,
+) -> TensileResult<()> {
+    let journal = EventJournal::new(None)?;
+
+    let mut sink = match socket {
+        Some(path) => Sink::socket(path)?,
+        None => Sink::Stdout,
+    };
+
+    let mut seq = match (after, lines) {
+        (Some(after), _) => after,
+        (None, lines) => {
+            let count = lines.unwrap_or(if follow { 0 } else { 10 });
+            journal.last_seq()?.saturating_sub(count as i64)
+        }
+    };
+
+    loop {
+        let entries = journal.after(seq, PAGE)?;
+        for entry in &entries {
+            if !sink.send(&json_line(entry)?)? {
+                return Ok(());
+            }
+            seq = entry.seq;
+        }
+
+        if entries.len() == PAGE {
+            continue;
+        }
+        if !follow {
+            return Ok(());
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn json_line(entry: &JournalEntry) -> TensileResult<String> {
+    serde_json::to_string(entry)
+        .map(|json| json + "\n")
+        .map_err(|e| TensileError::Serialization(e.to_string()))
+}
+
+/// Where followed events go.
+enum Sink {
+    Stdout,
+    #[cfg(unix)]
+    Socket(socket::Broadcast),
+}
+
+impl Sink {
+    #[cfg(unix)]
+    fn socket(path: PathBuf) -> TensileResult<Self> {
+        Ok(Sink::Socket(socket::Broadcast::bind(path)?))
+    }
+
+    #[cfg(not(unix))]
+    fn socket(_path: PathBuf) -> TensileResult<Self> {
+        Err(TensileError::Validation(
+            "--socket needs Unix domain sockets, which this platform lacks".to_string(),
+        ))
+    }
+
+    /// Write a line; false once nobody can read any more.
+    fn send(&mut self, line: &str) -> TensileResult<bool> {
+        match self {
+            Sink::Stdout => {
+                let mut stdout = io::stdout().lock();
+                match stdout
+                    .write_all(line.as_bytes())
+                    .and_then(|_| stdout.flush())
+                {
+                    Ok(()) => Ok(true),
+                    Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(false),
+                    Err(e) => Err(e.into()),
+                }
+            }
+            #[cfg(unix)]
+            Sink::Socket(broadcast) => {
+                broadcast.send(line);
+                Ok(true)
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+mod socket {
+    use crate::error::{TensileError, TensileResult};
+    use std::io::Write;
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// How long a client may go without reading before it is dropped.
+    const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+    /// Clients connected to the socket; each gets every line sent after it
+    /// connects.
+    pub struct Broadcast {
+        clients: Arc<Mutex<Vec<UnixStream>>>,
+    }
+
+    impl Broadcast {
+        pub fn bind(path: PathBuf) -> TensileResult<Self> {
+            if let Ok(metadata) = std::fs::symlink_metadata(&path) {
+                if !metadata.file_type().is_socket() {
+                    return Err(TensileError::Validation(format!(
+                        "{} exists and is not a socket",
+                        path.display()
+                    )));
+                }
+                if UnixStream::connect(&path).is_ok() {
+                    return Err(TensileError::Validation(format!(
+                        "Another process is already serving events on {}",
+                        path.display()
+                    )));
+                }
+                // Left behind by a process that did not shut down cleanly
+                std::fs::remove_file(&path)?;
+            }
+            let listener = UnixListener::bind(&path)?;
+            eprintln!("Serving events on {}", path.display());
+
+            let clients = Arc::new(Mutex::new(Vec::new()));
+            let accepted = Arc::clone(&clients);
+            std::thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    if stream.set_write_timeout(Some(WRITE_TIMEOUT)).is_err() {
+                        continue;
+                    }
+                    if let Ok(mut clients) = accepted.lock() {
+                        clients.push(stream);
+                    }
+                }
+            });
+            Ok(Broadcast { clients })
+        }
+
+        /// Send a line to every client, dropping those that have gone or
+        /// stopped reading.
+        pub fn send(&self, line: &str) {
+            if let Ok(mut clients) = self.clients.lock() {
+                clients.retain_mut(|client| client.write_all(line.as_bytes()).is_ok());
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::io::{BufRead, BufReader};
+
+        #[test]
+        fn a_client_that_stops_reading_is_dropped() {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("events.sock");
+            let broadcast = Broadcast::bind(path.clone()).unwrap();
+
+            let _stalled = UnixStream::connect(&path).unwrap();
+            let reader = UnixStream::connect(&path).unwrap();
+            while broadcast.clients.lock().unwrap().len() < 2 {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+
+            let lines = 2_000;
+            let reading =
+                std::thread::spawn(move || BufReader::new(reader).lines().take(lines).count());
+            let line = format!("{}\n", "x".repeat(1_000));
+            for _ in 0..lines {
+                broadcast.send(&line);
+            }
+
+            assert_eq!(broadcast.clients.lock().unwrap().len(), 1);
+            assert_eq!(reading.join().unwrap(), lines);
+        }
+    }
+}
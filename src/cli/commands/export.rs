@@ -0,0 +1,216 @@
+use crate::{
+    cli::output::{self, Confirmation, OutputFormat, Report},
+    error::{TensileError, TensileResult},
+    models::{Database, RecordKind},
+    persistence::{self, json_store, ImportMode, ImportSummary, RonStore},
+    service::Session,
+};
+use serde::Serialize;
+use std::fmt;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    Json,
+    Ron,
+}
+
+pub fn export_command(
+    format: Option<ExportFormat>,
+    dir: Option<PathBuf>,
+    file: Option<PathBuf>,
+    output: OutputFormat,
+) -> TensileResult<()> {
+    let format = match format {
+        Some(format) => format,
+        None if dir.is_some() => ExportFormat::Ron,
+        None => ExportFormat::Json,
+    };
+
+    match (format, dir) {
+        (ExportFormat::Ron, Some(dir)) => output::emit(output, &export_dir(dir)?),
+        (ExportFormat::Ron, None) => Err(TensileError::Validation(
+            "The ron format writes one file per vision; pass --dir".to_string(),
+        )),
+        (ExportFormat::Json, None) => match export_json(file)? {
+            Some(confirmation) => output::emit(output, &confirmation),
+            None => Ok(()),
+        },
+        (ExportFormat::Json, Some(_)) => Err(TensileError::Validation(
+            "--dir is only used by the ron format; use --file for json".to_string(),
+        )),
+    }
+}
+
+fn export_dir(dir: PathBuf) -> TensileResult<Confirmation> {
+    let db = persistence::load_database()?;
+    let summary = RonStore::new(dir.clone()).export(&db)?;
+
+    Ok(Confirmation::new(format!(
+        "Exported {} visions to {} ({} written, {} unchanged, {} removed)",
+        db.visions.len(),
+        dir.display(),
+        summary.written,
+        summary.unchanged,
+        summary.removed
+    )))
+}
+
+/// Write the export to `file`, or print it when there is none. The export is
+/// JSON already, so `--output` only applies to the confirmation.
+fn export_json(file: Option<PathBuf>) -> TensileResult<Option<Confirmation>> {
+    let db = persistence::load_database()?;
+    let json = json_store::to_json(&db)?;
+
+    match file {
+        Some(path) => {
+            std::fs::write(&path, json + "\n")?;
+            Ok(Some(Confirmation::new(format!(
+                "Exported {} visions, {} realities, {} actions to {}",
+                db.visions.len(),
+                db.realities.len(),
+                db.actions.len(),
+                path.display()
+            ))))
+        }
+        None => {
+            println!("{}", json);
+            Ok(None)
+        }
+    }
+}
+
+pub fn import_command(
+    file: Option<PathBuf>,
+    dir: Option<PathBuf>,
+    mode: ImportMode,
+    output: OutputFormat,
+) -> TensileResult<()> {
+    let (source, incoming) = match (file, dir) {
+        (Some(file), _) => (file.clone(), read_json(&file)?),
+        (None, Some(dir)) => {
+            if !dir.is_dir() {
+                return Err(TensileError::NotFound(format!(
+                    "Export directory does not exist: {}",
+                    dir.display()
+                )));
+            }
+            (dir.clone(), RonStore::new(dir).read()?)
+        }
+        (None, None) => {
+            return Err(TensileError::Validation(
+                "Pass a JSON file or --dir".to_string(),
+            ))
+        }
+    };
+
+    let mut session = Session::open()?;
+    let summary = session.import(incoming, mode);
+    session.commit()?;
+
+    output::emit(output, &ImportReport::new(source, summary))
+}
+
+fn read_json(path: &PathBuf) -> TensileResult<Database> {
+    if !path.exists() {
+        return Err(TensileError::NotFound(format!(
+            "Import file does not exist: {}",
+            path.display()
+        )));
+    }
+
+    let contents = std::fs::read_to_string(path)?;
+    json_store::from_json(&contents)
+}
+
+/// What an import changed.
+#[derive(Serialize, Debug, Clone)]
+pub struct ImportReport {
+    pub source: PathBuf,
+    pub added: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub skipped: usize,
+    pub removed: usize,
+    pub remapped: Vec<RemappedId>,
+}
+
+/// An imported id that already belonged to another local record.
+#[derive(Serialize, Debug, Clone)]
+pub struct RemappedId {
+    pub kind: RecordKind,
+    pub from: Uuid,
+    pub to: Uuid,
+}
+
+impl ImportReport {
+    fn new(source: PathBuf, summary: ImportSummary) -> Self {
+        ImportReport {
+            source,
+            added: summary.added,
+            updated: summary.updated,
+            unchanged: summary.unchanged,
+            skipped: summary.skipped,
+            removed: summary.removed,
+            remapped: summary
+                .remapped
+                .into_iter()
+                .map(|(kind, from, to)| RemappedId { kind, from, to })
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "✓ Imported from {}: {} added, {} updated, {} unchanged, {} skipped, {} removed",
+            self.source.display(),
+            self.added,
+            self.updated,
+            self.unchanged,
+            self.skipped,
+            self.removed
+        )?;
+
+        if !self.remapped.is_empty() {
+            writeln!(
+                f,
+                "\n⚠ {} id(s) already belonged to other local records and were reassigned:",
+                self.remapped.len()
+            )?;
+            for remap in &self.remapped {
+                writeln!(f, "  {} {} → {}", remap.kind, remap.from, remap.to)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Report for ImportReport {
+    fn headers(&self) -> Vec<&'static str> {
+        vec![
+            "source",
+            "added",
+            "updated",
+            "unchanged",
+            "skipped",
+            "removed",
+            "remapped",
+        ]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.source.display().to_string(),
+            self.added.to_string(),
+            self.updated.to_string(),
+            self.unchanged.to_string(),
+            self.skipped.to_string(),
+            self.removed.to_string(),
+            self.remapped.len().to_string(),
+        ]]
+    }
+}
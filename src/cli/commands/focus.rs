@@ -0,0 +1,103 @@
+use crate::{
+    cli::{
+        output::{self, Confirmation, OutputFormat, Report},
+        parsers::{short_id, VisionRef},
+    },
+    error::TensileResult,
+    service::{BlockedVision, Session},
+};
+use chrono::Local;
+use serde::Serialize;
+use std::fmt;
+use uuid::Uuid;
+
+/// The vision `.` currently refers to.
+#[derive(Serialize, Debug, Clone)]
+pub struct Focus {
+    pub vision_id: Option<Uuid>,
+    pub title: Option<String>,
+    /// Blocked visions it is time to look at again
+    pub due_for_review: Vec<BlockedVision>,
+}
+
+impl fmt::Display for Focus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.vision_id, &self.title) {
+            (Some(id), Some(title)) => writeln!(f, "Focus: {} [{}]", title, short_id(id)),
+            _ => writeln!(f, "No current focus (set one with: tensile focus <vision>)"),
+        }?;
+
+        if self.due_for_review.is_empty() {
+            return Ok(());
+        }
+        writeln!(f, "\n⏰ Time to re-evaluate:")?;
+        for vision in &self.due_for_review {
+            let why = match vision.blocker.as_ref().and_then(|b| b.unblock_on) {
+                Some(date) => format!("re-evaluate on {}", date),
+                None => "everything it waited on is achieved".to_string(),
+            };
+            writeln!(
+                f,
+                "  {} [{}] ({})",
+                vision.title,
+                short_id(&vision.vision_id),
+                why
+            )?;
+            writeln!(
+                f,
+                "    Still blocked? If not: tensile state transition {} in_progress",
+                short_id(&vision.vision_id)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Report for Focus {
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["vision_id", "title"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.vision_id.map(|id| id.to_string()).unwrap_or_default(),
+            self.title.clone().unwrap_or_default(),
+        ]]
+    }
+}
+
+pub fn focus_command(
+    session: &mut Session,
+    vision: Option<VisionRef>,
+    clear: bool,
+    output: OutputFormat,
+) -> TensileResult<String> {
+    if clear {
+        session.clear_focus();
+        return output::render(output, &Confirmation::new("Focus cleared"));
+    }
+
+    let Some(vision) = vision else {
+        let focus = session.current_focus();
+        return output::render(
+            output,
+            &Focus {
+                vision_id: focus.map(|v| v.id),
+                title: focus.map(|v| v.title.clone()),
+                due_for_review: session
+                    .blocked(Local::now().date_naive())
+                    .into_iter()
+                    .filter(|v| v.due)
+                    .collect(),
+            },
+        );
+    };
+
+    let id = session.resolve(&vision)?;
+    let vision = session.focus(id)?;
+
+    output::render(
+        output,
+        &Confirmation::new(format!("Focus set: {} [{}]", vision.title, short_id(&id))).with_id(id),
+    )
+}
@@ -1,10 +1,17 @@
 use clap::Subcommand;
-use uuid::Uuid;
+use serde::Serialize;
+use std::fmt;
 
 use crate::{
-    engine::{PatternAnalyzer, TensionCalculator},
-    error::{TensileError, TensileResult},
-    persistence,
+    cli::{
+        output::{self, OutputFormat, Report},
+        parsers::{short_id, VisionRef, VISION_HELP},
+    },
+    config::DisplayConfig,
+    engine::{dependency::CriticalPath, tension::VisionTension},
+    error::TensileResult,
+    models::VisionState,
+    service::{Session, Summary, VisionDetail},
 };
 
 #[derive(Subcommand)]
@@ -15,124 +22,352 @@ pub enum MetricsCommands {
 
     /// Show detailed metrics for a vision
     #[command(visible_alias = "d")]
-    Detail { vision_id: String },
+    Detail {
+        #[arg(help = VISION_HELP)]
+        vision_id: VisionRef,
+    },
 
     /// Show summary dashboard
     #[command(visible_alias = "s")]
     Summary,
+
+    /// Show which upstream vision to attack first
+    ///
+    /// Ranks the unfinished visions that others wait on, but that wait on
+    /// nothing themselves, by the stakes they unblock. Given a vision, only
+    /// what stands between you and it counts.
+    #[command(visible_alias = "cp")]
+    CriticalPath {
+        #[arg(help = VISION_HELP)]
+        vision_id: Option<VisionRef>,
+    },
 }
 
 impl MetricsCommands {
-    pub fn execute(self) -> TensileResult<()> {
+    pub fn execute(self, session: &mut Session, output: OutputFormat) -> TensileResult<String> {
         match self {
-            MetricsCommands::Priority => show_priorities(),
-            MetricsCommands::Detail { vision_id } => show_detail(vision_id),
-            MetricsCommands::Summary => show_summary(),
+            MetricsCommands::Priority => output::render(output, &show_priorities(session)?),
+            MetricsCommands::Detail { vision_id } => {
+                output::render(output, &show_detail(session, vision_id)?)
+            }
+            MetricsCommands::Summary => output::render(output, &show_summary(session)?),
+            MetricsCommands::CriticalPath { vision_id } => {
+                output::render(output, &show_critical_paths(session, vision_id)?)
+            }
         }
     }
 }
 
-fn show_priorities() -> TensileResult<()> {
-    let db = persistence::load_database()?;
+/// Visions ranked by tension, highest first. Serializes as a plain array.
+#[derive(Serialize, Debug, Clone)]
+#[serde(transparent)]
+pub struct Priorities {
+    pub visions: Vec<VisionTension>,
+    #[serde(skip)]
+    display: DisplayConfig,
+}
 
-    let tensions = TensionCalculator::calculate_all_tensions(&db);
-    let sorted = TensionCalculator::sort_by_tension(tensions);
+impl fmt::Display for Priorities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "\n📊 Priority Visions (by Tension):")?;
+        writeln!(f, "{:-^60}", "Rank | Vision | Tension | Actions")?;
 
-    println!("\n📊 Priority Visions (by Tension):");
-    println!("{:-^60}", "Rank | Vision | Tension | Actions");
+        for (rank, tension) in self.visions.iter().enumerate() {
+            let priority_icon = self.display.priority_icon(rank);
 
-    for (rank, tension) in sorted.iter().enumerate() {
-        let priority_icon = match rank {
-            0 => "🔴",
-            1 => "🟠",
-            2 => "🟡",
-            _ => "  ",
-        };
+            writeln!(
+                f,
+                "{} {} | {} | {:.0}% | {}",
+                priority_icon,
+                rank + 1,
+                truncate(&tension.vision_title, 25),
+                tension.tension_score,
+                tension.action_count
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Report for Priorities {
+    fn headers(&self) -> Vec<&'static str> {
+        vec![
+            "rank",
+            "vision_id",
+            "title",
+            "tension",
+            "actions",
+            "days_active",
+        ]
+    }
 
-        println!(
-            "{} {} | {} | {:.0}% | {}",
-            priority_icon,
-            rank + 1,
-            truncate(&tension.vision_title, 25),
-            tension.tension_score,
-            tension.action_count
-        );
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.visions
+            .iter()
+            .enumerate()
+            .map(|(rank, t)| {
+                vec![
+                    (rank + 1).to_string(),
+                    t.vision_id.to_string(),
+                    t.vision_title.clone(),
+                    format!("{:.1}", t.tension_score),
+                    t.action_count.to_string(),
+                    t.days_active.to_string(),
+                ]
+            })
+            .collect()
     }
+}
+
+/// Upstream visions to attack first. Serializes as a plain array.
+#[derive(Serialize, Debug, Clone)]
+#[serde(transparent)]
+pub struct CriticalPaths {
+    pub paths: Vec<CriticalPath>,
+    #[serde(skip)]
+    target: Option<String>,
+}
 
-    Ok(())
+impl fmt::Display for CriticalPaths {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.target {
+            Some(target) => writeln!(f, "\n🧭 Critical Path to: {}", target)?,
+            None => writeln!(f, "\n🧭 Critical Path")?,
+        }
+        writeln!(f, "{:─<50}", "")?;
+        if self.paths.is_empty() {
+            return writeln!(f, "  Nothing is waiting on another vision.");
+        }
+
+        for (rank, path) in self.paths.iter().enumerate() {
+            let marker = if rank == 0 { "🎯" } else { "  " };
+            writeln!(
+                f,
+                "{} {}. {} [{}] unblocks {} (stakes {})",
+                marker,
+                rank + 1,
+                path.title,
+                short_id(&path.vision_id),
+                visions(path.unblocks),
+                path.stakes_unblocked
+            )?;
+            writeln!(f, "      {}", chain(path))?;
+        }
+        Ok(())
+    }
 }
 
-fn show_detail(vision_id: String) -> TensileResult<()> {
-    let uuid = Uuid::parse_str(&vision_id)
-        .map_err(|_| TensileError::Parse(format!("Invalid UUID: {}", vision_id)))?;
+impl Report for CriticalPaths {
+    fn headers(&self) -> Vec<&'static str> {
+        vec![
+            "rank",
+            "vision_id",
+            "title",
+            "unblocks",
+            "stakes_unblocked",
+            "path",
+        ]
+    }
 
-    let db = persistence::load_database()?;
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.paths
+            .iter()
+            .enumerate()
+            .map(|(rank, p)| {
+                vec![
+                    (rank + 1).to_string(),
+                    p.vision_id.to_string(),
+                    p.title.clone(),
+                    p.unblocks.to_string(),
+                    p.stakes_unblocked.to_string(),
+                    chain(p),
+                ]
+            })
+            .collect()
+    }
+}
 
-    let vision = db
-        .visions
+fn chain(path: &CriticalPath) -> String {
+    path.path
         .iter()
-        .find(|v| v.id == uuid)
-        .ok_or_else(|| TensileError::NotFound(format!("Vision not found: {}", vision_id)))?;
-
-    if let Some(tension) = TensionCalculator::calculate_vision_tension(&db, uuid) {
-        if let Some(metrics) = PatternAnalyzer::get_detailed_metrics(&db, uuid) {
-            println!("\n📈 Detailed Metrics: {}", vision.title);
-            println!("{:─<50}", "");
-            println!("  State: {}", vision.state);
-            println!("  Tension Score: {:.1}%", tension.tension_score);
-            println!("  Days Active: {}", tension.days_active);
-            println!();
-            println!("  Pattern: {:?}", metrics.pattern);
-            println!("  Success Rate: {:.1}%", metrics.success_rate * 100.0);
-            println!("  Total Actions: {}", metrics.total_actions);
-            println!("  Recent (7 days): {}", metrics.recent_actions);
-            println!("  Velocity: {:.2} actions/day", metrics.velocity);
-            println!();
-
-            if let Some(desc) = &vision.description {
-                println!("  Description: {}", desc);
+        .map(|step| step.title.as_str())
+        .collect::<Vec<_>>()
+        .join(" → ")
+}
+
+fn visions(count: usize) -> String {
+    match count {
+        1 => "1 vision".to_string(),
+        n => format!("{} visions", n),
+    }
+}
+
+impl fmt::Display for VisionDetail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (Some(tension), Some(metrics)) = (&self.tension, &self.metrics) else {
+            return Ok(());
+        };
+
+        writeln!(f, "\n📈 Detailed Metrics: {}", self.title)?;
+        writeln!(f, "{:─<50}", "")?;
+        writeln!(f, "  State: {}", self.state)?;
+        writeln!(f, "  Stakes: {}", self.stakes)?;
+        writeln!(f, "  Tension Score: {:.1}%", tension.tension_score)?;
+        writeln!(f, "  Days Active: {}", tension.days_active)?;
+        writeln!(f)?;
+
+        if let Some(rollup) = &self.rollup {
+            writeln!(
+                f,
+                "  Sub-visions: {} of {} achieved",
+                rollup.achieved_children, rollup.children
+            )?;
+            writeln!(f, "  Rolled-up Progress: {:.0}%", rollup.progress * 100.0)?;
+            writeln!(f, "  Rolled-up Tension: {:.1}%", rollup.tension)?;
+            if rollup.all_achieved() && self.state != VisionState::Achieved {
+                writeln!(
+                    f,
+                    "  💡 Every sub-vision is achieved; mark this one achieved with `tensile vision complete {}`",
+                    short_id(&self.vision_id)
+                )?;
             }
+            writeln!(f)?;
         }
+        writeln!(f, "  Pattern: {:?}", metrics.pattern)?;
+        writeln!(f, "  Success Rate: {:.1}%", metrics.success_rate * 100.0)?;
+        writeln!(f, "  Total Actions: {}", metrics.total_actions)?;
+        writeln!(
+            f,
+            "  Recent ({} days): {}",
+            metrics.recent_days, metrics.recent_actions
+        )?;
+        writeln!(f, "  Velocity: {:.2} actions/day", metrics.velocity)?;
+        writeln!(f)?;
+
+        if let Some(desc) = &self.description {
+            writeln!(f, "  Description: {}", desc)?;
+        }
+        Ok(())
     }
+}
 
-    Ok(())
+impl Report for VisionDetail {
+    fn headers(&self) -> Vec<&'static str> {
+        vec![
+            "vision_id",
+            "title",
+            "state",
+            "stakes",
+            "tension",
+            "days_active",
+            "pattern",
+            "success_rate",
+            "total_actions",
+            "recent_actions",
+            "velocity",
+            "progress",
+            "rolled_up_tension",
+        ]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        let tension = self.tension.as_ref();
+        let metrics = self.metrics.as_ref();
+        vec![vec![
+            self.vision_id.to_string(),
+            self.title.clone(),
+            self.state.to_string(),
+            self.stakes.to_string(),
+            optional(tension.map(|t| format!("{:.1}", t.tension_score))),
+            optional(tension.map(|t| t.days_active)),
+            optional(metrics.map(|m| format!("{:?}", m.pattern))),
+            optional(metrics.map(|m| m.success_rate)),
+            optional(metrics.map(|m| m.total_actions)),
+            optional(metrics.map(|m| m.recent_actions)),
+            optional(metrics.map(|m| format!("{:.2}", m.velocity))),
+            optional(self.rollup.as_ref().map(|r| format!("{:.2}", r.progress))),
+            optional(self.rollup.as_ref().map(|r| format!("{:.1}", r.tension))),
+        ]]
+    }
 }
 
-fn show_summary() -> TensileResult<()> {
-    let db = persistence::load_database()?;
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "\n📊 Summary Dashboard")?;
+        writeln!(f, "{:─<50}", "")?;
+        writeln!(f, "  Total Visions: {}", self.total_visions)?;
+        writeln!(f, "  Active Visions: {}", self.active_visions)?;
+        writeln!(f, "  Total Actions Logged: {}", self.total_actions)?;
+        writeln!(f, "  Total Reality Assessments: {}", self.total_realities)?;
+        writeln!(f, "  Average Tension: {:.1}%", self.average_tension)?;
+        if self.achievements.achieved > 0 {
+            writeln!(f, "  Achieved Visions: {}", self.achievements)?;
+        }
 
-    let total_visions = db.visions.len();
-    let active_visions = db
-        .visions
-        .iter()
-        .filter(|v| !v.completed && matches!(v.state, crate::models::VisionState::InProgress))
-        .count();
-    let total_actions = db.actions.len();
-    let total_reality = db.realities.len();
-
-    let avg_tension = {
-        let tensions = TensionCalculator::calculate_all_tensions(&db);
-        if tensions.is_empty() {
-            0.0
-        } else {
-            tensions.iter().map(|t| t.tension_score).sum::<f32>() / tensions.len() as f32
+        if let Some(priority) = &self.top_priority {
+            writeln!(f, "\n  🎯 Top Priority: {}", priority.vision_title)?;
+            writeln!(f, "     Tension: {:.1}%", priority.tension_score)?;
         }
-    };
-
-    println!("\n📊 Summary Dashboard");
-    println!("{:─<50}", "");
-    println!("  Total Visions: {}", total_visions);
-    println!("  Active Visions: {}", active_visions);
-    println!("  Total Actions Logged: {}", total_actions);
-    println!("  Total Reality Assessments: {}", total_reality);
-    println!("  Average Tension: {:.1}%", avg_tension);
-
-    if let Some(priority) = TensionCalculator::get_priority_vision(&db) {
-        println!("\n  🎯 Top Priority: {}", priority.vision_title);
-        println!("     Tension: {:.1}%", priority.tension_score);
+        Ok(())
     }
+}
+
+impl Report for Summary {
+    fn headers(&self) -> Vec<&'static str> {
+        vec![
+            "total_visions",
+            "active_visions",
+            "total_actions",
+            "total_realities",
+            "average_tension",
+            "top_priority",
+            "achieved_visions",
+        ]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.total_visions.to_string(),
+            self.active_visions.to_string(),
+            self.total_actions.to_string(),
+            self.total_realities.to_string(),
+            format!("{:.1}", self.average_tension),
+            optional(self.top_priority.as_ref().map(|t| &t.vision_title)),
+            self.achievements.achieved.to_string(),
+        ]]
+    }
+}
+
+fn show_priorities(session: &Session) -> TensileResult<Priorities> {
+    Ok(Priorities {
+        visions: session.priorities(),
+        display: session.config().display.clone(),
+    })
+}
+
+fn show_detail(session: &Session, vision_id: VisionRef) -> TensileResult<VisionDetail> {
+    session.vision_detail(session.resolve(&vision_id)?)
+}
+
+fn show_summary(session: &Session) -> TensileResult<Summary> {
+    Ok(session.summary())
+}
+
+fn show_critical_paths(
+    session: &Session,
+    vision_id: Option<VisionRef>,
+) -> TensileResult<CriticalPaths> {
+    let target = vision_id.map(|id| session.resolve(&id)).transpose()?;
+    Ok(CriticalPaths {
+        paths: session.critical_paths(target)?,
+        target: target
+            .map(|id| session.vision(id).map(|v| v.title.clone()))
+            .transpose()?,
+    })
+}
 
-    Ok(())
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
 }
 
 fn truncate(s: &str, len: usize) -> String {
@@ -1,12 +1,31 @@
 pub mod action;
+pub mod batch;
+pub mod blocked;
+pub mod config;
+pub mod events;
+pub mod export;
+pub mod focus;
 pub mod metrics;
 pub mod pattern;
 pub mod reality;
 pub mod state;
+pub mod sync;
+pub mod template;
 pub mod vision;
 
-use crate::error::TensileResult;
+use crate::{
+    cli::{
+        complete,
+        output::{self, OutputFormat, Report},
+    },
+    error::{TensileError, TensileResult},
+    persistence::ImportMode,
+    service::Session,
+};
 use clap::Subcommand;
+use serde::Serialize;
+use std::fmt;
+use std::path::PathBuf;
 
 #[derive(Subcommand)]
 pub enum Commands {
@@ -34,35 +53,188 @@ pub enum Commands {
     #[command(subcommand)]
     Metrics(metrics::MetricsCommands),
 
+    /// Save vision trees as templates and create visions from them
+    #[command(subcommand)]
+    Template(template::TemplateCommands),
+
+    /// Show or set the vision `.` refers to
+    Focus {
+        #[arg(help = crate::cli::parsers::VISION_HELP)]
+        vision: Option<crate::cli::parsers::VisionRef>,
+        /// Clear the current focus
+        #[arg(long, conflicts_with = "vision")]
+        clear: bool,
+    },
+
+    /// List blocked visions with what blocks them
+    Blocked,
+
     /// Show structural coaching prompts
     #[command(visible_alias = "p")]
     Prompt,
 
+    /// Export the database as JSON, or as one RON file per vision
+    Export {
+        /// Output format (defaults to ron with --dir, else json)
+        #[arg(long, value_enum, ignore_case = true)]
+        format: Option<export::ExportFormat>,
+        /// Directory to write `<vision id>.ron` files into
+        #[arg(long)]
+        dir: Option<PathBuf>,
+        /// File to write JSON to (defaults to stdout)
+        #[arg(long, short)]
+        file: Option<PathBuf>,
+    },
+
+    /// Import a JSON export file or an exported directory
+    Import {
+        /// JSON file written by `tensile export --format json`
+        #[arg(required_unless_present = "dir", conflicts_with = "dir")]
+        file: Option<PathBuf>,
+        /// Directory holding `<vision id>.ron` files
+        #[arg(long)]
+        dir: Option<PathBuf>,
+        /// How to combine with local data
+        #[arg(long, value_enum, ignore_case = true, default_value_t = ImportMode::Merge)]
+        mode: ImportMode,
+    },
+
+    /// Synchronize with other devices
+    #[command(subcommand)]
+    Sync(sync::SyncCommands),
+
+    /// Run many commands from a file or stdin, saving once at the end
+    Batch {
+        /// File with one command per line: CLI arguments or JSON (defaults to stdin)
+        file: Option<PathBuf>,
+        /// Apply the lines that succeed instead of rolling back on the first error
+        #[arg(long)]
+        continue_on_error: bool,
+    },
+
+    /// Print published events as JSON Lines, optionally following new ones
+    Events {
+        /// Keep running and print events as other commands publish them
+        #[arg(long, short)]
+        follow: bool,
+        /// Start after this sequence number, e.g. the last `seq` seen
+        #[arg(long, conflicts_with = "lines")]
+        after: Option<i64>,
+        /// Past events to print first (default: 10, or none with --follow)
+        #[arg(long, short = 'n')]
+        lines: Option<usize>,
+        /// Serve the stream to clients of this Unix domain socket instead of stdout
+        #[arg(long, requires = "follow")]
+        socket: Option<PathBuf>,
+    },
+
+    /// Show or change settings in config.toml
+    #[command(subcommand)]
+    Config(config::ConfigCommands),
+
+    /// Print a shell completion script: bash, zsh, fish, elvish or powershell
+    Completions {
+        /// Shell to complete for
+        #[arg(value_enum, ignore_case = true)]
+        shell: clap_complete::Shell,
+    },
+
+    /// Complete the last of the given words (used by the completion scripts)
+    #[command(name = "__complete", hide = true)]
+    Complete {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        words: Vec<String>,
+    },
+
     /// Launch interactive TUI dashboard
     #[cfg(feature = "tui")]
     Dashboard,
 }
 
 impl Commands {
-    pub fn execute(self) -> TensileResult<()> {
+    pub fn execute(self, output: OutputFormat) -> TensileResult<()> {
         match self {
-            Commands::Vision(cmd) => cmd.execute(),
-            Commands::Reality(cmd) => cmd.execute(),
-            Commands::Action(cmd) => cmd.execute(),
-            Commands::Pattern(cmd) => cmd.execute(),
-            Commands::State(cmd) => cmd.execute(),
-            Commands::Metrics(cmd) => cmd.execute(),
-            Commands::Prompt => crate::cli::commands::prompt_command(),
+            Commands::Export { format, dir, file } => {
+                export::export_command(format, dir, file, output)
+            }
+            Commands::Import { file, dir, mode } => export::import_command(file, dir, mode, output),
+            Commands::Sync(cmd) => cmd.execute(output),
+            Commands::Batch {
+                file,
+                continue_on_error,
+            } => batch::batch_command(file, continue_on_error, output),
+            Commands::Events {
+                follow,
+                after,
+                lines,
+                socket,
+            } => events::events_command(follow, after, lines, socket),
+            Commands::Config(cmd) => cmd.execute(output),
+            Commands::Completions { shell } => complete::print_script(shell),
+            Commands::Complete { words } => complete::print_candidates(&words),
             #[cfg(feature = "tui")]
             Commands::Dashboard => {
                 let rt = tokio::runtime::Runtime::new()?;
                 rt.block_on(crate::tui::run_dashboard())
             }
+            command => {
+                let mut session = Session::open()?;
+                let rendered = command.apply(&mut session, output)?;
+                session.commit()?;
+                print!("{}", rendered);
+                Ok(())
+            }
         }
     }
+
+    /// Run a command on an open session without saving, returning its
+    /// rendered output. Commands that load and save data themselves, such
+    /// as `import` and `sync`, are rejected.
+    pub fn apply(self, session: &mut Session, output: OutputFormat) -> TensileResult<String> {
+        match self {
+            Commands::Vision(cmd) => cmd.execute(session, output),
+            Commands::Reality(cmd) => cmd.execute(session, output),
+            Commands::Action(cmd) => cmd.execute(session, output),
+            Commands::Pattern(cmd) => cmd.execute(session, output),
+            Commands::State(cmd) => cmd.execute(session, output),
+            Commands::Metrics(cmd) => cmd.execute(session, output),
+            Commands::Template(cmd) => cmd.execute(session, output),
+            Commands::Focus { vision, clear } => {
+                focus::focus_command(session, vision, clear, output)
+            }
+            Commands::Blocked => blocked::blocked_command(session, output),
+            Commands::Prompt => prompt_command(output),
+            _ => Err(TensileError::Validation(
+                "Only vision, reality, action, pattern, state, metrics, template, focus, blocked and prompt commands can run in a batch"
+                    .to_string(),
+            )),
+        }
+    }
+}
+
+/// A coaching prompt.
+#[derive(Serialize, Debug, Clone)]
+pub struct Prompt {
+    pub prompt: String,
+}
+
+impl fmt::Display for Prompt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "\n> {}", self.prompt)
+    }
+}
+
+impl Report for Prompt {
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["prompt"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![self.prompt.clone()]]
+    }
 }
 
-pub fn prompt_command() -> TensileResult<()> {
+pub fn prompt_command(output: OutputFormat) -> TensileResult<String> {
     let prompts = [
         "What would achieving this vision enable?",
         "What obstacles stand between current reality and vision?",
@@ -77,6 +249,10 @@ pub fn prompt_command() -> TensileResult<()> {
         .as_secs() as usize
         % prompts.len()];
 
-    println!("\n> {}", prompt);
-    Ok(())
+    output::render(
+        output,
+        &Prompt {
+            prompt: prompt.to_string(),
+        },
+    )
 }
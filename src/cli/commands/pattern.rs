@@ -1,64 +1,157 @@
 use crate::{
-    engine::PatternAnalyzer,
-    error::{TensileError, TensileResult},
-    persistence,
+    cli::{
+        output::{self, OutputFormat, Report},
+        parsers::{VisionRef, VISION_HELP},
+    },
+    engine::pattern::AchievementStats,
+    error::TensileResult,
+    service::{PatternReport, Session, VisionPattern},
 };
 use clap::Subcommand;
-use uuid::Uuid;
+use serde::Serialize;
+use std::fmt;
 
 #[derive(Subcommand)]
 pub enum PatternCommands {
     /// Analyze patterns in actions (advancing vs oscillating)
     #[command(visible_alias = "c")]
     Check {
-        #[arg(long)]
-        vision: Option<String>,
+        #[arg(long, help = VISION_HELP)]
+        vision: Option<VisionRef>,
     },
 }
 
 impl PatternCommands {
-    pub fn execute(self) -> TensileResult<()> {
+    pub fn execute(self, session: &mut Session, output: OutputFormat) -> TensileResult<String> {
         match self {
-            PatternCommands::Check { vision } => check_patterns(vision),
+            PatternCommands::Check { vision: Some(id) } => {
+                output::render(output, &check_pattern(session, id)?)
+            }
+            PatternCommands::Check { vision: None } => {
+                output::render(output, &check_patterns(session)?)
+            }
         }
     }
 }
 
-fn check_patterns(vision_id: Option<String>) -> TensileResult<()> {
-    let db = persistence::load_database()?;
+impl fmt::Display for PatternReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "\nPattern Analysis for: {}", self.title)?;
+        writeln!(f, "  Pattern: {:?}", self.pattern)?;
 
-    if let Some(id) = vision_id {
-        let uuid = Uuid::parse_str(&id)
-            .map_err(|_| TensileError::Parse(format!("Invalid UUID: {}", id)))?;
+        if let Some(success_rate) = self.success_rate {
+            writeln!(f, "  Success Rate: {:.1}%", success_rate * 100.0)?;
+            writeln!(f, "  Total Actions: {}", self.total_actions)?;
+        }
+        if let Some(days) = self.days_to_achieve {
+            writeln!(f, "  Achieved in: {} days", days)?;
+        }
+        if let Some(retrospective) = &self.retrospective {
+            writeln!(f, "  What made it work: {}", retrospective)?;
+        }
+        Ok(())
+    }
+}
 
-        let vision = db
-            .visions
-            .iter()
-            .find(|v| v.id == uuid)
-            .ok_or_else(|| TensileError::NotFound(format!("Vision not found: {}", id)))?;
+/// One line, e.g. "2 (41.5 days on average, 12.0 actions each, 84%
+/// successful)".
+impl fmt::Display for AchievementStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let details: Vec<String> = [
+            self.average_days
+                .map(|days| format!("{:.1} days on average", days)),
+            self.average_actions
+                .map(|actions| format!("{:.1} actions each", actions)),
+            self.success_rate
+                .map(|rate| format!("{:.0}% successful", rate * 100.0)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
 
-        let pattern = PatternAnalyzer::analyze_vision_pattern(&db, uuid);
-        println!("\nPattern Analysis for: {}", vision.title);
-        println!("  Pattern: {:?}", pattern);
+        write!(f, "{}", self.achieved)?;
+        if !details.is_empty() {
+            write!(f, " ({})", details.join(", "))?;
+        }
+        Ok(())
+    }
+}
 
-        let actions: Vec<_> = db.actions.iter().filter(|a| a.vision_id == uuid).collect();
+impl Report for PatternReport {
+    fn headers(&self) -> Vec<&'static str> {
+        vec![
+            "vision_id",
+            "title",
+            "pattern",
+            "success_rate",
+            "total_actions",
+            "days_to_achieve",
+        ]
+    }
 
-        if !actions.is_empty() {
-            let success_rate =
-                actions.iter().filter(|a| a.success).count() as f32 / actions.len() as f32;
-            println!("  Success Rate: {:.1}%", success_rate * 100.0);
-            println!("  Total Actions: {}", actions.len());
-        }
-    } else {
-        println!("\nPattern Analysis - All Visions:");
-        let patterns = PatternAnalyzer::analyze_all_patterns(&db);
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.vision_id.to_string(),
+            self.title.clone(),
+            format!("{:?}", self.pattern),
+            self.success_rate.map(|r| r.to_string()).unwrap_or_default(),
+            self.total_actions.to_string(),
+            self.days_to_achieve
+                .map(|d| d.to_string())
+                .unwrap_or_default(),
+        ]]
+    }
+}
 
-        for (vision_id, pattern) in patterns {
-            if let Some(vision) = db.visions.iter().find(|v| v.id == vision_id) {
-                println!("  {} - {:?}", vision.title, pattern);
-            }
+/// Pattern of every vision.
+#[derive(Serialize, Debug, Clone)]
+#[serde(transparent)]
+pub struct PatternOverview {
+    pub visions: Vec<VisionPattern>,
+    /// Shown in plain output; `metrics summary` has it as JSON
+    #[serde(skip)]
+    pub achievements: AchievementStats,
+}
+
+impl fmt::Display for PatternOverview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "\nPattern Analysis - All Visions:")?;
+        for vision in &self.visions {
+            writeln!(f, "  {} - {:?}", vision.title, vision.pattern)?;
         }
+        if self.achievements.achieved > 0 {
+            writeln!(f, "\n  Achieved Visions: {}", self.achievements)?;
+        }
+        Ok(())
+    }
+}
+
+impl Report for PatternOverview {
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["vision_id", "title", "pattern"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.visions
+            .iter()
+            .map(|v| {
+                vec![
+                    v.vision_id.to_string(),
+                    v.title.clone(),
+                    format!("{:?}", v.pattern),
+                ]
+            })
+            .collect()
     }
+}
+
+fn check_pattern(session: &Session, id: VisionRef) -> TensileResult<PatternReport> {
+    session.pattern(session.resolve(&id)?)
+}
 
-    Ok(())
+fn check_patterns(session: &Session) -> TensileResult<PatternOverview> {
+    Ok(PatternOverview {
+        visions: session.patterns(),
+        achievements: session.achievements(),
+    })
 }
@@ -1,17 +1,27 @@
 use crate::{
-    error::{TensileError, TensileResult},
+    cli::{
+        editor::{self, Template},
+        output::{self, timestamp, Confirmation, OutputFormat, Report},
+        parsers::{VisionRef, VISION_HELP},
+    },
+    error::TensileResult,
     models::RealityAssessment,
-    persistence,
+    service::Session,
 };
 use clap::Subcommand;
-use uuid::Uuid;
+use serde::Serialize;
+use std::fmt;
 
 #[derive(Subcommand)]
 pub enum RealityCommands {
     /// Update current reality assessment
     #[command(visible_alias = "u")]
     Update {
-        vision_id: String,
+        #[arg(help = VISION_HELP)]
+        vision_id: VisionRef,
+        /// Write the assessment in $EDITOR (the default when no text is given)
+        #[arg(long, short)]
+        edit: bool,
         #[arg(trailing_var_arg = true)]
         entry: Vec<String>,
     },
@@ -19,105 +29,131 @@ pub enum RealityCommands {
     /// View latest reality assessments
     #[command(visible_alias = "v")]
     View {
-        #[arg(long)]
-        vision: Option<String>,
+        #[arg(long, help = VISION_HELP)]
+        vision: Option<VisionRef>,
     },
 
     /// Show most recent assessment
     #[command(visible_alias = "l")]
     Latest {
-        #[arg(long)]
-        vision: Option<String>,
+        #[arg(long, help = VISION_HELP)]
+        vision: Option<VisionRef>,
     },
 }
 
 impl RealityCommands {
-    pub fn execute(self) -> TensileResult<()> {
+    pub fn execute(self, session: &mut Session, output: OutputFormat) -> TensileResult<String> {
         match self {
-            RealityCommands::Update { vision_id, entry } => update_reality(vision_id, entry),
-            RealityCommands::View { vision } => view_reality(vision),
-            RealityCommands::Latest { vision } => latest_reality(vision),
+            RealityCommands::Update {
+                vision_id,
+                edit,
+                entry,
+            } => output::render(output, &update_reality(session, vision_id, edit, entry)?),
+            RealityCommands::View { vision } => {
+                output::render(output, &view_reality(session, vision)?)
+            }
+            RealityCommands::Latest { vision } => {
+                output::render(output, &latest_reality(session, vision)?)
+            }
         }
     }
 }
 
-fn update_reality(vision_id: String, entry: Vec<String>) -> TensileResult<()> {
-    let uuid = Uuid::parse_str(&vision_id)
-        .map_err(|_| TensileError::Parse(format!("Invalid UUID: {}", vision_id)))?;
+/// Assessments in recorded order. Serializes as a plain array.
+#[derive(Serialize, Debug, Clone)]
+#[serde(transparent)]
+pub struct RealityList {
+    pub assessments: Vec<RealityAssessment>,
+}
+
+impl fmt::Display for RealityList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "\nReality Assessments:")?;
+        for assessment in &self.assessments {
+            writeln!(
+                f,
+                "  [{}] {}",
+                timestamp(&assessment.timestamp),
+                assessment.entry
+            )?;
+        }
+        Ok(())
+    }
+}
 
-    let mut db = persistence::load_database()?;
+impl Report for RealityList {
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["timestamp", "vision_id", "entry"]
+    }
 
-    let vision_exists = db.visions.iter().any(|v| v.id == uuid);
-    if !vision_exists {
-        return Err(TensileError::NotFound(format!(
-            "Vision not found: {}",
-            vision_id
-        )));
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.assessments.iter().map(row).collect()
     }
+}
 
-    let assessment = RealityAssessment::new(uuid, entry.join(" "));
-    db.realities.push(assessment);
-    persistence::save_database(&db)?;
-    println!("✓ Reality assessment recorded");
-    Ok(())
+/// The newest assessment. Serializes as the assessment, or null.
+#[derive(Serialize, Debug, Clone)]
+#[serde(transparent)]
+pub struct LatestReality {
+    pub assessment: Option<RealityAssessment>,
 }
 
-fn view_reality(vision_id: Option<String>) -> TensileResult<()> {
-    let db = persistence::load_database()?;
-
-    let filter_uuid = if let Some(id) = vision_id {
-        Some(
-            Uuid::parse_str(&id)
-                .map_err(|_| TensileError::Parse(format!("Invalid UUID: {}", id)))?,
-        )
-    } else {
-        None
-    };
-
-    println!("\nReality Assessments:");
-    for assessment in &db.realities {
-        if filter_uuid.is_none() || filter_uuid == Some(assessment.vision_id) {
-            println!(
-                "  [{}] {}",
-                assessment.timestamp.format("%Y-%m-%d %H:%M"),
-                assessment.entry
-            );
+impl fmt::Display for LatestReality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.assessment {
+            Some(latest) => {
+                writeln!(f, "\nLatest Assessment:")?;
+                writeln!(f, "  [{}] {}", timestamp(&latest.timestamp), latest.entry)
+            }
+            None => writeln!(f, "No reality assessments found"),
         }
     }
-
-    Ok(())
 }
 
-fn latest_reality(vision_id: Option<String>) -> TensileResult<()> {
-    let db = persistence::load_database()?;
-
-    let filter_uuid = if let Some(id) = vision_id {
-        Some(
-            Uuid::parse_str(&id)
-                .map_err(|_| TensileError::Parse(format!("Invalid UUID: {}", id)))?,
-        )
-    } else {
-        None
-    };
-
-    let mut assessments: Vec<_> = db
-        .realities
-        .iter()
-        .filter(|a| filter_uuid.is_none() || filter_uuid == Some(a.vision_id))
-        .collect();
-
-    assessments.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-
-    if let Some(latest) = assessments.first() {
-        println!("\nLatest Assessment:");
-        println!(
-            "  [{}] {}",
-            latest.timestamp.format("%Y-%m-%d %H:%M"),
-            latest.entry
-        );
-    } else {
-        println!("No reality assessments found");
+impl Report for LatestReality {
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["timestamp", "vision_id", "entry"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.assessment.iter().map(row).collect()
     }
+}
+
+fn row(assessment: &RealityAssessment) -> Vec<String> {
+    vec![
+        assessment.timestamp.to_rfc3339(),
+        assessment.vision_id.to_string(),
+        assessment.entry.clone(),
+    ]
+}
+
+fn update_reality(
+    session: &mut Session,
+    vision_id: VisionRef,
+    edit: bool,
+    entry: Vec<String>,
+) -> TensileResult<Confirmation> {
+    let id = session.resolve(&vision_id)?;
+    let entry = editor::text_or_edit(entry, edit, || {
+        Template::for_vision(session, id, "Current reality of")
+    })?;
+    let assessment = session.record_reality(id, entry)?;
+    Ok(Confirmation::new("Reality assessment recorded").with_id(assessment.id))
+}
+
+fn view_reality(session: &Session, vision_id: Option<VisionRef>) -> TensileResult<RealityList> {
+    let filter = vision_id.map(|id| session.resolve(&id)).transpose()?;
+
+    Ok(RealityList {
+        assessments: session.realities(filter).into_iter().cloned().collect(),
+    })
+}
+
+fn latest_reality(session: &Session, vision_id: Option<VisionRef>) -> TensileResult<LatestReality> {
+    let filter = vision_id.map(|id| session.resolve(&id)).transpose()?;
 
-    Ok(())
+    Ok(LatestReality {
+        assessment: session.latest_reality(filter).cloned(),
+    })
 }
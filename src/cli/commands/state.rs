@@ -1,10 +1,18 @@
 use crate::{
+    cli::{
+        editor,
+        output::{self, Confirmation, OutputFormat, Report},
+        parsers::{parse_date, short_id, VisionRef, DATE_HELP, VISION_HELP},
+    },
     engine::StateMachine,
     error::{TensileError, TensileResult},
-    models::VisionState,
-    persistence,
+    models::{Blocker, Vision, VisionState},
+    service::{Session, Transition},
 };
+use chrono::{DateTime, NaiveDate, Utc};
 use clap::Subcommand;
+use serde::Serialize;
+use std::fmt;
 use std::str::FromStr;
 use uuid::Uuid;
 
@@ -12,69 +20,275 @@ use uuid::Uuid;
 pub enum StateCommands {
     /// Show current state of a vision
     #[command(visible_alias = "s")]
-    Show { vision_id: String },
+    Show {
+        #[arg(help = VISION_HELP)]
+        vision_id: VisionRef,
+    },
 
     /// Transition vision to a new state
     #[command(visible_alias = "t")]
-    Transition { vision_id: String, state: String },
+    Transition {
+        #[arg(help = VISION_HELP)]
+        vision_id: VisionRef,
+        state: String,
+        /// What blocks it (when moving to blocked)
+        #[arg(long)]
+        reason: Option<String>,
+        /// What has to happen for it to move again (when moving to blocked)
+        #[arg(long)]
+        unblock_when: Option<String>,
+        /// When to take another look at it (when moving to blocked)
+        #[arg(long, value_parser = parse_date, help = DATE_HELP)]
+        unblock_on: Option<NaiveDate>,
+    },
 }
 
 impl StateCommands {
-    pub fn execute(self) -> TensileResult<()> {
+    pub fn execute(self, session: &mut Session, output: OutputFormat) -> TensileResult<String> {
         match self {
-            StateCommands::Show { vision_id } => show_state(vision_id),
-            StateCommands::Transition { vision_id, state } => transition_state(vision_id, state),
+            StateCommands::Show { vision_id } => {
+                output::render(output, &show_state(session, vision_id)?)
+            }
+            StateCommands::Transition {
+                vision_id,
+                state,
+                reason,
+                unblock_when,
+                unblock_on,
+            } => output::render(
+                output,
+                &transition_state(session, vision_id, state, reason, unblock_when, unblock_on)?,
+            ),
         }
     }
 }
 
-fn show_state(vision_id: String) -> TensileResult<()> {
-    let uuid = Uuid::parse_str(&vision_id)
-        .map_err(|_| TensileError::Parse(format!("Invalid UUID: {}", vision_id)))?;
+/// Where a vision is in its lifecycle.
+#[derive(Serialize, Debug, Clone)]
+pub struct StateReport {
+    pub vision_id: Uuid,
+    pub title: String,
+    pub state: VisionState,
+    pub created_at: DateTime<Utc>,
+    pub achieved_at: Option<DateTime<Utc>>,
+    pub retrospective: Option<String>,
+    pub blocker: Option<Blocker>,
+    /// Visions it waits on
+    pub depends_on: Vec<RelatedVision>,
+    /// Visions waiting on it
+    pub enables: Vec<RelatedVision>,
+}
 
-    let db = persistence::load_database()?;
-    let vision = db
-        .visions
-        .iter()
-        .find(|v| v.id == uuid)
-        .ok_or_else(|| TensileError::NotFound(format!("Vision not found: {}", vision_id)))?;
-
-    println!("\nState of Vision: {}", vision.title);
-    println!("  Current State: {}", vision.state);
-    println!("  Created: {}", vision.created_at.format("%Y-%m-%d"));
-    println!(
-        "  Completed: {}",
-        if vision.completed { "Yes" } else { "No" }
-    );
-
-    Ok(())
+/// A vision linked by a dependency.
+#[derive(Serialize, Debug, Clone)]
+pub struct RelatedVision {
+    pub vision_id: Uuid,
+    pub title: String,
+    pub state: VisionState,
+}
+
+impl RelatedVision {
+    fn of(vision: &Vision) -> Self {
+        RelatedVision {
+            vision_id: vision.id,
+            title: vision.title.clone(),
+            state: vision.state.clone(),
+        }
+    }
+}
+
+impl fmt::Display for RelatedVision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} [{}] {}",
+            self.title,
+            short_id(&self.vision_id),
+            self.state
+        )
+    }
+}
+
+impl fmt::Display for StateReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "\nState of Vision: {}", self.title)?;
+        writeln!(f, "  Current State: {}", self.state)?;
+        writeln!(f, "  Created: {}", self.created_at.format("%Y-%m-%d"))?;
+        if let Some(achieved_at) = self.achieved_at {
+            writeln!(f, "  Achieved: {}", achieved_at.format("%Y-%m-%d"))?;
+        }
+        if let Some(retrospective) = &self.retrospective {
+            writeln!(f, "  What made it work: {}", retrospective)?;
+        }
+        if let Some(blocker) = &self.blocker {
+            writeln!(
+                f,
+                "  Blocked since {}: {}",
+                blocker.since.format("%Y-%m-%d"),
+                blocker.reason
+            )?;
+            if let Some(condition) = &blocker.unblock_when {
+                writeln!(f, "  Unblocks when: {}", condition)?;
+            }
+            if let Some(date) = blocker.unblock_on {
+                writeln!(f, "  Re-evaluate on: {}", date)?;
+            }
+        }
+
+        if !self.depends_on.is_empty() {
+            writeln!(f, "  Depends on:")?;
+            for vision in &self.depends_on {
+                writeln!(f, "    {}", vision)?;
+            }
+        } else if self.state == VisionState::Blocked && self.blocker.is_none() {
+            writeln!(
+                f,
+                "  Depends on: nothing recorded (add what blocks it with `tensile vision depends`)"
+            )?;
+        }
+        if !self.enables.is_empty() {
+            writeln!(f, "  Enables:")?;
+            for vision in &self.enables {
+                writeln!(f, "    {}", vision)?;
+            }
+        }
+        Ok(())
+    }
 }
 
-fn transition_state(vision_id: String, new_state_str: String) -> TensileResult<()> {
-    let uuid = Uuid::parse_str(&vision_id)
-        .map_err(|_| TensileError::Parse(format!("Invalid UUID: {}", vision_id)))?;
+impl Report for StateReport {
+    fn headers(&self) -> Vec<&'static str> {
+        vec![
+            "vision_id",
+            "title",
+            "state",
+            "created_at",
+            "achieved_at",
+            "retrospective",
+            "blocked_reason",
+            "depends_on",
+            "enables",
+        ]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.vision_id.to_string(),
+            self.title.clone(),
+            self.state.to_string(),
+            self.created_at.to_rfc3339(),
+            self.achieved_at.map(|t| t.to_rfc3339()).unwrap_or_default(),
+            self.retrospective.clone().unwrap_or_default(),
+            self.blocker
+                .as_ref()
+                .map(|b| b.reason.clone())
+                .unwrap_or_default(),
+            ids(&self.depends_on),
+            ids(&self.enables),
+        ]]
+    }
+}
 
-    let new_state = VisionState::from_str(&new_state_str).map_err(TensileError::Parse)?;
+fn show_state(session: &Session, vision_id: VisionRef) -> TensileResult<StateReport> {
+    let vision = session.vision(session.resolve(&vision_id)?)?;
 
-    let mut db = persistence::load_database()?;
-    let (old_state_str, new_state_str_display) = {
-        let vision = db
-            .visions
-            .iter_mut()
-            .find(|v| v.id == uuid)
-            .ok_or_else(|| TensileError::NotFound(format!("Vision not found: {}", vision_id)))?;
+    Ok(StateReport {
+        vision_id: vision.id,
+        title: vision.title.clone(),
+        state: vision.state.clone(),
+        created_at: vision.created_at,
+        achieved_at: vision.achieved_at,
+        retrospective: vision.retrospective.clone(),
+        blocker: vision.blocker.clone(),
+        depends_on: session
+            .dependencies(vision.id)?
+            .into_iter()
+            .map(RelatedVision::of)
+            .collect(),
+        enables: session
+            .dependents(vision.id)
+            .into_iter()
+            .map(RelatedVision::of)
+            .collect(),
+    })
+}
 
-        let old_state = vision.state.clone();
-        StateMachine::validate_transition(&old_state, &new_state)?;
+fn ids(visions: &[RelatedVision]) -> String {
+    visions
+        .iter()
+        .map(|v| v.vision_id.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
 
-        vision.state = new_state.clone();
-        (old_state.to_string(), new_state.to_string())
+fn transition_state(
+    session: &mut Session,
+    vision_id: VisionRef,
+    state: String,
+    reason: Option<String>,
+    unblock_when: Option<String>,
+    unblock_on: Option<NaiveDate>,
+) -> TensileResult<Confirmation> {
+    let state = VisionState::from_str(&state).map_err(TensileError::Parse)?;
+    let id = session.resolve(&vision_id)?;
+    let transition = if state == VisionState::Blocked {
+        let blocker = blocker(session, id, reason, unblock_when, unblock_on)?;
+        session.block_vision(id, blocker)?
+    } else if reason.is_some() || unblock_when.is_some() || unblock_on.is_some() {
+        return Err(TensileError::Validation(
+            "--reason, --unblock-when and --unblock-on only apply when moving to blocked"
+                .to_string(),
+        ));
+    } else if state == VisionState::Achieved {
+        let from = session.vision(id)?.state.clone();
+        super::vision::complete(session, id, None, None)?;
+        Transition {
+            vision_id: id,
+            from,
+            to: state,
+        }
+    } else {
+        session.transition(id, state)?
     };
 
-    persistence::save_database(&db)?;
-    println!(
-        "✓ State transitioned: {} -> {}",
-        old_state_str, new_state_str_display
-    );
-    Ok(())
+    Ok(Confirmation::new(format!(
+        "State transitioned: {} -> {}",
+        transition.from, transition.to
+    ))
+    .with_id(id))
+}
+
+/// What blocks a vision about to be blocked. A reason is required, asked for
+/// in a terminal, unless unachieved dependencies already say what it waits on.
+fn blocker(
+    session: &Session,
+    id: Uuid,
+    reason: Option<String>,
+    unblock_when: Option<String>,
+    unblock_on: Option<NaiveDate>,
+) -> TensileResult<Option<Blocker>> {
+    // Refuse before asking anything
+    StateMachine::validate_transition(&session.vision(id)?.state, &VisionState::Blocked)?;
+
+    let reason = match reason {
+        Some(reason) => Some(reason),
+        None => editor::prompt("What is blocking it?")?,
+    };
+    match reason.filter(|r| !r.trim().is_empty()) {
+        Some(reason) => Ok(Some(Blocker {
+            unblock_when,
+            unblock_on,
+            ..Blocker::new(reason)
+        })),
+        None if unblock_when.is_none()
+            && unblock_on.is_none()
+            && session.dependencies(id)?.iter().any(|v| !v.is_achieved()) =>
+        {
+            Ok(None)
+        }
+        None => Err(TensileError::Validation(
+            "Say what blocks it with --reason, or record the visions it waits on with `tensile vision depends`"
+                .to_string(),
+        )),
+    }
 }
@@ -0,0 +1,394 @@
+use crate::{
+    cli::output::{self, timestamp, Confirmation, OutputFormat, Report},
+    config,
+    error::TensileResult,
+    service,
+    sync::{self, Conflict, FolderSync, MergeOutcome, Side, SyncStore},
+};
+#[cfg(feature = "cloud")]
+use crate::{
+    cloud::{CloudConfig, CloudSync, RecordCounts},
+    persistence,
+};
+use clap::Subcommand;
+use serde::Serialize;
+use std::fmt;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+#[derive(Subcommand)]
+pub enum SyncCommands {
+    /// Merge remote changes, then upload the local database
+    #[cfg(feature = "cloud")]
+    Push,
+
+    /// Merge the server copy into the local database
+    #[cfg(feature = "cloud")]
+    Pull,
+
+    /// Compare local and remote record counts
+    #[cfg(feature = "cloud")]
+    #[command(visible_alias = "s")]
+    Status,
+
+    /// Exchange change logs through a shared folder
+    #[command(visible_alias = "f")]
+    Folder {
+        /// Shared folder (defaults to $TENSILE_SYNC_DIR)
+        #[arg(long)]
+        dir: Option<PathBuf>,
+    },
+
+    /// List conflicts awaiting resolution
+    #[command(visible_alias = "c")]
+    Conflicts,
+
+    /// Resolve a conflict by keeping the local or remote version
+    #[command(visible_alias = "r")]
+    Resolve {
+        /// Conflict id, or a unique prefix of at least 4 characters
+        id: String,
+        /// Side to keep
+        #[arg(long, value_enum, ignore_case = true)]
+        keep: Side,
+    },
+}
+
+impl SyncCommands {
+    pub fn execute(self, output: OutputFormat) -> TensileResult<()> {
+        let store = SyncStore::new();
+
+        match self {
+            #[cfg(feature = "cloud")]
+            SyncCommands::Push => {
+                let report = cloud_runtime(|sync, rt| rt.block_on(push(sync, &store)))?;
+                output::emit(output, &report)
+            }
+            #[cfg(feature = "cloud")]
+            SyncCommands::Pull => {
+                let report = cloud_runtime(|sync, rt| rt.block_on(pull(sync, &store)))?;
+                output::emit(output, &report)
+            }
+            #[cfg(feature = "cloud")]
+            SyncCommands::Status => {
+                let report = cloud_runtime(|sync, rt| rt.block_on(status(sync, &store)))?;
+                output::emit(output, &report)
+            }
+            SyncCommands::Folder { dir } => output::emit(output, &folder_sync(&store, dir)?),
+            SyncCommands::Conflicts => output::emit(output, &list_conflicts(&store)?),
+            SyncCommands::Resolve { id, keep } => {
+                output::emit(output, &resolve_conflict(&store, id, keep)?)
+            }
+        }
+    }
+}
+
+/// Result of merging another copy of the database into this one.
+#[derive(Serialize, Debug, Clone)]
+pub struct MergeReport {
+    /// "Pushed" or "Merged", for the plain text
+    #[serde(skip)]
+    verb: &'static str,
+    pub visions: usize,
+    pub realities: usize,
+    pub actions: usize,
+    /// Conflicts found by this merge; each kept the local version
+    pub conflicts: Vec<Conflict>,
+}
+
+impl MergeReport {
+    fn new(verb: &'static str, outcome: MergeOutcome) -> Self {
+        MergeReport {
+            verb,
+            visions: outcome.merged.visions.len(),
+            realities: outcome.merged.realities.len(),
+            actions: outcome.merged.actions.len(),
+            conflicts: outcome.conflicts,
+        }
+    }
+}
+
+impl fmt::Display for MergeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "✓ {}: {} visions, {} realities, {} actions",
+            self.verb, self.visions, self.realities, self.actions
+        )?;
+
+        if !self.conflicts.is_empty() {
+            writeln!(
+                f,
+                "\n⚠ {} conflict(s) kept the local version:",
+                self.conflicts.len()
+            )?;
+            for conflict in &self.conflicts {
+                write_conflict(f, conflict)?;
+            }
+            writeln!(
+                f,
+                "\nResolve with: tensile sync resolve <id> --keep local|remote"
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Report for MergeReport {
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["visions", "realities", "actions", "conflicts"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.visions.to_string(),
+            self.realities.to_string(),
+            self.actions.to_string(),
+            self.conflicts.len().to_string(),
+        ]]
+    }
+}
+
+/// Record counts on both sides of the cloud sync.
+#[cfg(feature = "cloud")]
+#[derive(Serialize, Debug, Clone)]
+pub struct StatusReport {
+    pub server: String,
+    pub user: String,
+    pub local: RecordCounts,
+    pub remote: RecordCounts,
+    pub conflicts: usize,
+}
+
+#[cfg(feature = "cloud")]
+impl fmt::Display for StatusReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "\nSync Status")?;
+        writeln!(f, "  Server: {}", self.server)?;
+        writeln!(f, "  User: {}", self.user)?;
+        writeln!(f, "{:─<50}", "")?;
+        writeln!(f, "  {:<12} {:>8} {:>8}", "", "Local", "Remote")?;
+        for (name, local, remote) in self.counts() {
+            writeln!(f, "  {:<12} {:>8} {:>8}", name, local, remote)?;
+        }
+
+        if self.conflicts > 0 {
+            writeln!(f, "\n  ⚠ {} unresolved conflict(s)", self.conflicts)
+        } else if self.local == self.remote {
+            writeln!(f, "\n  ✓ Record counts match")
+        } else {
+            writeln!(f, "\n  ⚠ Local and remote differ")
+        }
+    }
+}
+
+#[cfg(feature = "cloud")]
+impl StatusReport {
+    fn counts(&self) -> [(&'static str, usize, usize); 3] {
+        [
+            ("Visions", self.local.visions, self.remote.visions),
+            ("Realities", self.local.realities, self.remote.realities),
+            ("Actions", self.local.actions, self.remote.actions),
+        ]
+    }
+}
+
+#[cfg(feature = "cloud")]
+impl Report for StatusReport {
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["records", "local", "remote"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.counts()
+            .iter()
+            .map(|(name, local, remote)| {
+                vec![name.to_lowercase(), local.to_string(), remote.to_string()]
+            })
+            .collect()
+    }
+}
+
+/// Result of exchanging change logs through a shared folder.
+#[derive(Serialize, Debug, Clone)]
+pub struct FolderReport {
+    pub peers: Vec<PeerReport>,
+    /// Local changes published, or `None` if held back by conflicts
+    pub exported: Option<usize>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PeerReport {
+    pub device: Uuid,
+    pub merge: MergeReport,
+}
+
+impl fmt::Display for FolderReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.peers.is_empty() {
+            writeln!(f, "No other devices in the sync folder yet")?;
+        }
+        for peer in &self.peers {
+            writeln!(f, "\nDevice {}:", peer.device)?;
+            write!(f, "{}", peer.merge)?;
+        }
+
+        match self.exported {
+            Some(0) => writeln!(f, "\n✓ No local changes to publish"),
+            Some(count) => writeln!(f, "\n✓ Published {} local change(s)", count),
+            None => writeln!(
+                f,
+                "\n⚠ Local changes not published until conflicts are resolved"
+            ),
+        }
+    }
+}
+
+impl Report for FolderReport {
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["device", "visions", "realities", "actions", "conflicts"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.peers
+            .iter()
+            .map(|peer| {
+                let mut row = vec![peer.device.to_string()];
+                row.extend(peer.merge.rows().remove(0));
+                row
+            })
+            .collect()
+    }
+}
+
+/// Conflicts awaiting resolution. Serializes as a plain array.
+#[derive(Serialize, Debug, Clone)]
+#[serde(transparent)]
+pub struct ConflictList {
+    pub conflicts: Vec<Conflict>,
+}
+
+impl fmt::Display for ConflictList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.conflicts.is_empty() {
+            return writeln!(f, "No sync conflicts");
+        }
+
+        writeln!(f, "\nSync Conflicts:")?;
+        for conflict in &self.conflicts {
+            write_conflict(f, conflict)?;
+        }
+        Ok(())
+    }
+}
+
+impl Report for ConflictList {
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["kind", "id", "local", "remote"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.conflicts
+            .iter()
+            .map(|c| {
+                vec![
+                    c.kind.to_string(),
+                    c.id.to_string(),
+                    describe(&c.local),
+                    describe(&c.remote),
+                ]
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "cloud")]
+fn cloud_runtime<F, T>(f: F) -> TensileResult<T>
+where
+    F: FnOnce(&CloudSync, &tokio::runtime::Runtime) -> TensileResult<T>,
+{
+    let sync = CloudSync::new(CloudConfig::from_env()?);
+    let rt = tokio::runtime::Runtime::new()?;
+    f(&sync, &rt)
+}
+
+#[cfg(feature = "cloud")]
+async fn push(sync: &CloudSync, store: &SyncStore) -> TensileResult<MergeReport> {
+    let outcome = sync.push_merge(store).await?;
+    Ok(MergeReport::new("Pushed", outcome))
+}
+
+#[cfg(feature = "cloud")]
+async fn pull(sync: &CloudSync, store: &SyncStore) -> TensileResult<MergeReport> {
+    let outcome = sync.pull_merge(store).await?;
+    Ok(MergeReport::new("Merged", outcome))
+}
+
+#[cfg(feature = "cloud")]
+async fn status(sync: &CloudSync, store: &SyncStore) -> TensileResult<StatusReport> {
+    let db = persistence::load_database()?;
+    let status = sync.status(&db).await?;
+
+    Ok(StatusReport {
+        server: status.api_url,
+        user: status.user_id,
+        local: status.local,
+        remote: status.remote,
+        conflicts: store.load_conflicts()?.len(),
+    })
+}
+
+fn folder_sync(store: &SyncStore, dir: Option<PathBuf>) -> TensileResult<FolderReport> {
+    let folder = match dir {
+        Some(dir) => FolderSync::new(dir, config::device_id()?)?,
+        None => FolderSync::from_env()?,
+    };
+
+    let report = folder.run(store)?;
+
+    Ok(FolderReport {
+        peers: report
+            .peers
+            .into_iter()
+            .map(|peer| PeerReport {
+                device: peer.device,
+                merge: MergeReport::new("Merged", peer.outcome),
+            })
+            .collect(),
+        exported: report.exported,
+    })
+}
+
+fn list_conflicts(store: &SyncStore) -> TensileResult<ConflictList> {
+    Ok(ConflictList {
+        conflicts: store.load_conflicts()?,
+    })
+}
+
+fn describe(record: &Option<sync::Record>) -> String {
+    match record {
+        Some(r) => format!("{} (updated {})", r.summary(), timestamp(&r.updated_at())),
+        None => "deleted".to_string(),
+    }
+}
+
+fn write_conflict(f: &mut fmt::Formatter<'_>, conflict: &Conflict) -> fmt::Result {
+    writeln!(f, "  {} {}", conflict.kind, conflict.id)?;
+    writeln!(f, "     local:  {}", describe(&conflict.local))?;
+    writeln!(f, "     remote: {}", describe(&conflict.remote))
+}
+
+fn resolve_conflict(store: &SyncStore, id: String, side: Side) -> TensileResult<Confirmation> {
+    let id = service::resolve_conflict(&store.load_conflicts()?, &id)?;
+    let conflict = sync::resolve(store, id, side)?;
+    Ok(Confirmation::new(format!(
+        "Resolved {} {} (kept {})",
+        conflict.kind,
+        conflict.id,
+        match side {
+            Side::Local => "local",
+            Side::Remote => "remote",
+        }
+    ))
+    .with_id(conflict.id))
+}
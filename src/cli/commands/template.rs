@@ -0,0 +1,196 @@
+use crate::{
+    cli::{
+        output::{self, Confirmation, OutputFormat, Report},
+        parsers::{short_id, VisionRef, VISION_HELP},
+    },
+    error::TensileResult,
+    models::VisionTemplate,
+    persistence::TemplateStore,
+    service::Session,
+};
+use clap::Subcommand;
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Subcommand)]
+pub enum TemplateCommands {
+    /// Save a vision and its sub-visions as a template
+    Save {
+        #[arg(help = VISION_HELP)]
+        vision_id: VisionRef,
+        /// Template name: letters, digits, spaces, '-' and '_'
+        name: String,
+        /// Replace an existing template of the same name
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Create fresh visions from a template
+    Apply {
+        name: String,
+        /// Vision to create them under (default: top level)
+        #[arg(long, help = VISION_HELP)]
+        parent: Option<VisionRef>,
+    },
+
+    /// List saved templates
+    #[command(visible_alias = "ls")]
+    List,
+
+    /// Delete a saved template
+    #[command(visible_alias = "rm")]
+    Delete { name: String },
+}
+
+impl TemplateCommands {
+    pub fn execute(self, session: &mut Session, output: OutputFormat) -> TensileResult<String> {
+        let store = TemplateStore::open();
+
+        match self {
+            TemplateCommands::Save {
+                vision_id,
+                name,
+                force,
+            } => output::render(
+                output,
+                &save_template(session, &store, vision_id, name, force)?,
+            ),
+            TemplateCommands::Apply { name, parent } => {
+                output::render(output, &apply_template(session, &store, name, parent)?)
+            }
+            TemplateCommands::List => output::render(output, &list_templates(&store)?),
+            TemplateCommands::Delete { name } => {
+                store.delete(&name)?;
+                output::render(
+                    output,
+                    &Confirmation::new(format!("Deleted template {}", name)),
+                )
+            }
+        }
+    }
+}
+
+/// A saved template and the tree it creates.
+#[derive(Serialize, Debug, Clone)]
+pub struct TemplateSummary {
+    pub name: String,
+    pub visions: usize,
+    pub template: VisionTemplate,
+}
+
+/// Saved templates, sorted by name. Serializes as a plain array.
+#[derive(Serialize, Debug, Clone)]
+#[serde(transparent)]
+pub struct TemplateList {
+    pub templates: Vec<TemplateSummary>,
+}
+
+impl fmt::Display for TemplateList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "\nTemplates:")?;
+        if self.templates.is_empty() {
+            return writeln!(f, "  (none)");
+        }
+        for summary in &self.templates {
+            writeln!(f, "  {}  {}", summary.name, summary.template.title)?;
+            for child in &summary.template.children {
+                write_outline(f, child, 1)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_outline(
+    f: &mut fmt::Formatter<'_>,
+    template: &VisionTemplate,
+    depth: usize,
+) -> fmt::Result {
+    writeln!(f, "{}- {}", "  ".repeat(depth + 1), template.title)?;
+    for child in &template.children {
+        write_outline(f, child, depth + 1)?;
+    }
+    Ok(())
+}
+
+impl Report for TemplateList {
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["name", "title", "visions"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.templates
+            .iter()
+            .map(|s| {
+                vec![
+                    s.name.clone(),
+                    s.template.title.clone(),
+                    s.visions.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+fn save_template(
+    session: &Session,
+    store: &TemplateStore,
+    vision_id: VisionRef,
+    name: String,
+    force: bool,
+) -> TensileResult<Confirmation> {
+    let id = session.resolve(&vision_id)?;
+    let template = session.capture_template(id)?;
+    let path = store.save(&name, &template, force)?;
+
+    Ok(Confirmation::new(format!(
+        "Saved template {}: {} ({}) to {}",
+        name,
+        template.title,
+        visions(template.size()),
+        path.display()
+    ))
+    .with_id(id))
+}
+
+fn apply_template(
+    session: &mut Session,
+    store: &TemplateStore,
+    name: String,
+    parent: Option<VisionRef>,
+) -> TensileResult<Confirmation> {
+    let template = store.load(&name)?;
+    let parent = parent.map(|p| session.resolve(&p)).transpose()?;
+    let created = session.apply_template(&template, parent)?;
+    let root = &created[0];
+
+    Ok(Confirmation::new(format!(
+        "Created {} from template {}: {} [{}]",
+        visions(created.len()),
+        name,
+        root.title,
+        short_id(&root.id)
+    ))
+    .with_id(root.id))
+}
+
+fn list_templates(store: &TemplateStore) -> TensileResult<TemplateList> {
+    Ok(TemplateList {
+        templates: store
+            .list()?
+            .into_iter()
+            .map(|(name, template)| TemplateSummary {
+                name,
+                visions: template.size(),
+                template,
+            })
+            .collect(),
+    })
+}
+
+fn visions(count: usize) -> String {
+    match count {
+        1 => "1 vision".to_string(),
+        n => format!("{} visions", n),
+    }
+}
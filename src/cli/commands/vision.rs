@@ -1,9 +1,18 @@
 use crate::{
-    error::{TensileError, TensileResult},
-    models::Vision,
-    persistence,
+    cli::{
+        editor::{self, Template},
+        output::{self, Confirmation, OutputFormat, Report},
+        parsers::{short_id, VisionRef, VISION_HELP},
+    },
+    config::StateIcons,
+    engine::StateMachine,
+    error::TensileResult,
+    models::{Vision, VisionState},
+    service::{Deletion, Session, TreeNode, TreeOptions},
 };
 use clap::Subcommand;
+use serde::Serialize;
+use std::fmt;
 use uuid::Uuid;
 
 #[derive(Subcommand)]
@@ -15,147 +24,584 @@ pub enum VisionCommands {
     /// View the vision tree
     #[command(visible_alias = "t")]
     Tree {
+        /// Show only this vision and its sub-visions
+        #[arg(long, help = VISION_HELP)]
+        root: Option<VisionRef>,
+        /// Number of levels to show
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        depth: Option<u64>,
+        /// Leave out achieved visions (their sub-visions move up)
+        #[arg(long)]
+        hide_achieved: bool,
+        /// Draw connectors with ASCII characters only
         #[arg(long)]
-        format: Option<String>,
+        ascii: bool,
+        /// Same as the global --output, which takes precedence when given
+        #[arg(long, value_enum, ignore_case = true)]
+        format: Option<OutputFormat>,
+    },
+
+    /// Change a vision's title
+    #[command(visible_alias = "r")]
+    Rename {
+        #[arg(help = VISION_HELP)]
+        id: VisionRef,
+        #[arg(trailing_var_arg = true, required = true)]
+        title: Vec<String>,
     },
 
     /// Add/edit description for a vision
     #[command(visible_alias = "d")]
     Describe {
-        id: String,
+        #[arg(help = VISION_HELP)]
+        id: VisionRef,
+        /// Add to the end of the description instead of replacing it
+        #[arg(long)]
+        append: bool,
+        /// Write the description in $EDITOR (the default when no text is given)
+        #[arg(long, short)]
+        edit: bool,
         #[arg(trailing_var_arg = true)]
         text: Vec<String>,
     },
 
     /// Link child vision to parent
     #[command(visible_alias = "l")]
-    Link { parent: String, child: String },
+    Link {
+        #[arg(help = VISION_HELP)]
+        parent: VisionRef,
+        #[arg(help = VISION_HELP)]
+        child: VisionRef,
+    },
+
+    /// Detach a vision from its parent
+    #[command(visible_alias = "u")]
+    Unlink {
+        #[arg(help = VISION_HELP)]
+        id: VisionRef,
+    },
+
+    /// Move a vision under a new parent
+    #[command(visible_alias = "mv")]
+    Move {
+        #[arg(help = VISION_HELP)]
+        child: VisionRef,
+        #[arg(help = VISION_HELP)]
+        parent: VisionRef,
+    },
+
+    /// Change a vision's place among its siblings
+    #[command(visible_alias = "o")]
+    Reorder {
+        #[arg(help = VISION_HELP)]
+        id: VisionRef,
+        /// New position, starting at 1
+        #[arg(value_parser = clap::value_parser!(u32).range(1..))]
+        position: u32,
+    },
+
+    /// Make a vision depend on another, which blocks it until achieved
+    ///
+    /// Dependencies are separate from sub-visions: any vision can wait on
+    /// any other, as long as no cycle forms.
+    Depends {
+        #[arg(help = VISION_HELP)]
+        id: VisionRef,
+        /// The vision it waits on
+        #[arg(long, help = VISION_HELP)]
+        on: VisionRef,
+        /// Remove the dependency instead
+        #[arg(long)]
+        remove: bool,
+    },
+
+    /// Set what is at stake in a vision, from 1 (little) to 5 (a lot)
+    ///
+    /// Stakes weigh a vision against its siblings in its parent's progress
+    /// and tension. New visions start at 3.
+    Stakes {
+        #[arg(help = VISION_HELP)]
+        id: VisionRef,
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=5))]
+        stakes: u8,
+    },
 
-    /// Mark vision as achieved
+    /// Delete a vision with its realities and actions
+    #[command(visible_alias = "rm")]
+    Delete {
+        #[arg(help = VISION_HELP)]
+        id: VisionRef,
+        /// Also delete sub-visions (otherwise they move up a level)
+        #[arg(long, short)]
+        recursive: bool,
+    },
+
+    /// Mark vision as achieved, with a closing reality and retrospective
+    ///
+    /// When run in a terminal, asks for whichever of the two is not given
+    /// (leave an answer empty to skip it).
     #[command(visible_alias = "c")]
-    Complete { id: String },
+    Complete {
+        #[arg(help = VISION_HELP)]
+        id: VisionRef,
+        /// Closing reality: what is true now that it is achieved
+        #[arg(long)]
+        reality: Option<String>,
+        /// What made this work
+        #[arg(long)]
+        retrospective: Option<String>,
+    },
 }
 
 impl VisionCommands {
-    pub fn execute(self) -> TensileResult<()> {
-        match self {
-            VisionCommands::New { title } => new_vision(title),
-            VisionCommands::Tree { format } => view_tree(format),
-            VisionCommands::Describe { id, text } => describe_vision(id, text),
-            VisionCommands::Link { parent, child } => link_visions(parent, child),
-            VisionCommands::Complete { id } => complete_vision(id),
-        }
+    pub fn execute(self, session: &mut Session, output: OutputFormat) -> TensileResult<String> {
+        let confirmation = match self {
+            VisionCommands::New { title } => new_vision(session, title)?,
+            VisionCommands::Tree {
+                root,
+                depth,
+                hide_achieved,
+                ascii,
+                format,
+            } => {
+                let format = match format {
+                    Some(format) if output == OutputFormat::Plain => format,
+                    _ => output,
+                };
+                let tree = view_tree(
+                    session,
+                    root,
+                    depth.map(|d| d as usize),
+                    hide_achieved,
+                    ascii,
+                )?;
+                return output::render(format, &tree);
+            }
+            VisionCommands::Rename { id, title } => rename_vision(session, id, title)?,
+            VisionCommands::Describe {
+                id,
+                append,
+                edit,
+                text,
+            } => describe_vision(session, id, append, edit, text)?,
+            VisionCommands::Link { parent, child } => move_vision(session, child, Some(parent))?,
+            VisionCommands::Unlink { id } => move_vision(session, id, None)?,
+            VisionCommands::Move { child, parent } => move_vision(session, child, Some(parent))?,
+            VisionCommands::Reorder { id, position } => reorder_vision(session, id, position)?,
+            VisionCommands::Depends { id, on, remove } => depend(session, id, on, remove)?,
+            VisionCommands::Stakes { id, stakes } => set_stakes(session, id, stakes)?,
+            VisionCommands::Delete { id, recursive } => {
+                return output::render(output, &delete_vision(session, id, recursive)?);
+            }
+            VisionCommands::Complete {
+                id,
+                reality,
+                retrospective,
+            } => complete_vision(session, id, reality, retrospective)?,
+        };
+        output::render(output, &confirmation)
     }
 }
 
-fn new_vision(title: String) -> TensileResult<()> {
-    let mut db = persistence::load_database()?;
-    let vision = Vision::new(title.clone());
-    let id = vision.id;
-    db.visions.push(vision);
-    persistence::save_database(&db)?;
-    println!("✓ Created vision: {} [{}]", title, id);
-    Ok(())
+/// The vision hierarchy. Serializes as an array of nested nodes.
+#[derive(Serialize, Debug, Clone)]
+#[serde(transparent)]
+pub struct VisionTree {
+    pub roots: Vec<TreeNode>,
+    #[serde(skip)]
+    ascii: bool,
+    #[serde(skip)]
+    icons: StateIcons,
 }
 
-fn view_tree(format: Option<String>) -> TensileResult<()> {
-    let db = persistence::load_database()?;
+/// Connectors drawn between tree nodes.
+struct Glyphs {
+    branch: &'static str,
+    last: &'static str,
+    vertical: &'static str,
+    space: &'static str,
+    /// Continues the line to a node's children past its description
+    bar: &'static str,
+}
 
-    if format.as_deref() == Some("json") {
-        let json = serde_json::to_string_pretty(&db.visions)
-            .map_err(|e| TensileError::Serialization(e.to_string()))?;
-        println!("{}", json);
-    } else {
-        println!("\nVisions:");
-        for vision in &db.visions {
-            let state_marker = match &vision.state {
-                crate::models::VisionState::Achieved => "✓",
-                crate::models::VisionState::Blocked => "⊗",
-                crate::models::VisionState::InProgress => "→",
-                _ => "○",
+const UNICODE: Glyphs = Glyphs {
+    branch: "├── ",
+    last: "└── ",
+    vertical: "│   ",
+    space: "    ",
+    bar: "│ ",
+};
+
+const ASCII: Glyphs = Glyphs {
+    branch: "|-- ",
+    last: "`-- ",
+    vertical: "|   ",
+    space: "    ",
+    bar: "| ",
+};
+
+impl VisionTree {
+    /// The configured icon, or a plain ASCII marker.
+    fn state_marker(&self, state: &VisionState) -> &str {
+        if !self.ascii {
+            return self.icons.get(state);
+        }
+        match state {
+            VisionState::Achieved => "+",
+            VisionState::Blocked => "x",
+            VisionState::InProgress => ">",
+            _ => "o",
+        }
+    }
+
+    fn write_node(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        node: &TreeNode,
+        connector: &str,
+        prefix: &str,
+    ) -> fmt::Result {
+        let glyphs = if self.ascii { &ASCII } else { &UNICODE };
+
+        writeln!(
+            f,
+            "{}{} {} [{}] {}{}",
+            connector,
+            self.state_marker(&node.state),
+            node.title,
+            short_id(&node.id),
+            node.state,
+            annotations(node)
+        )?;
+
+        if let Some(desc) = &node.description {
+            let bar = if node.children.is_empty() {
+                "  "
+            } else {
+                glyphs.bar
             };
-            println!(
-                "  {} {} [{}] {}",
-                state_marker, vision.title, vision.id, vision.state
-            );
-            if let Some(desc) = &vision.description {
-                println!("     {}", desc);
+            for line in desc.lines() {
+                writeln!(f, "{}{}{}", prefix, bar, line)?;
             }
         }
+
+        for (i, child) in node.children.iter().enumerate() {
+            let last = i + 1 == node.children.len();
+            let (connector, continuation) = if last {
+                (glyphs.last, glyphs.space)
+            } else {
+                (glyphs.branch, glyphs.vertical)
+            };
+            self.write_node(
+                f,
+                child,
+                &format!("{}{}", prefix, connector),
+                &format!("{}{}", prefix, continuation),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for VisionTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "\nVisions:")?;
+        if self.roots.is_empty() {
+            return writeln!(f, "  (none)");
+        }
+        for root in &self.roots {
+            self.write_node(f, root, "  ", "  ")?;
+        }
+        Ok(())
+    }
+}
+
+impl Report for VisionTree {
+    fn headers(&self) -> Vec<&'static str> {
+        vec![
+            "depth", "id", "title", "state", "tension", "pattern", "actions", "stakes", "progress",
+        ]
     }
 
-    Ok(())
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.roots
+            .iter()
+            .flat_map(TreeNode::walk)
+            .map(|(depth, node)| {
+                vec![
+                    depth.to_string(),
+                    node.id.to_string(),
+                    node.title.clone(),
+                    node.state.to_string(),
+                    node.tension
+                        .map(|t| format!("{:.1}", t))
+                        .unwrap_or_default(),
+                    format!("{:?}", node.pattern),
+                    node.action_count.to_string(),
+                    node.stakes.to_string(),
+                    node.rollup
+                        .as_ref()
+                        .map(|r| format!("{:.2}", r.progress))
+                        .unwrap_or_default(),
+                ]
+            })
+            .collect()
+    }
 }
 
-fn describe_vision(id: String, text: Vec<String>) -> TensileResult<()> {
-    let uuid =
-        Uuid::parse_str(&id).map_err(|_| TensileError::Parse(format!("Invalid UUID: {}", id)))?;
+fn annotations(node: &TreeNode) -> String {
+    let mut notes = Vec::new();
+    if let Some(tension) = node.tension {
+        notes.push(format!("tension {:.0}%", tension));
+    }
+    match node.open_blockers {
+        0 => {}
+        1 => notes.push("waiting on 1 vision".to_string()),
+        n => notes.push(format!("waiting on {} visions", n)),
+    }
+    if let Some(rollup) = node
+        .rollup
+        .as_ref()
+        .filter(|_| node.state != VisionState::Achieved)
+    {
+        notes.push(format!(
+            "progress {:.0}%, {}/{} achieved",
+            rollup.progress * 100.0,
+            rollup.achieved_children,
+            rollup.children
+        ));
+    }
+    match node.action_count {
+        0 => {}
+        1 => notes.push(format!("{:?}, 1 action", node.pattern)),
+        n => notes.push(format!("{:?}, {} actions", node.pattern, n)),
+    }
+
+    if notes.is_empty() {
+        String::new()
+    } else {
+        format!("  ({})", notes.join("; "))
+    }
+}
 
-    let mut db = persistence::load_database()?;
-    let vision_title = {
-        let vision = db
-            .visions
-            .iter_mut()
-            .find(|v| v.id == uuid)
-            .ok_or_else(|| TensileError::NotFound(format!("Vision not found: {}", id)))?;
+fn new_vision(session: &mut Session, title: String) -> TensileResult<Confirmation> {
+    let vision = session.create_vision(title)?;
+    Ok(
+        Confirmation::new(format!("Created vision: {} [{}]", vision.title, vision.id))
+            .with_id(vision.id),
+    )
+}
 
-        vision.description = Some(text.join(" "));
-        vision.title.clone()
+fn view_tree(
+    session: &Session,
+    root: Option<VisionRef>,
+    depth: Option<usize>,
+    hide_achieved: bool,
+    ascii: bool,
+) -> TensileResult<VisionTree> {
+    let options = TreeOptions {
+        root: root.map(|r| session.resolve(&r)).transpose()?,
+        depth,
+        hide_achieved,
     };
 
-    persistence::save_database(&db)?;
-    println!("✓ Description updated for vision: {}", vision_title);
-    Ok(())
+    Ok(VisionTree {
+        roots: session.tree(&options)?,
+        ascii,
+        icons: session.config().display.state_icons.clone(),
+    })
 }
 
-fn link_visions(parent_id: String, child_id: String) -> TensileResult<()> {
-    let parent_uuid = Uuid::parse_str(&parent_id)
-        .map_err(|_| TensileError::Parse(format!("Invalid parent UUID: {}", parent_id)))?;
-    let child_uuid = Uuid::parse_str(&child_id)
-        .map_err(|_| TensileError::Parse(format!("Invalid child UUID: {}", child_id)))?;
+fn rename_vision(
+    session: &mut Session,
+    id: VisionRef,
+    title: Vec<String>,
+) -> TensileResult<Confirmation> {
+    let id = session.resolve(&id)?;
+    let old = session.vision(id)?.title.clone();
+    let vision = session.rename_vision(id, title.join(" "))?;
+    Ok(Confirmation::new(format!("Renamed: {} -> {}", old, vision.title)).with_id(id))
+}
 
-    let mut db = persistence::load_database()?;
+fn describe_vision(
+    session: &mut Session,
+    id: VisionRef,
+    append: bool,
+    edit: bool,
+    text: Vec<String>,
+) -> TensileResult<Confirmation> {
+    let id = session.resolve(&id)?;
+    let text = editor::text_or_edit(text, edit, || {
+        let template = Template::for_vision(session, id, "Describe the desired state of")?;
+        let current = session.vision(id)?.description.clone().unwrap_or_default();
+        Ok(if append {
+            template
+        } else {
+            template.with_text(current)
+        })
+    })?;
 
-    let child_exists = db.visions.iter().any(|v| v.id == child_uuid);
-    let parent_exists = db.visions.iter().any(|v| v.id == parent_uuid);
+    let vision = if append {
+        session.append_description(id, text)?
+    } else {
+        session.describe_vision(id, text)?
+    };
+    Ok(Confirmation::new(format!("Description updated for vision: {}", vision.title)).with_id(id))
+}
 
-    if !child_exists || !parent_exists {
-        return Err(TensileError::NotFound(
-            "Parent or child vision not found".to_string(),
-        ));
-    }
+fn move_vision(
+    session: &mut Session,
+    child: VisionRef,
+    parent: Option<VisionRef>,
+) -> TensileResult<Confirmation> {
+    let child = session.resolve(&child)?;
+    let parent = parent.map(|p| session.resolve(&p)).transpose()?;
+    let vision = session.move_vision(child, parent)?;
 
-    let child = db.visions.iter_mut().find(|v| v.id == child_uuid).unwrap();
-    child.parent = Some(parent_uuid);
+    let message = match parent {
+        Some(parent) => format!(
+            "Linked visions: {} is now under {}",
+            vision.title,
+            session.vision(parent)?.title
+        ),
+        None => format!("Unlinked vision: {} is now top-level", vision.title),
+    };
+    Ok(Confirmation::new(message).with_id(child))
+}
+
+fn reorder_vision(
+    session: &mut Session,
+    id: VisionRef,
+    position: u32,
+) -> TensileResult<Confirmation> {
+    let id = session.resolve(&id)?;
+    let vision = session.reorder_vision(id, position as usize - 1)?;
+    Ok(Confirmation::new(format!(
+        "Moved {} to position {} among its siblings",
+        vision.title,
+        vision.position + 1
+    ))
+    .with_id(id))
+}
 
-    let parent = db.visions.iter_mut().find(|v| v.id == parent_uuid).unwrap();
-    if !parent.children.contains(&child_uuid) {
-        parent.children.push(child_uuid);
+fn depend(
+    session: &mut Session,
+    id: VisionRef,
+    on: VisionRef,
+    remove: bool,
+) -> TensileResult<Confirmation> {
+    let id = session.resolve(&id)?;
+    let on = session.resolve(&on)?;
+    let (vision, verb) = if remove {
+        (session.remove_dependency(id, on)?, "no longer depends")
+    } else {
+        (session.add_dependency(id, on)?, "now depends")
+    };
+    Ok(Confirmation::new(format!(
+        "{} {} on {}",
+        vision.title,
+        verb,
+        session.vision(on)?.title
+    ))
+    .with_id(id))
+}
+
+fn set_stakes(session: &mut Session, id: VisionRef, stakes: u8) -> TensileResult<Confirmation> {
+    let id = session.resolve(&id)?;
+    let vision = session.set_stakes(id, stakes)?;
+    Ok(Confirmation::new(format!("Stakes of {}: {}", vision.title, vision.stakes)).with_id(id))
+}
+
+fn delete_vision(session: &mut Session, id: VisionRef, recursive: bool) -> TensileResult<Deletion> {
+    let id = session.resolve(&id)?;
+    let deletion = session.delete_vision(id, recursive)?;
+    Ok(deletion)
+}
+
+impl fmt::Display for Deletion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "✓ Deleted vision: {} ({} sub-visions, {} realities, {} actions)",
+            self.title,
+            self.visions.len() - 1,
+            self.realities,
+            self.actions
+        )?;
+        if !self.reparented.is_empty() {
+            writeln!(
+                f,
+                "  {} sub-vision(s) moved up a level",
+                self.reparented.len()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Report for Deletion {
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["id", "title", "realities", "actions"]
     }
 
-    persistence::save_database(&db)?;
-    println!("✓ Linked visions");
-    Ok(())
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.visions[0].to_string(),
+            self.title.clone(),
+            self.realities.to_string(),
+            self.actions.to_string(),
+        ]]
+    }
 }
 
-fn complete_vision(id: String) -> TensileResult<()> {
-    let uuid =
-        Uuid::parse_str(&id).map_err(|_| TensileError::Parse(format!("Invalid UUID: {}", id)))?;
+fn complete_vision(
+    session: &mut Session,
+    id: VisionRef,
+    reality: Option<String>,
+    retrospective: Option<String>,
+) -> TensileResult<Confirmation> {
+    let id = session.resolve(&id)?;
+    let vision = complete(session, id, reality, retrospective)?;
+    Ok(Confirmation::new(format!("Vision achieved: {}", vision.title)).with_id(id))
+}
 
-    let mut db = persistence::load_database()?;
-    let vision_title = {
-        let vision = db
-            .visions
-            .iter_mut()
-            .find(|v| v.id == uuid)
-            .ok_or_else(|| TensileError::NotFound(format!("Vision not found: {}", id)))?;
+/// Mark a vision achieved, asking in a terminal for the closing reality and
+/// retrospective when they are not given. Also how `state transition`
+/// reaches achieved.
+pub(super) fn complete(
+    session: &mut Session,
+    id: Uuid,
+    reality: Option<String>,
+    retrospective: Option<String>,
+) -> TensileResult<Vision> {
+    // Refuse before asking anything
+    StateMachine::validate_transition(&session.vision(id)?.state, &VisionState::Achieved)?;
 
-        vision.completed = true;
-        vision.state = crate::models::VisionState::Achieved;
-        vision.title.clone()
+    let reality = match reality {
+        Some(reality) => Some(reality),
+        None => editor::prompt("Closing reality (what is true now)?")?,
+    };
+    let retrospective = match retrospective {
+        Some(retrospective) => Some(retrospective),
+        None => editor::prompt("What made this work?")?,
     };
+    let vision = session.complete_vision(
+        id,
+        reality.filter(|r| !r.trim().is_empty()),
+        retrospective.filter(|r| !r.trim().is_empty()),
+    )?;
+    suggest_completing_parent(session, id);
+    Ok(vision)
+}
 
-    persistence::save_database(&db)?;
-    println!("✓ Vision achieved: {}", vision_title);
-    Ok(())
+/// Point out a parent whose sub-visions are now all achieved. Goes to
+/// stderr so it stays out of structured output.
+fn suggest_completing_parent(session: &Session, id: Uuid) {
+    if let Some(parent) = session.completable_parent(id) {
+        eprintln!(
+            "💡 All sub-visions of {} are achieved; mark it achieved with `tensile vision complete {}`",
+            parent.title,
+            short_id(&parent.id)
+        );
+    }
 }
@@ -0,0 +1,310 @@
+//! Shell completion: static scripts generated from the command definitions,
+//! plus a hidden `__complete` command the scripts call for values that come
+//! from the database.
+
+use crate::{
+    cli::{parsers::VisionRef, Cli},
+    engine::StateMachine,
+    error::TensileResult,
+    models::{Database, VisionState},
+    persistence::SqliteStore,
+    service::{resolve_vision, short_id, Session},
+};
+use clap::{Arg, Command, CommandFactory};
+use clap_complete::Shell;
+use std::io::Write;
+
+/// A completion value and the text shown beside it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub value: String,
+    pub description: String,
+}
+
+/// Print the completion script for a shell. Bash, zsh and fish also get
+/// the dynamic completion of vision references and target states.
+pub fn print_script(shell: Shell) -> TensileResult<()> {
+    let mut cmd = Cli::command();
+    let name = cmd.get_name().to_string();
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut cmd, name, &mut script);
+
+    let dynamic = match shell {
+        Shell::Bash => BASH_DYNAMIC,
+        Shell::Zsh => ZSH_DYNAMIC,
+        Shell::Fish => FISH_DYNAMIC,
+        _ => "",
+    };
+    script.extend_from_slice(dynamic.as_bytes());
+
+    // Piped into e.g. `head`, the reader may stop early; that is not an error
+    let mut stdout = std::io::stdout().lock();
+    match stdout.write_all(&script).and_then(|_| stdout.flush()) {
+        Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Print candidates for the last of `words`, the arguments typed so far,
+/// one `value<TAB>description` per line. Prints nothing where the static
+/// script knows better, e.g. for subcommands and flags.
+pub fn print_candidates(words: &[String]) -> TensileResult<()> {
+    // The local replica is enough, and much faster than a server round trip.
+    let db = SqliteStore::load()?;
+    for candidate in candidates(&db, words) {
+        println!("{}\t{}", candidate.value, candidate.description);
+    }
+    Ok(())
+}
+
+/// Candidates for the last of `words`, given the words before it.
+pub fn candidates(db: &Database, words: &[String]) -> Vec<Candidate> {
+    let Some((current, typed)) = words.split_last() else {
+        return Vec::new();
+    };
+
+    let mut cmd = Cli::command();
+    cmd.build();
+    let mut path: Vec<String> = Vec::new();
+    let mut positionals: Vec<&str> = Vec::new();
+    let mut pending: Option<Arg> = None;
+
+    for word in typed {
+        if pending.take().is_some() {
+            continue;
+        }
+        if let Some(flag) = word.strip_prefix('-').filter(|f| !f.is_empty()) {
+            pending = option_awaiting_value(&cmd, flag);
+            continue;
+        }
+        if let Some(sub) = cmd.find_subcommand(word).cloned() {
+            path.push(sub.get_name().to_string());
+            cmd = sub;
+            positionals.clear();
+            continue;
+        }
+        positionals.push(word);
+    }
+
+    let arg = match pending {
+        Some(arg) => arg,
+        None if current.starts_with('-') => return Vec::new(),
+        None => match positional(&cmd, positionals.len()) {
+            Some(arg) => arg,
+            None => return Vec::new(),
+        },
+    };
+
+    let session = Session::new(db.clone());
+    if path == ["state", "transition"] && arg.get_id() == "state" {
+        return positionals
+            .first()
+            .map(|vision| target_states(&session, vision, current))
+            .unwrap_or_default();
+    }
+    if arg.get_value_parser().type_id() == std::any::TypeId::of::<VisionRef>() {
+        return visions(&session, current);
+    }
+    Vec::new()
+}
+
+/// The option named by `flag` (without its first dash), if the next word
+/// is its value.
+fn option_awaiting_value(cmd: &Command, flag: &str) -> Option<Arg> {
+    let arg = match flag.strip_prefix('-') {
+        Some(long) if long.contains('=') => return None,
+        Some(long) => cmd.get_arguments().find(|a| a.get_long() == Some(long)),
+        // A run of short flags: only the last can take the next word.
+        None => {
+            let short = flag.chars().last()?;
+            cmd.get_arguments().find(|a| a.get_short() == Some(short))
+        }
+    }?;
+    arg.get_action().takes_values().then(|| arg.clone())
+}
+
+/// The positional argument at `index`; a trailing list takes the rest.
+fn positional(cmd: &Command, index: usize) -> Option<Arg> {
+    let args: Vec<&Arg> = cmd.get_positionals().collect();
+    args.get(index)
+        .or_else(|| {
+            args.last()
+                .filter(|a| a.get_num_args().is_some_and(|n| n.max_values() > 1))
+        })
+        .map(|a| (*a).clone())
+}
+
+/// Unfinished visions whose short id or title starts with `prefix`.
+fn visions(session: &Session, prefix: &str) -> Vec<Candidate> {
+    let prefix = prefix.to_lowercase();
+    let mut candidates = Vec::new();
+
+    if let Some(focus) = session.current_focus() {
+        if ".".starts_with(&prefix) {
+            candidates.push(Candidate {
+                value: ".".to_string(),
+                description: format!("current focus: {}", focus.title),
+            });
+        }
+    }
+
+    let active = session
+        .tree_order()
+        .into_iter()
+        .filter(|v| !v.is_achieved());
+    for vision in active {
+        let id = short_id(&vision.id);
+        if vision.title.to_lowercase().starts_with(&prefix) {
+            candidates.push(Candidate {
+                value: vision.title.clone(),
+                description: id.clone(),
+            });
+        }
+        if id.starts_with(&prefix) {
+            candidates.push(Candidate {
+                value: id,
+                description: vision.title.clone(),
+            });
+        }
+    }
+    candidates
+}
+
+/// States the vision can move to, as accepted by `state transition`.
+fn target_states(session: &Session, vision: &str, prefix: &str) -> Vec<Candidate> {
+    let Ok(id) = resolve_vision(session.database(), vision) else {
+        return Vec::new();
+    };
+    let Ok(vision) = session.vision(id) else {
+        return Vec::new();
+    };
+
+    let prefix = prefix.to_lowercase();
+    VisionState::ALL
+        .iter()
+        .filter(|to| **to != vision.state)
+        .filter(|to| StateMachine::validate_transition(&vision.state, to).is_ok())
+        .map(|to| Candidate {
+            value: state_arg(to),
+            description: format!("from {}", vision.state),
+        })
+        .filter(|c| c.value.starts_with(&prefix))
+        .collect()
+}
+
+fn state_arg(state: &VisionState) -> String {
+    match state {
+        VisionState::InProgress => "in_progress".to_string(),
+        other => other.to_string().to_lowercase(),
+    }
+}
+
+const BASH_DYNAMIC: &str = r#"
+_tensile_dynamic() {
+    local line
+    local -a values=()
+    while IFS= read -r line; do
+        values+=("$(printf '%q' "${line%%$'\t'*}")")
+    done < <(tensile __complete -- "${COMP_WORDS[@]:1:COMP_CWORD}" 2>/dev/null)
+    if (( ${#values[@]} )); then
+        COMPREPLY=("${values[@]}")
+        return 0
+    fi
+    _tensile "$@"
+}
+complete -F _tensile_dynamic -o bashdefault -o default tensile
+"#;
+
+const ZSH_DYNAMIC: &str = r#"
+_tensile_dynamic() {
+    local line
+    local -a lines values descriptions
+    lines=("${(@f)$(tensile __complete -- "${(@)words[2,CURRENT]}" 2>/dev/null)}")
+    lines=(${lines:#})
+    if (( ${#lines} )); then
+        for line in "${lines[@]}"; do
+            values+=("${line%%$'\t'*}")
+            descriptions+=("${line%%$'\t'*}  -- ${line#*$'\t'}")
+        done
+        compadd -U -l -d descriptions -a values
+        return
+    fi
+    _tensile "$@"
+}
+compdef _tensile_dynamic tensile
+"#;
+
+const FISH_DYNAMIC: &str = r#"
+complete -c tensile -a '(tensile __complete -- (commandline -opc)[2..-1] (commandline -ct) 2>/dev/null)'
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(line: &str) -> Vec<String> {
+        let mut words: Vec<String> = line.split_whitespace().map(String::from).collect();
+        if line.ends_with(' ') {
+            words.push(String::new());
+        }
+        words
+    }
+
+    fn values(db: &Database, line: &str) -> Vec<String> {
+        candidates(db, &words(line))
+            .into_iter()
+            .map(|c| c.value)
+            .collect()
+    }
+
+    #[test]
+    fn completes_visions_and_reachable_states() {
+        let mut session = Session::new(Database::default());
+        let cello = session.create_vision("Learn the cello").unwrap().id;
+        let done = session.create_vision("Learn to juggle").unwrap().id;
+        session.transition(done, VisionState::Achieved).unwrap();
+        let db = session.into_database();
+        let id = short_id(&cello);
+
+        assert_eq!(values(&db, "action log "), ["Learn the cello", id.as_str()]);
+        assert_eq!(values(&db, "-o json action l lea"), ["Learn the cello"]);
+        assert_eq!(
+            values(&db, "reality view --vision "),
+            ["Learn the cello", id.as_str()]
+        );
+        assert_eq!(
+            values(&db, "action log --failed cello Played "),
+            Vec::<String>::new()
+        );
+        assert_eq!(values(&db, "action log --"), Vec::<String>::new());
+        assert_eq!(values(&db, "vision "), Vec::<String>::new());
+
+        assert_eq!(
+            values(&db, "state transition cello "),
+            ["in_progress", "achieved"]
+        );
+        assert_eq!(values(&db, &format!("state t {} a", id)), ["achieved"]);
+    }
+
+    #[test]
+    fn every_vision_argument_is_typed_for_completion() {
+        fn check(cmd: &Command) {
+            for arg in cmd.get_arguments() {
+                let help = arg.get_help().map(|h| h.to_string());
+                let typed = arg.get_value_parser().type_id() == std::any::TypeId::of::<VisionRef>();
+                assert_eq!(
+                    help.as_deref() == Some(crate::cli::parsers::VISION_HELP),
+                    typed,
+                    "{} {}",
+                    cmd.get_name(),
+                    arg.get_id()
+                );
+            }
+            cmd.get_subcommands().for_each(check);
+        }
+
+        let mut cmd = Cli::command();
+        cmd.build();
+        check(&cmd);
+    }
+}
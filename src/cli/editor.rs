@@ -0,0 +1,199 @@
+//! Writing long-form entries in `$VISUAL` / `$EDITOR`, and short answers at
+//! a prompt.
+
+use crate::{
+    error::{TensileError, TensileResult},
+    models::Vision,
+    service::Session,
+};
+use std::io::{BufRead, IsTerminal, Write};
+use std::process::Command;
+use uuid::Uuid;
+
+/// Editor used when neither `$VISUAL` nor `$EDITOR` is set.
+const DEFAULT_EDITOR: &str = "vi";
+
+/// What the editor opens with: text to start from, and context shown as
+/// comment lines that are stripped again afterwards.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Template {
+    pub text: String,
+    pub comments: Vec<String>,
+}
+
+impl Template {
+    /// Context for an entry about a vision: its desired state and the
+    /// previous reality assessment.
+    pub fn for_vision(session: &Session, id: Uuid, heading: &str) -> TensileResult<Self> {
+        let vision = session.vision(id)?;
+        let mut comments = vec![format!("{}: {}", heading, vision.title)];
+        push_section(&mut comments, "Desired state", desired_state(vision));
+        if let Some(reality) = session.latest_reality(Some(id)) {
+            let label = format!(
+                "Previous reality ({})",
+                reality.timestamp.format("%Y-%m-%d %H:%M")
+            );
+            push_section(&mut comments, &label, &reality.entry);
+        }
+
+        Ok(Template {
+            text: String::new(),
+            comments,
+        })
+    }
+
+    pub fn with_text(mut self, text: impl Into<String>) -> Self {
+        self.text = text.into();
+        self
+    }
+
+    /// The file contents handed to the editor.
+    pub fn render(&self) -> String {
+        let mut contents = self.text.clone();
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            contents.push('\n');
+        }
+        contents.push('\n');
+        for line in &self.comments {
+            contents.push_str(&comment(line));
+            contents.push('\n');
+        }
+        contents.push_str("#\n# Lines starting with '#' are ignored. An empty entry aborts.\n");
+        contents
+    }
+}
+
+/// Text from the trailing arguments or, with `--edit` or when there are
+/// none, from the editor. Arguments given along with `--edit` pre-fill it.
+pub fn text_or_edit(
+    words: Vec<String>,
+    edit: bool,
+    template: impl FnOnce() -> TensileResult<Template>,
+) -> TensileResult<String> {
+    let text = words.join(" ");
+    if !edit && !text.trim().is_empty() {
+        return Ok(text);
+    }
+    if !edit && !std::io::stdin().is_terminal() {
+        return Err(TensileError::Validation(
+            "No text given; pass it as arguments or use --edit".to_string(),
+        ));
+    }
+
+    let template = template()?;
+    let template = if text.is_empty() {
+        template
+    } else {
+        template.with_text(text)
+    };
+    edit_text(&template)
+}
+
+/// Open the editor on a temporary file and return what was saved, without
+/// comment lines.
+pub fn edit_text(template: &Template) -> TensileResult<String> {
+    let path = std::env::temp_dir().join(format!("tensile-{}.md", Uuid::new_v4()));
+    std::fs::write(&path, template.render())?;
+    let result = run_editor(&path).and_then(|_| Ok(std::fs::read_to_string(&path)?));
+    let _ = std::fs::remove_file(&path);
+
+    let text = strip_comments(&result?);
+    if text.is_empty() {
+        return Err(TensileError::Validation(
+            "Aborted: the entry is empty".to_string(),
+        ));
+    }
+    Ok(text)
+}
+
+/// Ask a question on the terminal and read a one-line answer. `None` when
+/// not run interactively or when the answer is empty.
+pub fn prompt(question: &str) -> TensileResult<Option<String>> {
+    if !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+        return Ok(None);
+    }
+
+    eprint!("{} ", question);
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    let answer = answer.trim();
+    Ok((!answer.is_empty()).then(|| answer.to_string()))
+}
+
+fn run_editor(path: &std::path::Path) -> TensileResult<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_string());
+
+    // Allow editors with arguments, such as `code --wait`.
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or(DEFAULT_EDITOR);
+    let status = Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .map_err(|e| TensileError::Unknown(format!("Could not run editor '{}': {}", editor, e)))?;
+
+    if !status.success() {
+        return Err(TensileError::Validation(format!(
+            "Editor '{}' exited with {}; nothing saved",
+            editor, status
+        )));
+    }
+    Ok(())
+}
+
+/// Drop comment lines and surrounding blank lines, keeping inner ones.
+fn strip_comments(contents: &str) -> String {
+    contents
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+fn desired_state(vision: &Vision) -> &str {
+    vision
+        .description
+        .as_deref()
+        .filter(|d| !d.trim().is_empty())
+        .unwrap_or("(no description yet)")
+}
+
+fn push_section(comments: &mut Vec<String>, label: &str, body: &str) {
+    comments.push(String::new());
+    comments.push(format!("{}:", label));
+    comments.extend(body.lines().map(|line| format!("  {}", line)));
+}
+
+fn comment(line: &str) -> String {
+    if line.is_empty() {
+        "#".to_string()
+    } else {
+        format!("# {}", line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comments_round_trip_out_of_the_text() {
+        let template = Template {
+            text: "First line\n\nSecond paragraph".to_string(),
+            comments: vec!["Reality for: Cello".to_string(), String::new()],
+        };
+        let rendered = template.render();
+
+        assert!(rendered.starts_with("First line\n\nSecond paragraph\n\n# Reality for: Cello\n#\n"));
+        assert_eq!(strip_comments(&rendered), "First line\n\nSecond paragraph");
+        assert_eq!(strip_comments("# only comments\n\n#\n"), "");
+    }
+}
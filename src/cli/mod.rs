@@ -1,14 +1,29 @@
 pub mod commands;
+pub mod complete;
+pub mod editor;
+pub mod output;
 pub mod parsers;
 
 use crate::error::TensileResult;
 use clap::Parser;
+use output::OutputFormat;
 
 #[derive(Parser)]
 #[command(name = "tensile")]
 #[command(about = "A structural tension tracker for creative systems builders", long_about = None)]
 #[command(version)]
 pub struct Cli {
+    /// Output format
+    #[arg(
+        long,
+        short,
+        global = true,
+        value_enum,
+        ignore_case = true,
+        default_value_t
+    )]
+    output: OutputFormat,
+
     #[command(subcommand)]
     command: commands::Commands,
 }
@@ -16,6 +31,6 @@ pub struct Cli {
 impl Cli {
     pub fn run() -> TensileResult<()> {
         let cli = Cli::parse();
-        cli.command.execute()
+        cli.command.execute(cli.output)
     }
 }
@@ -0,0 +1,139 @@
+use crate::error::{TensileError, TensileResult};
+use prettytable::{Row, Table};
+use serde::Serialize;
+use std::fmt;
+use uuid::Uuid;
+
+/// How command results are printed, chosen with the global `--output` flag.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    #[value(alias = "text")]
+    Plain,
+    Json,
+    Table,
+    Csv,
+}
+
+/// The typed result of a command.
+///
+/// `Display` is the plain text, JSON serializes the value as is, and table
+/// and CSV output lay out `rows` under `headers`.
+pub trait Report: Serialize + fmt::Display {
+    fn headers(&self) -> Vec<&'static str>;
+    fn rows(&self) -> Vec<Vec<String>>;
+}
+
+/// Render a report in the given format.
+pub fn render<R: Report>(format: OutputFormat, report: &R) -> TensileResult<String> {
+    match format {
+        OutputFormat::Plain => Ok(report.to_string()),
+        OutputFormat::Json => serde_json::to_string_pretty(report)
+            .map(|json| json + "\n")
+            .map_err(|e| TensileError::Serialization(e.to_string())),
+        OutputFormat::Table => Ok(table(report).to_string()),
+        OutputFormat::Csv => {
+            let csv_error = |e: String| TensileError::Serialization(format!("CSV: {}", e));
+            let bytes = table(report)
+                .to_csv(Vec::new())
+                .map_err(|e| csv_error(e.to_string()))?
+                .into_inner()
+                .map_err(|e| csv_error(e.to_string()))?;
+            String::from_utf8(bytes).map_err(|e| csv_error(e.to_string()))
+        }
+    }
+}
+
+/// Print a report to stdout in the given format.
+pub fn emit<R: Report>(format: OutputFormat, report: &R) -> TensileResult<()> {
+    print!("{}", render(format, report)?);
+    Ok(())
+}
+
+fn table<R: Report>(report: &R) -> Table {
+    let mut table = Table::new();
+    table.set_titles(Row::from(report.headers()));
+    for row in report.rows() {
+        table.add_row(Row::from(row));
+    }
+    table
+}
+
+/// Result of a command that changes data.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Confirmation {
+    pub message: String,
+    /// The record created or changed, if there is a single one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Uuid>,
+}
+
+impl Confirmation {
+    pub fn new(message: impl Into<String>) -> Self {
+        Confirmation {
+            message: message.into(),
+            id: None,
+        }
+    }
+
+    pub fn with_id(mut self, id: Uuid) -> Self {
+        self.id = Some(id);
+        self
+    }
+}
+
+impl fmt::Display for Confirmation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "✓ {}", self.message)
+    }
+}
+
+impl Report for Confirmation {
+    fn headers(&self) -> Vec<&'static str> {
+        vec!["message", "id"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.message.clone(),
+            self.id.map(|id| id.to_string()).unwrap_or_default(),
+        ]]
+    }
+}
+
+/// Format a timestamp the way every listing shows it.
+pub fn timestamp(at: &chrono::DateTime<chrono::Utc>) -> String {
+    at.format("%Y-%m-%d %H:%M").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::ValueEnum;
+
+    #[test]
+    fn renders_every_format() {
+        let report = Confirmation::new("Action logged, \"quoted\"");
+
+        assert_eq!(
+            render(OutputFormat::Plain, &report).unwrap(),
+            "✓ Action logged, \"quoted\"\n"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render(OutputFormat::Json, &report).unwrap()).unwrap();
+        assert_eq!(json["message"], "Action logged, \"quoted\"");
+        assert!(json.get("id").is_none());
+
+        assert_eq!(
+            render(OutputFormat::Csv, &report).unwrap(),
+            "message,id\n\"Action logged, \"\"quoted\"\"\",\n"
+        );
+        assert!(render(OutputFormat::Table, &report)
+            .unwrap()
+            .contains("| message"));
+
+        assert!(OutputFormat::from_str("yaml", true).is_err());
+    }
+}
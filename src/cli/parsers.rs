@@ -1,6 +1,273 @@
+pub use crate::service::{resolve_vision, short_id};
+use chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday,
+};
+use std::fmt;
 use uuid::Uuid;
 
+/// Help text for every argument that names a vision.
+pub const VISION_HELP: &str = "Vision: id, unique id prefix, title, or . for the current focus";
+
+/// A vision as typed on the command line, resolved against the database by
+/// `Session::resolve`. Vision arguments take this type rather than `String`
+/// so that shell completion can tell them apart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VisionRef(String);
+
+impl From<String> for VisionRef {
+    fn from(input: String) -> Self {
+        VisionRef(input)
+    }
+}
+
+impl std::ops::Deref for VisionRef {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for VisionRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Help text for every argument that takes a point in time.
+pub const DATETIME_HELP: &str =
+    "Time: RFC 3339, YYYY-MM-DD [HH:MM] in local time, today, yesterday, (last) monday, or e.g. 3h ago";
+
+/// Help text for arguments that take a day ahead.
+pub const DATE_HELP: &str = "Date: YYYY-MM-DD, today, tomorrow, (next) friday, or e.g. in 2 weeks";
+
 #[allow(dead_code)]
 pub fn parse_uuid(s: &str) -> Result<Uuid, uuid::Error> {
     Uuid::parse_str(s)
 }
+
+/// Parse a point in time relative to now. Dates without a time mean the
+/// start of that day; times without an offset are local.
+pub fn parse_datetime(input: &str) -> Result<DateTime<Utc>, String> {
+    parse_datetime_from(input, Local::now()).map(|(time, _)| time)
+}
+
+/// Like `parse_datetime`, for the end of a range: a date without a time
+/// means the end of that day, so the whole day is included.
+pub fn parse_end_datetime(input: &str) -> Result<DateTime<Utc>, String> {
+    let (time, day) = parse_datetime_from(input, Local::now())?;
+    match day {
+        Some(day) => local(start_of(day + Duration::days(1))).ok_or_else(|| invalid(input)),
+        None => Ok(time),
+    }
+}
+
+/// The time, and the day if only a day was given.
+fn parse_datetime_from(
+    input: &str,
+    now: DateTime<Local>,
+) -> Result<(DateTime<Utc>, Option<NaiveDate>), String> {
+    let s = input.trim().to_lowercase();
+    let today = now.date_naive();
+    let day = |date: NaiveDate| {
+        local(start_of(date))
+            .map(|time| (time, Some(date)))
+            .ok_or_else(|| invalid(input))
+    };
+
+    match s.as_str() {
+        "now" => return Ok((now.with_timezone(&Utc), None)),
+        "today" => return day(today),
+        "yesterday" => return day(today - Duration::days(1)),
+        _ => {}
+    }
+
+    // "monday" is the latest Monday, today included; "last monday" the
+    // latest one before today.
+    let (last, name) = match s.strip_prefix("last ") {
+        Some(name) => (true, name),
+        None => (false, s.as_str()),
+    };
+    if let Ok(weekday) = name.parse::<Weekday>() {
+        let mut back =
+            (today.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+        if last && back == 0 {
+            back = 7;
+        }
+        return day(today - Duration::days(back.into()));
+    }
+
+    if let Some(ago) = s.strip_suffix(" ago") {
+        let ago = ago.replace(' ', "");
+        let split = ago
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(|| invalid(input))?;
+        let amount: i64 = ago[..split].parse().map_err(|_| invalid(input))?;
+        let unit = match &ago[split..] {
+            "m" | "min" | "mins" | "minute" | "minutes" => Duration::minutes(1),
+            "h" | "hour" | "hours" => Duration::hours(1),
+            "d" | "day" | "days" => Duration::days(1),
+            "w" | "week" | "weeks" => Duration::weeks(1),
+            _ => return Err(invalid(input)),
+        };
+        return i32::try_from(amount)
+            .ok()
+            .and_then(|amount| unit.checked_mul(amount))
+            .and_then(|ago| now.checked_sub_signed(ago))
+            .map(|time| (time.with_timezone(&Utc), None))
+            .ok_or_else(|| invalid(input));
+    }
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(input.trim()) {
+        return Ok((dt.with_timezone(&Utc), None));
+    }
+    for format in ["%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(&s, format) {
+            return local(naive)
+                .map(|time| (time, None))
+                .ok_or_else(|| invalid(input));
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(&s, "%Y-%m-%d") {
+        return day(date);
+    }
+
+    Err(invalid(input))
+}
+
+/// Parse a day, looking ahead from today.
+pub fn parse_date(input: &str) -> Result<NaiveDate, String> {
+    parse_date_from(input, Local::now().date_naive())
+}
+
+fn parse_date_from(input: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    let s = input.trim().to_lowercase();
+    let invalid = || format!("Invalid date: {} ({})", input, DATE_HELP);
+
+    match s.as_str() {
+        "today" => return Ok(today),
+        "tomorrow" => return Ok(today + Duration::days(1)),
+        _ => {}
+    }
+
+    // "friday" is the next Friday, today included; "next friday" the next
+    // one after today.
+    let (next, name) = match s.strip_prefix("next ") {
+        Some(name) => (true, name),
+        None => (false, s.as_str()),
+    };
+    if let Ok(weekday) = name.parse::<Weekday>() {
+        let mut ahead =
+            (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+        if next && ahead == 0 {
+            ahead = 7;
+        }
+        return Ok(today + Duration::days(ahead.into()));
+    }
+
+    if let Some(after) = s.strip_prefix("in ") {
+        let after = after.replace(' ', "");
+        let split = after
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(invalid)?;
+        let amount: i64 = after[..split].parse().map_err(|_| invalid())?;
+        let unit = match &after[split..] {
+            "d" | "day" | "days" => Duration::days(1),
+            "w" | "week" | "weeks" => Duration::weeks(1),
+            _ => return Err(invalid()),
+        };
+        return i32::try_from(amount)
+            .ok()
+            .and_then(|amount| unit.checked_mul(amount))
+            .and_then(|after| today.checked_add_signed(after))
+            .ok_or_else(invalid);
+    }
+
+    NaiveDate::parse_from_str(&s, "%Y-%m-%d").map_err(|_| invalid())
+}
+
+fn invalid(input: &str) -> String {
+    format!("Invalid time: {} ({})", input, DATETIME_HELP)
+}
+
+fn start_of(date: NaiveDate) -> NaiveDateTime {
+    date.and_hms_opt(0, 0, 0).unwrap_or_default()
+}
+
+/// A local wall-clock time in UTC; the earlier one if it occurs twice.
+fn local(naive: NaiveDateTime) -> Option<DateTime<Utc>> {
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(input: &str, now: DateTime<Local>) -> Result<DateTime<Utc>, String> {
+        parse_datetime_from(input, now).map(|(time, _)| time)
+    }
+
+    fn midnight(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+        local(start_of(NaiveDate::from_ymd_opt(year, month, day).unwrap())).unwrap()
+    }
+
+    #[test]
+    fn parses_absolute_and_relative_times() {
+        // A Wednesday afternoon.
+        let now = Local.with_ymd_and_hms(2026, 10, 14, 15, 0, 0).unwrap();
+
+        assert_eq!(
+            at("2024-03-01T12:00:00Z", now).unwrap(),
+            Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap()
+        );
+        assert_eq!(
+            at("2024-03-01 09:30", now).unwrap(),
+            midnight(2024, 3, 1) + Duration::minutes(9 * 60 + 30)
+        );
+        assert_eq!(
+            at("3h ago", now).unwrap(),
+            (now - Duration::hours(3)).with_timezone(&Utc)
+        );
+        assert_eq!(
+            at("2 days ago", now).unwrap(),
+            (now - Duration::days(2)).with_timezone(&Utc)
+        );
+        assert_eq!(at("yesterday", now).unwrap(), midnight(2026, 10, 13));
+        assert_eq!(at("monday", now).unwrap(), midnight(2026, 10, 12));
+        assert_eq!(at("Last Monday", now).unwrap(), midnight(2026, 10, 12));
+        assert_eq!(at("wednesday", now).unwrap(), midnight(2026, 10, 14));
+        assert_eq!(at("last wed", now).unwrap(), midnight(2026, 10, 7));
+        assert_eq!(
+            parse_datetime_from("2026-09-01", now).unwrap().1,
+            NaiveDate::from_ymd_opt(2026, 9, 1)
+        );
+
+        assert!(at("next tuesday", now).is_err());
+        assert!(at("3 fortnights ago", now).is_err());
+        assert!(at("99999999 weeks ago", now).is_err());
+        assert!(at("9999999999 weeks ago", now).is_err());
+    }
+
+    #[test]
+    fn parses_days_ahead() {
+        // A Wednesday.
+        let today = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+        let day = |d| NaiveDate::from_ymd_opt(2026, 10, d).unwrap();
+
+        assert_eq!(parse_date_from("tomorrow", today), Ok(day(15)));
+        assert_eq!(parse_date_from("friday", today), Ok(day(16)));
+        assert_eq!(parse_date_from("wednesday", today), Ok(day(14)));
+        assert_eq!(parse_date_from("Next Wed", today), Ok(day(21)));
+        assert_eq!(parse_date_from("in 2 weeks", today), Ok(day(28)));
+        assert_eq!(parse_date_from("in 3d", today), Ok(day(17)));
+        assert_eq!(parse_date_from("2026-10-20", today), Ok(day(20)));
+
+        assert!(parse_date_from("last friday", today).is_err());
+        assert!(parse_date_from("in 2 months", today).is_err());
+        assert!(parse_date_from("in 9999999999 weeks", today).is_err());
+        assert!(parse_date_from("in 99999999 weeks", today).is_err());
+    }
+}
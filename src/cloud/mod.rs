@@ -2,7 +2,8 @@
 pub mod sync;
 
 #[cfg(feature = "cloud")]
-pub use sync::CloudSync;
+#[allow(unused_imports)]
+pub use sync::{CloudSync, RecordCounts, SyncStatus};
 
 #[cfg(feature = "cloud")]
 pub struct CloudConfig {
@@ -15,7 +16,9 @@ pub struct CloudConfig {
 impl CloudConfig {
     pub fn from_env() -> crate::TensileResult<Self> {
         let api_url = std::env::var("TENSILE_CLOUD_URL")
-            .unwrap_or_else(|_| "https://api.tensile.dev".to_string());
+            .unwrap_or_else(|_| "https://api.tensile.dev".to_string())
+            .trim_end_matches('/')
+            .to_string();
         let api_token = std::env::var("TENSILE_CLOUD_TOKEN").map_err(|_| {
             crate::error::TensileError::Validation("TENSILE_CLOUD_TOKEN not set".to_string())
         })?;
@@ -1,63 +1,215 @@
 #[cfg(feature = "cloud")]
-use crate::{error::TensileError, models::Database, TensileResult};
+use crate::{
+    error::TensileError,
+    models::Database,
+    persistence,
+    sync::{self, MergeOutcome, SyncStore},
+    TensileResult,
+};
+
+/// Name of the sync base kept for the cloud server.
+#[cfg(feature = "cloud")]
+pub const CHANNEL: &str = "cloud";
+
+/// How often to re-merge when other devices keep pushing in between.
+#[cfg(feature = "cloud")]
+const PUSH_ATTEMPTS: usize = 3;
 
 #[cfg(feature = "cloud")]
 pub struct CloudSync {
     config: super::CloudConfig,
+    client: reqwest::Client,
+}
+
+/// Record counts on both sides of a sync, used by `tensile sync status`.
+#[cfg(feature = "cloud")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncStatus {
+    pub api_url: String,
+    pub user_id: String,
+    pub local: RecordCounts,
+    pub remote: RecordCounts,
+}
+
+#[cfg(feature = "cloud")]
+#[derive(serde::Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RecordCounts {
+    pub visions: usize,
+    pub realities: usize,
+    pub actions: usize,
+}
+
+#[cfg(feature = "cloud")]
+impl RecordCounts {
+    pub fn of(db: &Database) -> Self {
+        RecordCounts {
+            visions: db.visions.len(),
+            realities: db.realities.len(),
+            actions: db.actions.len(),
+        }
+    }
 }
 
 #[cfg(feature = "cloud")]
 impl CloudSync {
     pub fn new(config: super::CloudConfig) -> Self {
-        CloudSync { config }
+        CloudSync {
+            config,
+            client: reqwest::Client::new(),
+        }
     }
 
+    /// Replace the server copy with `db`, whatever other devices pushed.
     pub async fn push(&self, db: &Database) -> TensileResult<()> {
+        let response = self.send_push(db, None).await?;
+
+        if !response.status().is_success() {
+            return Err(TensileError::Sync(format!(
+                "Cloud push failed: {}",
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Replace the server copy with `db` if it is still the version tagged
+    /// `etag` (`None` for a server that had nothing). Returns `false`,
+    /// uploading nothing, if another device pushed in the meantime.
+    pub async fn push_if(&self, db: &Database, etag: Option<&str>) -> TensileResult<bool> {
+        let response = self.send_push(db, Some(etag)).await?;
+
+        if response.status() == reqwest::StatusCode::PRECONDITION_FAILED {
+            return Ok(false);
+        }
+
+        if !response.status().is_success() {
+            return Err(TensileError::Sync(format!(
+                "Cloud push failed: {}",
+                response.status()
+            )));
+        }
+
+        Ok(true)
+    }
+
+    async fn send_push(
+        &self,
+        db: &Database,
+        precondition: Option<Option<&str>>,
+    ) -> TensileResult<reqwest::Response> {
         let payload =
             serde_json::to_string(db).map_err(|e| TensileError::Serialization(e.to_string()))?;
 
-        let client = reqwest::Client::new();
-        let response = client
+        let request = self
+            .client
             .post(format!("{}/sync/push", self.config.api_url))
             .bearer_auth(&self.config.api_token)
             .header("X-User-ID", &self.config.user_id)
-            .body(payload)
-            .send()
-            .await
-            .map_err(|e| TensileError::Unknown(e.to_string()))?;
+            .header("Content-Type", "application/json")
+            .body(payload);
 
-        if !response.status().is_success() {
-            return Err(TensileError::Unknown(format!(
-                "Cloud sync failed: {}",
-                response.status()
-            )));
-        }
+        let request = match precondition {
+            None => request,
+            Some(Some(etag)) => request.header(reqwest::header::IF_MATCH, etag),
+            Some(None) => request.header(reqwest::header::IF_NONE_MATCH, "*"),
+        };
 
-        Ok(())
+        request
+            .send()
+            .await
+            .map_err(|e| TensileError::Sync(e.to_string()))
     }
 
+    /// Fetch the remote database. A server that has never received a push
+    /// answers 404, which is treated as an empty database.
     pub async fn pull(&self) -> TensileResult<Database> {
-        let client = reqwest::Client::new();
-        let response = client
+        Ok(self.fetch().await?.1)
+    }
+
+    /// Fetch the remote database along with the ETag of the version served,
+    /// `None` when the server has nothing yet.
+    pub async fn fetch(&self) -> TensileResult<(Option<String>, Database)> {
+        let response = self
+            .client
             .get(format!("{}/sync/pull", self.config.api_url))
             .bearer_auth(&self.config.api_token)
             .header("X-User-ID", &self.config.user_id)
             .send()
             .await
-            .map_err(|e| TensileError::Unknown(e.to_string()))?;
+            .map_err(|e| TensileError::Sync(e.to_string()))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok((None, Database::default()));
+        }
 
         if !response.status().is_success() {
-            return Err(TensileError::Unknown(format!(
-                "Cloud sync failed: {}",
+            return Err(TensileError::Sync(format!(
+                "Cloud pull failed: {}",
                 response.status()
             )));
         }
 
+        let etag = response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from);
+
         let body = response
             .text()
             .await
-            .map_err(|e| TensileError::Unknown(e.to_string()))?;
+            .map_err(|e| TensileError::Sync(e.to_string()))?;
+
+        let db =
+            serde_json::from_str(&body).map_err(|e| TensileError::Serialization(e.to_string()))?;
+        Ok((etag, db))
+    }
+
+    /// Fetch the server copy and three-way merge it into the local database.
+    pub async fn pull_merge(&self, store: &SyncStore) -> TensileResult<MergeOutcome> {
+        let _lock = store.lock()?;
+        let remote = self.pull().await?;
+        sync::reconcile(store, CHANNEL, &remote)
+    }
+
+    /// Merge in remote changes, then upload the result. Refuses to push
+    /// while conflicts are pending, so unresolved edits never overwrite
+    /// the other device's version. The upload only goes through if the
+    /// server is still at the version that was merged; otherwise the newer
+    /// version is merged and the upload retried.
+    pub async fn push_merge(&self, store: &SyncStore) -> TensileResult<MergeOutcome> {
+        let _lock = store.lock()?;
+        sync::ensure_no_conflicts(store)?;
+
+        for _ in 0..PUSH_ATTEMPTS {
+            let (etag, remote) = self.fetch().await?;
+            let outcome = sync::reconcile(store, CHANNEL, &remote)?;
+            sync::ensure_no_conflicts(store)?;
+
+            let local = persistence::load_database()?;
+            // Another device pushed since the fetch: merge with that
+            if !self.push_if(&local, etag.as_deref()).await? {
+                continue;
+            }
+            store.save_base(CHANNEL, &local)?;
+
+            return Ok(outcome);
+        }
+
+        Err(TensileError::Sync(
+            "The server kept changing while pushing; local changes are kept, try again".to_string(),
+        ))
+    }
+
+    pub async fn status(&self, local: &Database) -> TensileResult<SyncStatus> {
+        let remote = self.pull().await?;
 
-        serde_json::from_str(&body).map_err(|e| TensileError::Serialization(e.to_string()))
+        Ok(SyncStatus {
+            api_url: self.config.api_url.clone(),
+            user_id: self.config.user_id.clone(),
+            local: RecordCounts::of(local),
+            remote: RecordCounts::of(&remote),
+        })
     }
 }
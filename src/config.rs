@@ -1,2 +1,567 @@
 // Configuration management for Tensile
-// Moved to individual modules (e.g., SqliteStore handles DB path)
+use crate::{
+    error::{TensileError, TensileResult},
+    models::VisionState,
+};
+use chrono::Weekday;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// Directory holding the database and local state. Defaults to `~/.tensile`
+/// and can be overridden with `TENSILE_HOME`.
+pub fn data_dir() -> PathBuf {
+    if let Ok(dir) = std::env::var("TENSILE_HOME") {
+        return PathBuf::from(dir);
+    }
+
+    dirs::home_dir()
+        .map(|h| h.join(".tensile"))
+        .unwrap_or_else(|| PathBuf::from(".tensile"))
+}
+
+/// The configured database file.
+pub fn db_path() -> TensileResult<PathBuf> {
+    Ok(Config::load()?.db_path())
+}
+
+pub fn ensure_db_dir() -> TensileResult<()> {
+    std::fs::create_dir_all(data_dir())?;
+    Ok(())
+}
+
+/// Stable identifier for this installation, created on first use.
+pub fn device_id() -> TensileResult<Uuid> {
+    let path = data_dir().join("device_id");
+
+    if let Ok(contents) = std::fs::read_to_string(&path) {
+        if let Ok(id) = Uuid::parse_str(contents.trim()) {
+            return Ok(id);
+        }
+    }
+
+    ensure_db_dir()?;
+    let id = Uuid::new_v4();
+    std::fs::write(&path, id.to_string())?;
+    Ok(id)
+}
+
+/// Settings read from `config.toml` in the data directory. Every setting is
+/// optional; missing ones take the defaults below.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub database: DatabaseConfig,
+    pub patterns: PatternConfig,
+    pub review: ReviewConfig,
+    pub display: DisplayConfig,
+    pub hooks: HooksConfig,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    /// Database file; relative paths are taken from the data directory and
+    /// `~` is expanded. Default: `tensile.db` in the data directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+
+/// Thresholds for classifying a vision's actions.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct PatternConfig {
+    /// Success rate above which a vision is advancing
+    pub advancing_above: f64,
+    /// Success rate above which a vision is oscillating rather than stagnant
+    pub oscillating_above: f64,
+    /// Days counted as recent activity
+    pub recent_days: u32,
+}
+
+impl Default for PatternConfig {
+    fn default() -> Self {
+        PatternConfig {
+            advancing_above: 0.7,
+            oscillating_above: 0.3,
+            recent_days: 7,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReviewConfig {
+    /// Period of `action review` without `--period` or a range: daily,
+    /// weekly or monthly, checked by the service layer
+    pub default_period: String,
+    /// First day of a week, for weekly reviews and grouping by week
+    #[serde(with = "weekday")]
+    pub week_starts_on: Weekday,
+}
+
+impl Default for ReviewConfig {
+    fn default() -> Self {
+        ReviewConfig {
+            default_period: "daily".to_string(),
+            week_starts_on: Weekday::Mon,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct DisplayConfig {
+    /// Markers for the highest-tension visions, in rank order
+    pub priority_icons: Vec<String>,
+    pub state_icons: StateIcons,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        DisplayConfig {
+            priority_icons: ["🔴", "🟠", "🟡"].map(String::from).to_vec(),
+            state_icons: StateIcons::default(),
+        }
+    }
+}
+
+impl DisplayConfig {
+    /// Marker for the vision ranked `rank` by tension, from 0; blank past
+    /// the configured icons.
+    pub fn priority_icon(&self, rank: usize) -> &str {
+        self.priority_icons.get(rank).map_or("  ", String::as_str)
+    }
+}
+
+/// Marker shown beside a vision in each state.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct StateIcons {
+    pub conceived: String,
+    pub in_progress: String,
+    pub blocked: String,
+    pub reassessed: String,
+    pub achieved: String,
+}
+
+impl Default for StateIcons {
+    fn default() -> Self {
+        StateIcons {
+            conceived: "○".to_string(),
+            in_progress: "→".to_string(),
+            blocked: "⊗".to_string(),
+            reassessed: "◌".to_string(),
+            achieved: "✓".to_string(),
+        }
+    }
+}
+
+impl StateIcons {
+    pub fn get(&self, state: &VisionState) -> &str {
+        match state {
+            VisionState::Conceived => &self.conceived,
+            VisionState::InProgress => &self.in_progress,
+            VisionState::Blocked => &self.blocked,
+            VisionState::Reassessed => &self.reassessed,
+            VisionState::Achieved => &self.achieved,
+        }
+    }
+}
+
+/// Commands run when data changes; see `crate::hooks`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct HooksConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_action_logged: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_state_transition: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_vision_achieved: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_tension_threshold_crossed: Option<String>,
+    /// Tension score, in percent, watched by on_tension_threshold_crossed
+    pub tension_threshold: f64,
+    /// Seconds a hook may run before it is stopped
+    pub timeout_secs: u64,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        HooksConfig {
+            on_action_logged: None,
+            on_state_transition: None,
+            on_vision_achieved: None,
+            on_tension_threshold_crossed: None,
+            tension_threshold: 75.0,
+            timeout_secs: 10,
+        }
+    }
+}
+
+impl HooksConfig {
+    /// The command configured for the hook setting `name`, e.g.
+    /// `on_action_logged`, if any.
+    pub fn command(&self, name: &str) -> Option<&str> {
+        let command = match name {
+            "on_action_logged" => &self.on_action_logged,
+            "on_state_transition" => &self.on_state_transition,
+            "on_vision_achieved" => &self.on_vision_achieved,
+            "on_tension_threshold_crossed" => &self.on_tension_threshold_crossed,
+            _ => return None,
+        };
+        command.as_deref().filter(|c| !c.trim().is_empty())
+    }
+
+    /// Whether no hook is configured.
+    pub fn is_empty(&self) -> bool {
+        [
+            &self.on_action_logged,
+            &self.on_state_transition,
+            &self.on_vision_achieved,
+            &self.on_tension_threshold_crossed,
+        ]
+        .iter()
+        .all(|c| c.is_none())
+    }
+}
+
+impl Config {
+    /// Where the configuration is read from: `config.toml` in the data
+    /// directory.
+    pub fn path() -> PathBuf {
+        data_dir().join("config.toml")
+    }
+
+    /// Read and validate the configuration file, or the defaults if there
+    /// is none.
+    pub fn load() -> TensileResult<Self> {
+        let path = Self::path();
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Self::from_toml(&text).map_err(|e| match e {
+                TensileError::Validation(message) => {
+                    TensileError::Validation(format!("{}: {}", path.display(), message))
+                }
+                other => other,
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn from_toml(text: &str) -> TensileResult<Self> {
+        let config: Config = toml_edit::de::from_str(text)
+            .map_err(|e| TensileError::Validation(e.to_string().trim_end().to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check values that parse but make no sense.
+    pub fn validate(&self) -> TensileResult<()> {
+        let patterns = &self.patterns;
+        for (key, value) in [
+            ("patterns.advancing_above", patterns.advancing_above),
+            ("patterns.oscillating_above", patterns.oscillating_above),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(TensileError::Validation(format!(
+                    "{} must be a success rate between 0 and 1, not {}",
+                    key, value
+                )));
+            }
+        }
+        if patterns.oscillating_above > patterns.advancing_above {
+            return Err(TensileError::Validation(format!(
+                "patterns.oscillating_above ({}) must not exceed patterns.advancing_above ({})",
+                patterns.oscillating_above, patterns.advancing_above
+            )));
+        }
+        if patterns.recent_days == 0 {
+            return Err(TensileError::Validation(
+                "patterns.recent_days must be at least 1".to_string(),
+            ));
+        }
+        if !(0.0..=100.0).contains(&self.hooks.tension_threshold) {
+            return Err(TensileError::Validation(format!(
+                "hooks.tension_threshold must be a tension score between 0 and 100, not {}",
+                self.hooks.tension_threshold
+            )));
+        }
+        if self.hooks.timeout_secs == 0 {
+            return Err(TensileError::Validation(
+                "hooks.timeout_secs must be at least 1".to_string(),
+            ));
+        }
+        if self
+            .database
+            .path
+            .as_ref()
+            .is_some_and(|p| p.as_os_str().is_empty())
+        {
+            return Err(TensileError::Validation(
+                "database.path must not be empty; remove it to use the default".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The database file this configuration points to.
+    pub fn db_path(&self) -> PathBuf {
+        let Some(path) = &self.database.path else {
+            return data_dir().join("tensile.db");
+        };
+        match path.strip_prefix("~").ok().zip(dirs::home_dir()) {
+            Some((rest, home)) => home.join(rest),
+            None => data_dir().join(path),
+        }
+    }
+
+    /// Every setting as a dotted key and its TOML value, in file order. The
+    /// database path is shown resolved.
+    pub fn entries(&self) -> Vec<(String, String)> {
+        let mut entries = vec![(
+            "database.path".to_string(),
+            toml_edit::Value::from(self.db_path().display().to_string()).to_string(),
+        )];
+        let rest = Config {
+            database: DatabaseConfig::default(),
+            ..self.clone()
+        };
+        if let Ok(doc) = toml_edit::ser::to_document(&rest) {
+            flatten("", doc.as_table(), &mut entries);
+        }
+        entries
+    }
+
+    /// The value of one dotted key, e.g. `patterns.recent_days`.
+    pub fn get(&self, key: &str) -> TensileResult<String> {
+        self.entries()
+            .into_iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value)
+            .ok_or_else(|| unknown_key(key))
+    }
+
+    /// Set one dotted key in the configuration file, keeping its comments
+    /// and layout. `value` is read as TOML, falling back to a plain string,
+    /// and the whole file is validated before it is written: by `validate`,
+    /// then by `check` for settings other layers define. If the file was
+    /// invalid to begin with, only the new setting itself has to be valid.
+    pub fn set(
+        key: &str,
+        value: &str,
+        check: impl Fn(&Config) -> TensileResult<()>,
+    ) -> TensileResult<Edited> {
+        let value = value
+            .parse::<toml_edit::Value>()
+            .unwrap_or_else(|_| toml_edit::Value::from(value));
+
+        // The setting on its own, so that a file that is already invalid
+        // cannot take on another mistake
+        let mut alone = toml_edit::DocumentMut::new();
+        *slot(&mut alone, key)? = toml_edit::value(value.clone());
+        toml_edit::de::from_str::<Config>(&alone.to_string())
+            .map_err(|e| cannot_change("set", key, e.message()))?;
+
+        Self::edit("set", key, check, |doc| {
+            *slot(doc, key)? = toml_edit::value(value);
+            Ok(())
+        })
+    }
+
+    /// Remove one dotted key from the configuration file, so it takes its
+    /// default. Unlike `set`, this also removes keys tensile does not know.
+    pub fn unset(key: &str, check: impl Fn(&Config) -> TensileResult<()>) -> TensileResult<Edited> {
+        Self::edit("unset", key, check, |doc| {
+            let parts: Vec<&str> = key.split('.').collect();
+            let (name, tables) = parts.split_last().ok_or_else(|| unknown_key(key))?;
+            let mut table: &mut dyn toml_edit::TableLike = doc.as_table_mut();
+            for part in tables {
+                table = table
+                    .get_mut(part)
+                    .and_then(toml_edit::Item::as_table_like_mut)
+                    .ok_or_else(|| not_set(key))?;
+            }
+            table.remove(name).map(|_| ()).ok_or_else(|| not_set(key))
+        })
+    }
+
+    /// Apply `change` to the configuration file and write it if the result
+    /// is valid. A file that was invalid before is written anyway, so that
+    /// it can be repaired one key at a time.
+    fn edit(
+        verb: &str,
+        key: &str,
+        check: impl Fn(&Config) -> TensileResult<()>,
+        change: impl FnOnce(&mut toml_edit::DocumentMut) -> TensileResult<()>,
+    ) -> TensileResult<Edited> {
+        let path = Self::path();
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let mut doc: toml_edit::DocumentMut = text
+            .parse()
+            .map_err(|e| TensileError::Parse(format!("{}: {}", path.display(), e)))?;
+        change(&mut doc)?;
+
+        let checked = |text: &str| Self::from_toml(text).and_then(|c| check(&c).map(|_| c));
+        let edited = match checked(&doc.to_string()) {
+            Ok(config) => Edited::Valid(Box::new(config)),
+            Err(TensileError::Validation(message)) if checked(&text).is_err() => {
+                Edited::StillInvalid(message)
+            }
+            Err(TensileError::Validation(message)) => {
+                return Err(cannot_change(verb, key, &message))
+            }
+            Err(e) => return Err(e),
+        };
+        ensure_db_dir()?;
+        std::fs::write(&path, doc.to_string())?;
+        Ok(edited)
+    }
+}
+
+/// The configuration file after `Config::set` or `Config::unset`.
+#[derive(Debug, Clone)]
+pub enum Edited {
+    Valid(Box<Config>),
+    /// It was invalid before the change and still is, for this reason
+    StillInvalid(String),
+}
+
+/// The value for a dotted key, creating the tables on the way.
+fn slot<'a>(
+    doc: &'a mut toml_edit::DocumentMut,
+    key: &str,
+) -> TensileResult<&'a mut toml_edit::Item> {
+    let parts: Vec<&str> = key.split('.').collect();
+    let (name, tables) = match parts.split_last() {
+        Some((name, tables)) if !tables.is_empty() && parts.iter().all(|p| !p.is_empty()) => {
+            (*name, tables)
+        }
+        _ => return Err(unknown_key(key)),
+    };
+    let mut table = doc.as_table_mut();
+    for part in tables {
+        table = table
+            .entry(part)
+            .or_insert_with(toml_edit::table)
+            .as_table_mut()
+            .ok_or_else(|| unknown_key(key))?;
+    }
+    Ok(&mut table[name])
+}
+
+fn cannot_change(verb: &str, key: &str, message: &str) -> TensileError {
+    TensileError::Validation(format!("Cannot {} {}: {}", verb, key, message))
+}
+
+fn not_set(key: &str) -> TensileError {
+    TensileError::NotFound(format!(
+        "{} is not set in {}",
+        key,
+        Config::path().display()
+    ))
+}
+
+/// Every key in a configuration file as written, valid or not.
+pub fn raw_entries(text: &str) -> TensileResult<Vec<(String, String)>> {
+    let doc: toml_edit::DocumentMut = text
+        .parse()
+        .map_err(|e| TensileError::Parse(format!("{}: {}", Config::path().display(), e)))?;
+    let mut entries = Vec::new();
+    flatten("", doc.as_table(), &mut entries);
+    Ok(entries)
+}
+
+fn flatten(prefix: &str, table: &toml_edit::Table, entries: &mut Vec<(String, String)>) {
+    for (key, item) in table.iter() {
+        let key = format!("{}{}", prefix, key);
+        match item {
+            toml_edit::Item::Table(table) => flatten(&format!("{}.", key), table, entries),
+            toml_edit::Item::Value(toml_edit::Value::InlineTable(table)) => {
+                flatten(&format!("{}.", key), &table.clone().into_table(), entries)
+            }
+            toml_edit::Item::Value(value) => {
+                entries.push((key, value.clone().decorated("", "").to_string()))
+            }
+            _ => {}
+        }
+    }
+}
+
+fn unknown_key(key: &str) -> TensileError {
+    TensileError::NotFound(format!(
+        "Unknown setting: {} (see `tensile config list`)",
+        key
+    ))
+}
+
+/// Weekdays written as full lowercase names, e.g. `"sunday"`.
+mod weekday {
+    use chrono::Weekday;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(day: &Weekday, serializer: S) -> Result<S::Ok, S::Error> {
+        let name = match day {
+            Weekday::Mon => "monday",
+            Weekday::Tue => "tuesday",
+            Weekday::Wed => "wednesday",
+            Weekday::Thu => "thursday",
+            Weekday::Fri => "friday",
+            Weekday::Sat => "saturday",
+            Weekday::Sun => "sunday",
+        };
+        serializer.serialize_str(name)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Weekday, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(|_| {
+            D::Error::custom(format!(
+                "expected a day such as \"monday\", not \"{}\"",
+                name
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_settings_take_defaults_and_bad_ones_are_explained() {
+        let config = Config::from_toml("[patterns]\nadvancing_above = 0.8\n").unwrap();
+        assert_eq!(config.patterns.advancing_above, 0.8);
+        assert_eq!(config.patterns.recent_days, 7);
+        assert_eq!(config.review.week_starts_on, Weekday::Mon);
+
+        let config = Config::from_toml("[review]\nweek_starts_on = \"sunday\"\n").unwrap();
+        assert_eq!(config.review.week_starts_on, Weekday::Sun);
+
+        for (toml, expected) in [
+            ("[patterns]\nadvancing_above = 1.5", "between 0 and 1"),
+            ("[patterns]\noscillating_above = 0.9", "must not exceed"),
+            ("[patterns]\nrecent_days = 0", "at least 1"),
+            ("[patterns]\nthreshold = 1", "unknown field"),
+        ] {
+            let error = Config::from_toml(toml).unwrap_err().to_string();
+            assert!(error.contains(expected), "{}: {}", toml, error);
+        }
+        // Period names are the service layer's to check
+        assert!(Config::from_toml("[review]\ndefault_period = \"yearly\"").is_ok());
+    }
+
+    #[test]
+    fn entries_use_dotted_keys() {
+        let config = Config::default();
+        assert_eq!(config.get("patterns.recent_days").unwrap(), "7");
+        assert_eq!(config.get("review.default_period").unwrap(), "\"daily\"");
+        assert_eq!(config.get("display.state_icons.achieved").unwrap(), "\"✓\"");
+        assert!(config.get("patterns.colour").is_err());
+    }
+}
@@ -0,0 +1,248 @@
+use crate::models::{Database, Vision, VisionState};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// Follows "depends on" links between visions. A vision is blocked by the
+/// visions it depends on until they are achieved; in turn, each of those
+/// enables it.
+pub struct DependencyAnalyzer;
+
+/// An upstream vision worth attacking, and what achieving it opens up.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CriticalPath {
+    pub vision_id: Uuid,
+    pub title: String,
+    /// Unfinished visions waiting on it, directly or further down
+    pub unblocks: usize,
+    /// Total stakes of those visions
+    pub stakes_unblocked: u32,
+    /// The longest chain of waiting visions, starting with this one
+    pub path: Vec<PathStep>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct PathStep {
+    pub vision_id: Uuid,
+    pub title: String,
+    pub state: VisionState,
+}
+
+impl DependencyAnalyzer {
+    /// The visions `vision_id` depends on that are not achieved yet.
+    pub fn open_blockers(db: &Database, vision_id: Uuid) -> Vec<&Vision> {
+        let Some(vision) = find(db, vision_id) else {
+            return Vec::new();
+        };
+        vision
+            .depends_on
+            .iter()
+            .filter_map(|id| find(db, *id))
+            .filter(|v| !v.is_achieved())
+            .collect()
+    }
+
+    /// The visions that depend on `vision_id`.
+    pub fn dependents(db: &Database, vision_id: Uuid) -> Vec<&Vision> {
+        db.visions
+            .iter()
+            .filter(|v| v.depends_on.contains(&vision_id))
+            .collect()
+    }
+
+    /// Whether `vision_id` depends on `on`, directly or through others.
+    pub fn depends_on(db: &Database, vision_id: Uuid, on: Uuid) -> bool {
+        let mut seen = HashSet::new();
+        let mut stack = vec![vision_id];
+        while let Some(next) = stack.pop() {
+            if !seen.insert(next) {
+                continue;
+            }
+            let Some(vision) = find(db, next) else {
+                continue;
+            };
+            if vision.depends_on.contains(&on) {
+                return true;
+            }
+            stack.extend(vision.depends_on.iter().copied());
+        }
+        false
+    }
+
+    /// Unfinished visions that nothing blocks but that others wait on, the
+    /// most consequential first: by the stakes they unblock, then by how many
+    /// visions and how long a chain.
+    ///
+    /// With a `target`, only the visions upstream of it count, and each path
+    /// ends at the target.
+    pub fn critical_paths(db: &Database, target: Option<Uuid>) -> Vec<CriticalPath> {
+        let open: HashMap<Uuid, &Vision> = db
+            .visions
+            .iter()
+            .filter(|v| !v.is_achieved())
+            .map(|v| (v.id, v))
+            .collect();
+
+        // Who each open vision enables, restricted to the target's upstream
+        let scope: Option<HashSet<Uuid>> = target.map(|t| upstream(&open, t));
+        let in_scope = |id: &Uuid| scope.as_ref().is_none_or(|s| s.contains(id));
+        let mut enables: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for vision in db.visions.iter().filter(|v| in_scope(&v.id)) {
+            if !open.contains_key(&vision.id) {
+                continue;
+            }
+            for blocker in &vision.depends_on {
+                if open.contains_key(blocker) && in_scope(blocker) {
+                    enables.entry(*blocker).or_default().push(vision.id);
+                }
+            }
+        }
+        let blocked: HashSet<Uuid> = enables.values().flatten().copied().collect();
+
+        let mut longest = HashMap::new();
+        let mut paths: Vec<CriticalPath> = db
+            .visions
+            .iter()
+            .filter(|v| enables.contains_key(&v.id) && !blocked.contains(&v.id))
+            .map(|root| {
+                let downstream = reachable(&enables, root.id);
+                let path = longest_path(&enables, root.id, &mut longest, &mut HashSet::new());
+                CriticalPath {
+                    vision_id: root.id,
+                    title: root.title.clone(),
+                    unblocks: downstream.len(),
+                    stakes_unblocked: downstream.iter().map(|id| u32::from(open[id].stakes)).sum(),
+                    path: path
+                        .iter()
+                        .map(|id| PathStep {
+                            vision_id: *id,
+                            title: open[id].title.clone(),
+                            state: open[id].state.clone(),
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+
+        paths.sort_by(|a, b| {
+            b.stakes_unblocked
+                .cmp(&a.stakes_unblocked)
+                .then(b.unblocks.cmp(&a.unblocks))
+                .then(b.path.len().cmp(&a.path.len()))
+                .then(a.title.cmp(&b.title))
+        });
+        paths
+    }
+}
+
+fn find(db: &Database, id: Uuid) -> Option<&Vision> {
+    db.visions.iter().find(|v| v.id == id)
+}
+
+/// `target` and every open vision it waits on, directly or further up.
+fn upstream(open: &HashMap<Uuid, &Vision>, target: Uuid) -> HashSet<Uuid> {
+    let mut found = HashSet::new();
+    let mut stack = vec![target];
+    while let Some(next) = stack.pop() {
+        if !found.insert(next) {
+            continue;
+        }
+        if let Some(vision) = open.get(&next) {
+            stack.extend(vision.depends_on.iter().filter(|id| open.contains_key(id)));
+        }
+    }
+    found
+}
+
+/// Every vision downstream of `from`, not counting itself.
+fn reachable(enables: &HashMap<Uuid, Vec<Uuid>>, from: Uuid) -> HashSet<Uuid> {
+    let mut found = HashSet::new();
+    let mut stack = enables.get(&from).cloned().unwrap_or_default();
+    while let Some(next) = stack.pop() {
+        if found.insert(next) {
+            stack.extend(enables.get(&next).into_iter().flatten());
+        }
+    }
+    found.remove(&from);
+    found
+}
+
+/// The longest chain starting at `from`, memoized. `visiting` guards
+/// against cycles made outside the CLI.
+fn longest_path(
+    enables: &HashMap<Uuid, Vec<Uuid>>,
+    from: Uuid,
+    memo: &mut HashMap<Uuid, Vec<Uuid>>,
+    visiting: &mut HashSet<Uuid>,
+) -> Vec<Uuid> {
+    if let Some(path) = memo.get(&from) {
+        return path.clone();
+    }
+    if !visiting.insert(from) {
+        return Vec::new();
+    }
+
+    let mut best = Vec::new();
+    for next in enables.get(&from).into_iter().flatten() {
+        let path = longest_path(enables, *next, memo, visiting);
+        if path.len() > best.len() {
+            best = path;
+        }
+    }
+    best.insert(0, from);
+
+    visiting.remove(&from);
+    memo.insert(from, best.clone());
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vision(db: &mut Database, title: &str, depends_on: &[Uuid]) -> Uuid {
+        let mut vision = Vision::new(title.to_string());
+        vision.depends_on = depends_on.to_vec();
+        db.visions.push(vision.clone());
+        vision.id
+    }
+
+    #[test]
+    fn critical_path_starts_upstream_and_follows_the_longest_chain() {
+        let mut db = Database::default();
+        let visa = vision(&mut db, "Get a visa", &[]);
+        let savings = vision(&mut db, "Save money", &[]);
+        let flights = vision(&mut db, "Book flights", &[visa, savings]);
+        let race = vision(&mut db, "Run in Tokyo", &[flights, visa]);
+        let other = vision(&mut db, "Learn the cello", &[savings]);
+
+        assert!(DependencyAnalyzer::depends_on(&db, race, visa));
+        assert!(!DependencyAnalyzer::depends_on(&db, visa, race));
+
+        let paths = DependencyAnalyzer::critical_paths(&db, None);
+        assert_eq!(paths[0].vision_id, savings);
+        assert_eq!(paths[0].unblocks, 3);
+        assert_eq!(paths[1].vision_id, visa);
+        let chain: Vec<Uuid> = paths[1].path.iter().map(|s| s.vision_id).collect();
+        assert_eq!(chain, [visa, flights, race]);
+
+        let paths = DependencyAnalyzer::critical_paths(&db, Some(race));
+        assert_eq!(paths.len(), 2);
+        assert!(paths.iter().all(|p| p.unblocks == 2));
+        assert!(paths
+            .iter()
+            .all(|p| p.path.last().unwrap().vision_id == race));
+        assert!(DependencyAnalyzer::critical_paths(&db, Some(other))[0]
+            .path
+            .iter()
+            .all(|s| s.vision_id != race));
+
+        db.visions
+            .iter_mut()
+            .find(|v| v.id == savings)
+            .unwrap()
+            .state = VisionState::Achieved;
+        assert_eq!(DependencyAnalyzer::open_blockers(&db, flights).len(), 1);
+        assert_eq!(DependencyAnalyzer::critical_paths(&db, None).len(), 1);
+    }
+}
@@ -1,7 +1,11 @@
+pub mod dependency;
 pub mod pattern;
+pub mod rollup;
 pub mod state_machine;
 pub mod tension;
 
+pub use dependency::DependencyAnalyzer;
 pub use pattern::PatternAnalyzer;
+pub use rollup::RollupCalculator;
 pub use state_machine::StateMachine;
 pub use tension::TensionCalculator;
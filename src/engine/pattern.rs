@@ -1,28 +1,49 @@
-use crate::models::{ActionLog, Database};
+use crate::{
+    config::PatternConfig,
+    models::{ActionLog, Database, Vision},
+};
+use serde::Serialize;
 use uuid::Uuid;
 
 pub struct PatternAnalyzer;
 
-#[derive(Debug, Clone)]
+#[derive(Serialize, Debug, Clone)]
 pub enum Pattern {
     Advancing,
     Oscillating,
     Stagnant,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Serialize, Debug, Clone)]
 pub struct PatternMetrics {
-    #[allow(dead_code)]
     pub vision_id: Uuid,
     pub pattern: Pattern,
     pub success_rate: f32,
     pub total_actions: usize,
     pub recent_actions: usize,
+    /// Days counted as recent
+    pub recent_days: u32,
     pub velocity: f32,
 }
 
+/// What achieved visions took to get there.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct AchievementStats {
+    pub achieved: usize,
+    /// Mean days from creation to achievement, where the date is known
+    pub average_days: Option<f32>,
+    /// Mean actions logged per achieved vision
+    pub average_actions: Option<f32>,
+    /// Share of successful actions across achieved visions, from 0 to 1
+    pub success_rate: Option<f32>,
+}
+
 impl PatternAnalyzer {
-    pub fn analyze_vision_pattern(db: &Database, vision_id: Uuid) -> Pattern {
+    pub fn analyze_vision_pattern(
+        db: &Database,
+        vision_id: Uuid,
+        config: &PatternConfig,
+    ) -> Pattern {
         let actions: Vec<&ActionLog> = db
             .actions
             .iter()
@@ -36,23 +57,55 @@ impl PatternAnalyzer {
         let success_rate: f32 =
             actions.iter().filter(|a| a.success).count() as f32 / actions.len() as f32;
 
-        if success_rate > 0.7 {
+        if f64::from(success_rate) > config.advancing_above {
             Pattern::Advancing
-        } else if success_rate > 0.3 {
+        } else if f64::from(success_rate) > config.oscillating_above {
             Pattern::Oscillating
         } else {
             Pattern::Stagnant
         }
     }
 
-    pub fn analyze_all_patterns(db: &Database) -> Vec<(Uuid, Pattern)> {
+    pub fn analyze_all_patterns(db: &Database, config: &PatternConfig) -> Vec<(Uuid, Pattern)> {
         db.visions
             .iter()
-            .map(|v| (v.id, Self::analyze_vision_pattern(db, v.id)))
+            .map(|v| (v.id, Self::analyze_vision_pattern(db, v.id, config)))
             .collect()
     }
 
-    pub fn get_detailed_metrics(db: &Database, vision_id: Uuid) -> Option<PatternMetrics> {
+    pub fn achievement_stats(db: &Database) -> AchievementStats {
+        let achieved: Vec<&Vision> = db.visions.iter().filter(|v| v.is_achieved()).collect();
+        if achieved.is_empty() {
+            return AchievementStats::default();
+        }
+
+        let days: Vec<f32> = achieved
+            .iter()
+            .filter_map(|v| v.achieved_at.map(|at| at - v.created_at))
+            .map(|taken| taken.num_seconds().max(0) as f32 / 86_400.0)
+            .collect();
+        let actions: Vec<&ActionLog> = db
+            .actions
+            .iter()
+            .filter(|a| achieved.iter().any(|v| v.id == a.vision_id))
+            .collect();
+
+        AchievementStats {
+            achieved: achieved.len(),
+            average_days: (!days.is_empty()).then(|| days.iter().sum::<f32>() / days.len() as f32),
+            average_actions: Some(actions.len() as f32 / achieved.len() as f32),
+            success_rate: (!actions.is_empty()).then(|| {
+                actions.iter().filter(|a| a.success).count() as f32 / actions.len() as f32
+            }),
+        }
+    }
+
+    /// Metrics for one vision; "recent" covers `config.recent_days`.
+    pub fn get_detailed_metrics(
+        db: &Database,
+        vision_id: Uuid,
+        config: &PatternConfig,
+    ) -> Option<PatternMetrics> {
         let vision = db.visions.iter().find(|v| v.id == vision_id)?;
 
         let all_actions: Vec<&ActionLog> = db
@@ -68,16 +121,17 @@ impl PatternAnalyzer {
                 success_rate: 0.0,
                 total_actions: 0,
                 recent_actions: 0,
+                recent_days: config.recent_days,
                 velocity: 0.0,
             });
         }
 
         let now = chrono::Utc::now();
-        let seven_days_ago = now - chrono::Duration::days(7);
+        let recent_since = now - chrono::Duration::days(config.recent_days.into());
 
         let recent_actions = all_actions
             .iter()
-            .filter(|a| a.timestamp > seven_days_ago)
+            .filter(|a| a.timestamp > recent_since)
             .count();
         let days_active = now
             .signed_duration_since(vision.created_at)
@@ -90,10 +144,11 @@ impl PatternAnalyzer {
 
         Some(PatternMetrics {
             vision_id,
-            pattern: Self::analyze_vision_pattern(db, vision_id),
+            pattern: Self::analyze_vision_pattern(db, vision_id, config),
             success_rate,
             total_actions: all_actions.len(),
             recent_actions,
+            recent_days: config.recent_days,
             velocity,
         })
     }
@@ -121,7 +176,41 @@ mod tests {
             db.actions.push(action);
         }
 
-        let pattern = PatternAnalyzer::analyze_vision_pattern(&db, vision_id);
+        let config = PatternConfig::default();
+        let pattern = PatternAnalyzer::analyze_vision_pattern(&db, vision_id, &config);
         assert!(matches!(pattern, Pattern::Advancing));
+
+        let strict = PatternConfig {
+            advancing_above: 0.9,
+            ..config
+        };
+        let pattern = PatternAnalyzer::analyze_vision_pattern(&db, vision_id, &strict);
+        assert!(matches!(pattern, Pattern::Oscillating));
+    }
+
+    #[test]
+    fn achievement_stats_cover_only_achieved_visions() {
+        let mut db = Database::default();
+        assert_eq!(
+            PatternAnalyzer::achievement_stats(&db),
+            AchievementStats::default()
+        );
+
+        let mut achieved = Vision::new("Run a marathon".to_string());
+        achieved.state = crate::models::VisionState::Achieved;
+        achieved.achieved_at = Some(achieved.created_at + chrono::Duration::days(30));
+        let open = Vision::new("Learn the cello".to_string());
+        for (vision, success) in [(&achieved, true), (&achieved, false), (&open, true)] {
+            let mut action = ActionLog::new(vision.id, "action".to_string());
+            action.success = success;
+            db.actions.push(action);
+        }
+        db.visions = vec![achieved, open];
+
+        let stats = PatternAnalyzer::achievement_stats(&db);
+        assert_eq!(stats.achieved, 1);
+        assert_eq!(stats.average_days, Some(30.0));
+        assert_eq!(stats.average_actions, Some(2.0));
+        assert_eq!(stats.success_rate, Some(0.5));
     }
 }
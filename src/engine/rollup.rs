@@ -0,0 +1,129 @@
+use crate::{
+    engine::TensionCalculator,
+    models::{Database, Vision, VisionState},
+};
+use serde::Serialize;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+pub struct RollupCalculator;
+
+/// A parent's progress and tension, taken from its sub-visions and weighted
+/// by their stakes.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Rollup {
+    pub vision_id: Uuid,
+    /// From 0 to 1
+    pub progress: f32,
+    /// From 0 to 100, like a single vision's tension score
+    pub tension: f32,
+    pub children: usize,
+    pub achieved_children: usize,
+}
+
+impl Rollup {
+    pub fn all_achieved(&self) -> bool {
+        self.achieved_children == self.children
+    }
+}
+
+impl RollupCalculator {
+    /// How far along a vision is by its state alone.
+    pub fn state_progress(state: &VisionState) -> f32 {
+        match state {
+            VisionState::Conceived => 0.0,
+            VisionState::Reassessed => 0.25,
+            VisionState::InProgress | VisionState::Blocked => 0.5,
+            VisionState::Achieved => 1.0,
+        }
+    }
+
+    /// The rollup of a vision's sub-visions, or `None` if it has none.
+    ///
+    /// Each sub-vision counts with its own rollup when it has sub-visions of
+    /// its own, and with its state and tension score otherwise. Achieved
+    /// sub-visions count as done with no tension.
+    pub fn calculate(db: &Database, vision_id: Uuid) -> Option<Rollup> {
+        let mut seen = HashSet::from([vision_id]);
+        Self::rollup(db, vision_id, &mut seen)
+    }
+
+    fn rollup(db: &Database, vision_id: Uuid, seen: &mut HashSet<Uuid>) -> Option<Rollup> {
+        // Guards against cycles made outside the CLI
+        let children: Vec<&Vision> = db
+            .visions
+            .iter()
+            .filter(|v| v.parent == Some(vision_id) && seen.insert(v.id))
+            .collect();
+        if children.is_empty() {
+            return None;
+        }
+
+        let mut weight = 0.0;
+        let mut progress = 0.0;
+        let mut tension = 0.0;
+        for child in &children {
+            let stakes = f32::from(child.stakes.max(1));
+            let (child_progress, child_tension) = if child.is_achieved() {
+                (1.0, 0.0)
+            } else if let Some(rollup) = Self::rollup(db, child.id, seen) {
+                (rollup.progress, rollup.tension)
+            } else {
+                (
+                    Self::state_progress(&child.state),
+                    TensionCalculator::calculate_vision_tension(db, child.id)
+                        .map_or(0.0, |t| t.tension_score),
+                )
+            };
+            weight += stakes;
+            progress += stakes * child_progress;
+            tension += stakes * child_tension;
+        }
+
+        Some(Rollup {
+            vision_id,
+            progress: progress / weight,
+            tension: tension / weight,
+            children: children.len(),
+            achieved_children: children.iter().filter(|c| c.is_achieved()).count(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn child(db: &mut Database, parent: Uuid, state: VisionState, stakes: u8) -> Uuid {
+        let mut vision = Vision::new("child".to_string());
+        vision.parent = Some(parent);
+        vision.state = state;
+        vision.stakes = stakes;
+        db.visions.push(vision.clone());
+        vision.id
+    }
+
+    #[test]
+    fn rollup_weights_sub_visions_by_stakes() {
+        let mut db = Database::default();
+        let parent = Vision::new("parent".to_string());
+        let parent_id = parent.id;
+        db.visions.push(parent);
+        assert_eq!(RollupCalculator::calculate(&db, parent_id), None);
+
+        child(&mut db, parent_id, VisionState::Achieved, 3);
+        let open = child(&mut db, parent_id, VisionState::Conceived, 1);
+        let rollup = RollupCalculator::calculate(&db, parent_id).unwrap();
+        assert_eq!(rollup.progress, 0.75);
+        assert_eq!(rollup.tension, 25.0);
+        assert_eq!((rollup.children, rollup.achieved_children), (2, 1));
+        assert!(!rollup.all_achieved());
+
+        // A sub-vision with sub-visions of its own counts with its rollup.
+        child(&mut db, open, VisionState::Achieved, 3);
+        let rollup = RollupCalculator::calculate(&db, parent_id).unwrap();
+        assert_eq!(rollup.progress, 1.0);
+        assert_eq!(rollup.tension, 0.0);
+        assert!(!rollup.all_achieved());
+    }
+}
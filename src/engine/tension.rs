@@ -1,24 +1,47 @@
-use crate::models::{Database, VisionState};
+use crate::{
+    engine::DependencyAnalyzer,
+    models::{Database, VisionState},
+};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
 pub struct TensionCalculator;
 
-#[derive(Debug, Clone)]
+/// Share of a blocked vision's tension taken from its most tense open
+/// blocker; the rest is its own.
+pub const INHERITED_SHARE: f32 = 0.5;
+
+#[derive(Serialize, Debug, Clone)]
 pub struct VisionTension {
-    #[allow(dead_code)]
     pub vision_id: Uuid,
     pub vision_title: String,
     pub tension_score: f32,
     pub action_count: usize,
-    #[allow(dead_code)]
     pub reality_count: usize,
-    #[allow(dead_code)]
     pub state: VisionState,
     pub days_active: u64,
+    /// Visions it depends on that are not achieved yet
+    pub open_blockers: usize,
 }
 
 impl TensionCalculator {
+    /// A vision's tension from its actions and realities. While it waits on
+    /// other visions, part of it comes from the most tense of them instead.
     pub fn calculate_vision_tension(db: &Database, vision_id: Uuid) -> Option<VisionTension> {
+        Self::tension(db, vision_id, &mut HashSet::new(), &mut HashMap::new())
+    }
+
+    /// `path` holds the visions being calculated further down, to guard
+    /// against dependency cycles made outside the CLI. `scores` caches each
+    /// vision's score, so visions reached along several dependency chains
+    /// are only calculated once.
+    fn tension(
+        db: &Database,
+        vision_id: Uuid,
+        path: &mut HashSet<Uuid>,
+        scores: &mut HashMap<Uuid, f32>,
+    ) -> Option<VisionTension> {
         let vision = db.visions.iter().find(|v| v.id == vision_id)?;
 
         let action_count = db
@@ -39,12 +62,36 @@ impl TensionCalculator {
         } else {
             1.0 - (action_count as f32 / (total_entries + 1) as f32)
         };
-        let tension_score = if vision.completed {
+        let own_score = if vision.is_achieved() {
             0.0
         } else {
             gap_factor * 100.0
         };
 
+        let blockers = DependencyAnalyzer::open_blockers(db, vision_id);
+        path.insert(vision_id);
+        let mut inherited: Option<f32> = None;
+        for blocker in &blockers {
+            if path.contains(&blocker.id) {
+                continue;
+            }
+            let score = match scores.get(&blocker.id) {
+                Some(score) => Some(*score),
+                None => Self::tension(db, blocker.id, path, scores).map(|t| t.tension_score),
+            };
+            if let Some(score) = score {
+                inherited = Some(inherited.map_or(score, |i| i.max(score)));
+            }
+        }
+        path.remove(&vision_id);
+        let tension_score = match inherited {
+            Some(inherited) if !vision.is_achieved() => {
+                (1.0 - INHERITED_SHARE) * own_score + INHERITED_SHARE * inherited
+            }
+            _ => own_score,
+        };
+        scores.insert(vision_id, tension_score);
+
         let days_active = (chrono::Utc::now()
             .signed_duration_since(vision.created_at)
             .num_days() as u64)
@@ -58,13 +105,15 @@ impl TensionCalculator {
             reality_count,
             state: vision.state.clone(),
             days_active,
+            open_blockers: blockers.len(),
         })
     }
 
     pub fn calculate_all_tensions(db: &Database) -> Vec<VisionTension> {
+        let mut scores = HashMap::new();
         db.visions
             .iter()
-            .filter_map(|v| Self::calculate_vision_tension(db, v.id))
+            .filter_map(|v| Self::tension(db, v.id, &mut HashSet::new(), &mut scores))
             .collect()
     }
 
@@ -79,3 +128,35 @@ impl TensionCalculator {
             .next()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Vision;
+
+    #[test]
+    fn diamond_dependencies_are_calculated_once() {
+        // 40 layers of two visions, each depending on both of the next
+        // layer: 2^40 chains, which only finishes if shared visions are
+        // calculated once.
+        let mut db = Database::default();
+        let mut below: Vec<Uuid> = Vec::new();
+        for layer in 0..40 {
+            let mut ids = Vec::new();
+            for side in 0..2 {
+                let mut vision = Vision::new(format!("{}-{}", layer, side));
+                vision.depends_on = below.clone();
+                ids.push(vision.id);
+                db.visions.push(vision);
+            }
+            below = ids;
+        }
+
+        let tensions = TensionCalculator::calculate_all_tensions(&db);
+        assert_eq!(tensions.len(), 80);
+        // Nothing has entries, so every score is 100 however it is inherited
+        assert!(tensions.iter().all(|t| t.tension_score == 100.0));
+        let top = TensionCalculator::calculate_vision_tension(&db, below[0]).unwrap();
+        assert_eq!(top.open_blockers, 2);
+    }
+}
@@ -21,6 +21,9 @@ pub enum TensileError {
     #[error("Not found: {0}")]
     NotFound(String),
 
+    #[error("Ambiguous: {0}")]
+    Ambiguous(String),
+
     #[error("State conflict: {0}")]
     #[allow(dead_code)]
     StateConflict(String),
@@ -28,6 +31,10 @@ pub enum TensileError {
     #[error("Invalid state transition: {0} -> {1}")]
     InvalidStateTransition(String, String),
 
+    #[error("Sync error: {0}")]
+    #[allow(dead_code)]
+    Sync(String),
+
     #[error("Unknown error: {0}")]
     #[allow(dead_code)]
     Unknown(String),
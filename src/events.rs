@@ -0,0 +1,162 @@
+//! Domain events and the bus that delivers them.
+//!
+//! `Session` queues an event for every change it makes and publishes them
+//! once the changes are saved, so subscribers never hear about changes that
+//! were rolled back:
+//!
+//! ```no_run
+//! use tensile::service::Session;
+//!
+//! let mut session = Session::open()?;
+//! session.subscribe(|event| eprintln!("{}", event.name()));
+//! session.create_vision("Learn the cello")?;
+//! session.commit()?; // prints "vision_created"
+//! # Ok::<(), tensile::TensileError>(())
+//! ```
+
+use crate::{
+    models::{ActionLog, RealityAssessment, Vision, VisionState},
+    service::Deletion,
+};
+use serde::Serialize;
+use std::fmt;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Something that happened to the data. Serializes with its name in an
+/// `event` field, e.g. `{"event": "action_logged", ...}`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    VisionCreated {
+        vision: Vision,
+    },
+    /// Renamed, described, moved or reordered
+    VisionUpdated {
+        vision: Vision,
+    },
+    VisionDeleted {
+        deletion: Deletion,
+    },
+    StateChanged {
+        vision_id: Uuid,
+        vision_title: String,
+        from: VisionState,
+        to: VisionState,
+    },
+    /// Follows the `StateChanged` that reached `Achieved`
+    VisionAchieved {
+        vision_id: Uuid,
+        vision_title: String,
+        retrospective: Option<String>,
+    },
+    RealityRecorded {
+        assessment: RealityAssessment,
+    },
+    ActionLogged {
+        vision_id: Uuid,
+        vision_title: String,
+        action: ActionLog,
+    },
+    ActionAmended {
+        action: ActionLog,
+    },
+    ActionDeleted {
+        action: ActionLog,
+    },
+    FocusChanged {
+        vision_id: Option<Uuid>,
+    },
+    /// Records came in from an export
+    DataImported {
+        added: usize,
+        updated: usize,
+        removed: usize,
+    },
+    /// A vision's tension score moved across `hooks.tension_threshold`
+    TensionThresholdCrossed {
+        vision_id: Uuid,
+        vision_title: String,
+        threshold: f64,
+        from: f32,
+        to: f32,
+        /// "above" or "below"
+        direction: &'static str,
+    },
+}
+
+impl Event {
+    /// The event's name, as serialized.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::VisionCreated { .. } => "vision_created",
+            Event::VisionUpdated { .. } => "vision_updated",
+            Event::VisionDeleted { .. } => "vision_deleted",
+            Event::StateChanged { .. } => "state_changed",
+            Event::VisionAchieved { .. } => "vision_achieved",
+            Event::RealityRecorded { .. } => "reality_recorded",
+            Event::ActionLogged { .. } => "action_logged",
+            Event::ActionAmended { .. } => "action_amended",
+            Event::ActionDeleted { .. } => "action_deleted",
+            Event::FocusChanged { .. } => "focus_changed",
+            Event::DataImported { .. } => "data_imported",
+            Event::TensionThresholdCrossed { .. } => "tension_threshold_crossed",
+        }
+    }
+}
+
+type Subscriber = Arc<dyn Fn(&Event) + Send + Sync>;
+
+/// Delivers each published event to every subscriber, in the order they
+/// subscribed. Clones share their subscribers.
+#[derive(Clone, Default)]
+pub struct EventBus {
+    subscribers: Vec<Subscriber>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        EventBus::default()
+    }
+
+    pub fn subscribe(&mut self, subscriber: impl Fn(&Event) + Send + Sync + 'static) {
+        self.subscribers.push(Arc::new(subscriber));
+    }
+
+    pub fn publish(&self, event: &Event) {
+        for subscriber in &self.subscribers {
+            subscriber(event);
+        }
+    }
+}
+
+impl fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventBus")
+            .field("subscribers", &self.subscribers.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn subscribers_hear_events_in_order() {
+        let heard = Arc::new(Mutex::new(Vec::new()));
+        let mut bus = EventBus::new();
+        for tag in ["first", "second"] {
+            let heard = Arc::clone(&heard);
+            bus.subscribe(move |event| heard.lock().unwrap().push((tag, event.name())));
+        }
+
+        bus.clone()
+            .publish(&Event::FocusChanged { vision_id: None });
+        assert_eq!(
+            *heard.lock().unwrap(),
+            [("first", "focus_changed"), ("second", "focus_changed")]
+        );
+    }
+}
@@ -0,0 +1,133 @@
+//! External commands run when data changes, configured in the `[hooks]`
+//! section of `config.toml`.
+//!
+//! Hooks subscribe to the session's `EventBus`, so each runs through the
+//! shell once its changes are saved, with the event as JSON on stdin and the
+//! hook's name in `TENSILE_EVENT`. A hook that fails
+//! or runs past its timeout is reported as a warning; it never undoes or
+//! blocks the change.
+
+use crate::{
+    config::HooksConfig,
+    events::{Event, EventBus},
+};
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// The `[hooks]` setting run for an event, if any event of its kind can
+/// have a hook.
+pub fn hook_name(event: &Event) -> Option<&'static str> {
+    match event {
+        Event::ActionLogged { .. } => Some("on_action_logged"),
+        Event::StateChanged { .. } => Some("on_state_transition"),
+        Event::VisionAchieved { .. } => Some("on_vision_achieved"),
+        Event::TensionThresholdCrossed { .. } => Some("on_tension_threshold_crossed"),
+        _ => None,
+    }
+}
+
+/// Run the configured hooks for events published on `bus`. Does nothing
+/// when no hook is configured.
+pub fn subscribe(bus: &mut EventBus, config: &HooksConfig) {
+    if config.is_empty() {
+        return;
+    }
+    let config = config.clone();
+    bus.subscribe(move |event| run(&config, event));
+}
+
+/// Run the configured hook for an event, if any, warning on stderr if it
+/// fails.
+pub fn run(config: &HooksConfig, event: &Event) {
+    let Some((name, command)) =
+        hook_name(event).and_then(|name| Some((name, config.command(name)?)))
+    else {
+        return;
+    };
+    let timeout = Duration::from_secs(config.timeout_secs);
+    if let Err(message) = run_hook(command, name, event, timeout) {
+        eprintln!("⚠ Hook {} failed: {}", name, message);
+    }
+}
+
+fn run_hook(command: &str, name: &str, event: &Event, timeout: Duration) -> Result<(), String> {
+    let payload = serde_json::to_vec(event).map_err(|e| e.to_string())?;
+
+    let mut child = shell(command)
+        .env("TENSILE_EVENT", name)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("could not start `{}`: {}", command, e))?;
+
+    // Write from a thread, so a hook that never reads a payload larger than
+    // the pipe buffer still runs into the timeout. A hook that ignores its
+    // input closes the pipe early; that is fine.
+    if let Some(mut stdin) = child.stdin.take() {
+        std::thread::spawn(move || {
+            let _ = stdin.write_all(&payload);
+        });
+    }
+    // Read stderr as it comes so a chatty hook cannot fill the pipe and stall.
+    // Lines go over a channel: a process the hook left in the background may
+    // hold the pipe open long after the hook itself is gone.
+    let (lines, received) = mpsc::channel();
+    if let Some(stderr) = child.stderr.take() {
+        std::thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                if lines.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait().map_err(|e| e.to_string())? {
+            Some(status) => break status,
+            None if started.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!(
+                    "`{}` timed out after {}s",
+                    command,
+                    timeout.as_secs()
+                ));
+            }
+            None => std::thread::sleep(Duration::from_millis(20)),
+        }
+    };
+    if status.success() {
+        return Ok(());
+    }
+
+    // Whatever arrives by the end of the timeout, or until the pipe closes
+    let mut last = None;
+    while let Ok(line) = received.recv_timeout(timeout.saturating_sub(started.elapsed())) {
+        if !line.trim().is_empty() {
+            last = Some(line);
+        }
+    }
+    let detail = last
+        .map(|line| format!(": {}", line.trim()))
+        .unwrap_or_default();
+    Err(format!("`{}` exited with {}{}", command, status, detail))
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
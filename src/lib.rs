@@ -2,8 +2,15 @@ pub mod cli;
 pub mod config;
 pub mod engine;
 pub mod error;
+pub mod events;
+pub mod hooks;
 pub mod models;
 pub mod persistence;
+pub mod service;
+pub mod sync;
+
+#[cfg(feature = "cloud")]
+pub mod cloud;
 
 #[cfg(feature = "tui")]
 pub mod tui;
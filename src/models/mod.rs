@@ -1,12 +1,16 @@
 pub mod action;
 pub mod reality;
+pub mod template;
+pub mod tombstone;
 pub mod user;
 pub mod vision;
 
 pub use action::ActionLog;
 pub use reality::RealityAssessment;
+pub use template::VisionTemplate;
+pub use tombstone::{RecordKind, Tombstone};
 pub use user::User;
-pub use vision::{Vision, VisionState};
+pub use vision::{Blocker, Vision, VisionState};
 
 use serde::{Deserialize, Serialize};
 
@@ -16,4 +20,78 @@ pub struct Database {
     pub realities: Vec<RealityAssessment>,
     pub actions: Vec<ActionLog>,
     pub user: Option<User>,
+    #[serde(default)]
+    pub tombstones: Vec<Tombstone>,
+}
+
+impl Database {
+    /// Delete a record and leave a tombstone behind. Deleting a vision also
+    /// deletes its entries and detaches its children.
+    pub fn remove_record(&mut self, kind: RecordKind, id: uuid::Uuid) {
+        match kind {
+            RecordKind::Vision => {
+                let entries: Vec<(RecordKind, uuid::Uuid)> = self
+                    .realities
+                    .iter()
+                    .filter(|r| r.vision_id == id)
+                    .map(|r| (RecordKind::Reality, r.id))
+                    .chain(
+                        self.actions
+                            .iter()
+                            .filter(|a| a.vision_id == id)
+                            .map(|a| (RecordKind::Action, a.id)),
+                    )
+                    .collect();
+                for (kind, entry_id) in entries {
+                    self.remove_record(kind, entry_id);
+                }
+
+                for child in self.visions.iter_mut().filter(|v| v.parent == Some(id)) {
+                    child.parent = None;
+                    child.touch();
+                }
+                for dependent in self
+                    .visions
+                    .iter_mut()
+                    .filter(|v| v.depends_on.contains(&id))
+                {
+                    dependent.depends_on.retain(|d| *d != id);
+                    dependent.touch();
+                }
+                self.visions.retain(|v| v.id != id);
+                self.rebuild_children();
+
+                if let Some(user) = self.user.as_mut() {
+                    if user.current_focus == Some(id) {
+                        user.current_focus = None;
+                    }
+                }
+            }
+            RecordKind::Reality => self.realities.retain(|r| r.id != id),
+            RecordKind::Action => self.actions.retain(|a| a.id != id),
+        }
+
+        if !self.tombstones.iter().any(|t| t.id == id) {
+            self.tombstones.push(Tombstone::new(kind, id));
+        }
+    }
+
+    /// Recompute every vision's `children` from the `parent` links.
+    pub fn rebuild_children(&mut self) {
+        let links: Vec<(uuid::Uuid, uuid::Uuid)> = self
+            .visions
+            .iter()
+            .filter_map(|v| v.parent.map(|p| (p, v.id)))
+            .collect();
+
+        for vision in &mut self.visions {
+            vision.children.clear();
+        }
+
+        for (parent_id, child_id) in links {
+            if let Some(parent) = self.visions.iter_mut().find(|v| v.id == parent_id) {
+                parent.children.push(child_id);
+            }
+        }
+    }
 }
@@ -2,21 +2,34 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct RealityAssessment {
     pub id: Uuid,
     pub vision_id: Uuid,
     pub entry: String,
     pub timestamp: DateTime<Utc>,
+    #[serde(default)]
+    pub updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub updated_by: Option<Uuid>,
 }
 
 impl RealityAssessment {
     pub fn new(vision_id: Uuid, entry: String) -> Self {
+        let now = Utc::now();
         RealityAssessment {
             id: Uuid::new_v4(),
             vision_id,
             entry,
-            timestamp: Utc::now(),
+            timestamp: now,
+            updated_at: now,
+            updated_by: None,
         }
     }
+
+    /// Mark the entry as modified on this device.
+    pub fn touch(&mut self) {
+        self.updated_at = Utc::now();
+        self.updated_by = None;
+    }
 }
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+/// A reusable vision tree: titles, descriptions and hierarchy, without
+/// ids, states or entries. Applying it creates fresh visions.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VisionTemplate {
+    pub title: String,
+    /// The desired state
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Sub-visions in sibling order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<VisionTemplate>,
+}
+
+impl VisionTemplate {
+    /// Number of visions the template creates.
+    pub fn size(&self) -> usize {
+        1 + self
+            .children
+            .iter()
+            .map(VisionTemplate::size)
+            .sum::<usize>()
+    }
+}
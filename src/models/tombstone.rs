@@ -0,0 +1,54 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RecordKind {
+    Vision,
+    Reality,
+    Action,
+}
+
+impl std::fmt::Display for RecordKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordKind::Vision => write!(f, "Vision"),
+            RecordKind::Reality => write!(f, "Reality"),
+            RecordKind::Action => write!(f, "Action"),
+        }
+    }
+}
+
+impl std::str::FromStr for RecordKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "vision" => Ok(RecordKind::Vision),
+            "reality" => Ok(RecordKind::Reality),
+            "action" => Ok(RecordKind::Action),
+            _ => Err(format!("Unknown record kind: {}", s)),
+        }
+    }
+}
+
+/// Marker left behind when a record is deleted, so that sync can
+/// propagate the deletion instead of resurrecting the record.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Tombstone {
+    pub id: Uuid,
+    pub kind: RecordKind,
+    pub deleted_at: DateTime<Utc>,
+    pub deleted_by: Option<Uuid>,
+}
+
+impl Tombstone {
+    pub fn new(kind: RecordKind, id: Uuid) -> Self {
+        Tombstone {
+            id,
+            kind,
+            deleted_at: Utc::now(),
+            deleted_by: None,
+        }
+    }
+}
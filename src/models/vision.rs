@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -11,6 +11,17 @@ pub enum VisionState {
     Achieved,
 }
 
+impl VisionState {
+    /// Every state, in lifecycle order.
+    pub const ALL: [VisionState; 5] = [
+        VisionState::Conceived,
+        VisionState::InProgress,
+        VisionState::Blocked,
+        VisionState::Reassessed,
+        VisionState::Achieved,
+    ];
+}
+
 impl std::str::FromStr for VisionState {
     type Err = String;
 
@@ -38,29 +49,106 @@ impl std::fmt::Display for VisionState {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// What keeps a blocked vision from moving, recorded as it became blocked.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Blocker {
+    pub reason: String,
+    /// What has to happen for it to move again
+    #[serde(default)]
+    pub unblock_when: Option<String>,
+    /// When to take another look at it
+    #[serde(default)]
+    pub unblock_on: Option<NaiveDate>,
+    pub since: DateTime<Utc>,
+}
+
+impl Blocker {
+    pub fn new(reason: impl Into<String>) -> Self {
+        Blocker {
+            reason: reason.into(),
+            unblock_when: None,
+            unblock_on: None,
+            since: Utc::now(),
+        }
+    }
+
+    /// Whether its unblock date has come.
+    pub fn is_due(&self, today: NaiveDate) -> bool {
+        self.unblock_on.is_some_and(|date| date <= today)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Vision {
     pub id: Uuid,
     pub title: String,
     pub description: Option<String>,
     pub parent: Option<Uuid>,
     pub children: Vec<Uuid>,
+    /// Visions that block this one until they are achieved; separate from
+    /// the parent/child decomposition
+    #[serde(default)]
+    pub depends_on: Vec<Uuid>,
+    /// Order among siblings, lowest first
+    #[serde(default)]
+    pub position: u32,
+    /// What is at stake, from 1 (little) to 5 (a lot); weighs the vision
+    /// against its siblings in its parent's rollup
+    #[serde(default = "default_stakes")]
+    pub stakes: u8,
     pub created_at: DateTime<Utc>,
-    pub completed: bool,
     pub state: VisionState,
+    /// When the vision last became achieved
+    #[serde(default)]
+    pub achieved_at: Option<DateTime<Utc>>,
+    /// What made it work, written when it was achieved
+    #[serde(default)]
+    pub retrospective: Option<String>,
+    /// What blocks it, while it is blocked
+    #[serde(default)]
+    pub blocker: Option<Blocker>,
+    #[serde(default)]
+    pub updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub updated_by: Option<Uuid>,
+}
+
+fn default_stakes() -> u8 {
+    Vision::DEFAULT_STAKES
 }
 
 impl Vision {
+    pub const DEFAULT_STAKES: u8 = 3;
+    pub const MAX_STAKES: u8 = 5;
+
     pub fn new(title: String) -> Self {
+        let now = Utc::now();
         Vision {
             id: Uuid::new_v4(),
             title,
             description: None,
             parent: None,
             children: vec![],
-            created_at: Utc::now(),
-            completed: false,
+            depends_on: vec![],
+            position: 0,
+            stakes: Vision::DEFAULT_STAKES,
+            created_at: now,
             state: VisionState::Conceived,
+            achieved_at: None,
+            retrospective: None,
+            blocker: None,
+            updated_at: now,
+            updated_by: None,
         }
     }
+
+    pub fn is_achieved(&self) -> bool {
+        self.state == VisionState::Achieved
+    }
+
+    /// Mark the vision as modified on this device.
+    pub fn touch(&mut self) {
+        self.updated_at = Utc::now();
+        self.updated_by = None;
+    }
 }
@@ -0,0 +1,134 @@
+//! Published events, kept in the local database so other processes can
+//! follow them with `tensile events --follow`.
+
+use super::SqliteStore;
+use crate::{
+    config,
+    error::{TensileError, TensileResult},
+    events::Event,
+};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use serde_json::Value;
+use std::path::PathBuf;
+
+/// Entries kept; older ones are pruned as new ones arrive.
+const KEEP: i64 = 10_000;
+
+/// One journaled event: the event's own fields plus its place in the
+/// journal.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct JournalEntry {
+    /// Increases with every entry; resume after the last one seen
+    pub seq: i64,
+    pub recorded_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub event: Value,
+}
+
+pub struct EventJournal {
+    db_path: PathBuf,
+}
+
+impl EventJournal {
+    /// The journal in the database file at `db_path`, or the configured
+    /// database when `None`.
+    pub fn new(db_path: Option<PathBuf>) -> TensileResult<Self> {
+        let db_path = match db_path {
+            Some(path) => path,
+            None => config::db_path()?,
+        };
+        // Creates the file and the journal table if needed.
+        SqliteStore::new(Some(db_path.clone()))?;
+        Ok(EventJournal { db_path })
+    }
+
+    fn connection(&self) -> TensileResult<Connection> {
+        let conn = Connection::open(&self.db_path)?;
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        Ok(conn)
+    }
+
+    pub fn append(&self, event: &Event) -> TensileResult<i64> {
+        let payload =
+            serde_json::to_string(event).map_err(|e| TensileError::Serialization(e.to_string()))?;
+        let conn = self.connection()?;
+        conn.execute(
+            "INSERT INTO event_journal (recorded_at, event, payload) VALUES (?1, ?2, ?3)",
+            params![Utc::now().to_rfc3339(), event.name(), payload],
+        )?;
+        let seq = conn.last_insert_rowid();
+        conn.execute(
+            "DELETE FROM event_journal WHERE seq <= ?1",
+            params![seq - KEEP],
+        )?;
+        Ok(seq)
+    }
+
+    /// Entries after `seq`, oldest first, at most `limit` of them.
+    pub fn after(&self, seq: i64, limit: usize) -> TensileResult<Vec<JournalEntry>> {
+        let conn = self.connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT seq, recorded_at, payload FROM event_journal WHERE seq > ?1 ORDER BY seq LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![seq, limit as i64], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            let (seq, recorded_at, payload) = row?;
+            entries.push(JournalEntry {
+                seq,
+                recorded_at: DateTime::parse_from_rfc3339(&recorded_at)
+                    .map_err(|e| TensileError::Parse(e.to_string()))?
+                    .with_timezone(&Utc),
+                event: serde_json::from_str(&payload)
+                    .map_err(|e| TensileError::Serialization(e.to_string()))?,
+            });
+        }
+        Ok(entries)
+    }
+
+    /// The last `count` entries, oldest first.
+    pub fn latest(&self, count: usize) -> TensileResult<Vec<JournalEntry>> {
+        self.after(self.last_seq()?.saturating_sub(count as i64), count)
+    }
+
+    /// Sequence number of the newest entry, or 0 when there is none.
+    pub fn last_seq(&self) -> TensileResult<i64> {
+        let conn = self.connection()?;
+        Ok(conn.query_row(
+            "SELECT COALESCE(MAX(seq), 0) FROM event_journal",
+            [],
+            |row| row.get(0),
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_resume_after_a_sequence_number() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = EventJournal::new(Some(dir.path().join("tensile.db"))).unwrap();
+        assert_eq!(journal.last_seq().unwrap(), 0);
+
+        for _ in 0..3 {
+            journal
+                .append(&Event::FocusChanged { vision_id: None })
+                .unwrap();
+        }
+        let entries = journal.after(1, 10).unwrap();
+        assert_eq!(entries.iter().map(|e| e.seq).collect::<Vec<_>>(), [2, 3]);
+        assert_eq!(entries[0].event["event"], "focus_changed");
+        assert_eq!(journal.latest(1).unwrap()[0].seq, 3);
+    }
+}
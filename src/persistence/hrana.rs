@@ -0,0 +1,365 @@
+//! Minimal client for Hrana over HTTP (v2), the remote protocol of `sqld`.
+//!
+//! Every call is a single `POST /v2/pipeline` that opens a stream, runs its
+//! requests and closes the stream again, so no baton is ever carried over.
+use crate::error::TensileError;
+use rusqlite::types::Value;
+use serde::{Deserialize, Serialize};
+
+/// Why a pipeline failed. Being offline is expected for a local-first CLI
+/// and lets callers fall back to the replica; anything else is a real error.
+#[derive(Debug)]
+pub(crate) enum HranaError {
+    Offline(String),
+    Remote(String),
+}
+
+impl From<HranaError> for TensileError {
+    fn from(e: HranaError) -> Self {
+        match e {
+            HranaError::Offline(msg) => TensileError::Sync(format!("sqld unreachable: {}", msg)),
+            HranaError::Remote(msg) => TensileError::Sync(msg),
+        }
+    }
+}
+
+pub(crate) type HranaResult<T> = Result<T, HranaError>;
+
+/// Result rows of one statement.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Rows {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Stmt {
+    pub sql: String,
+    pub args: Vec<Value>,
+}
+
+impl Stmt {
+    pub fn new(sql: impl Into<String>) -> Self {
+        Stmt {
+            sql: sql.into(),
+            args: vec![],
+        }
+    }
+
+    pub fn with_args(sql: impl Into<String>, args: Vec<Value>) -> Self {
+        Stmt {
+            sql: sql.into(),
+            args,
+        }
+    }
+}
+
+pub(crate) struct HranaClient {
+    pipeline_url: String,
+    token: Option<String>,
+    client: reqwest::blocking::Client,
+}
+
+impl HranaClient {
+    /// Accepts `libsql://`, `https://` and `http://` URLs.
+    pub fn new(url: &str, token: Option<String>) -> Self {
+        let base = match url.strip_prefix("libsql://") {
+            Some(host) => format!("https://{}", host),
+            None => url.to_string(),
+        };
+
+        HranaClient {
+            pipeline_url: format!("{}/v2/pipeline", base.trim_end_matches('/')),
+            token,
+            client: reqwest::blocking::Client::builder()
+                .timeout(std::time::Duration::from_secs(10))
+                .build()
+                .expect("HTTP client with default TLS settings"),
+        }
+    }
+
+    /// Run statements in order and return the rows of each.
+    pub fn query_all(&self, stmts: Vec<Stmt>) -> HranaResult<Vec<Rows>> {
+        let requests = stmts
+            .into_iter()
+            .map(|stmt| StreamRequest::Execute {
+                stmt: WireStmt::from(stmt),
+            })
+            .collect();
+
+        self.pipeline(requests)?
+            .into_iter()
+            .map(|response| match response {
+                StreamResponse::Execute { result } => Ok(result.into()),
+                other => Err(unexpected(&other)),
+            })
+            .collect()
+    }
+
+    /// Run a semicolon-separated script, e.g. a migration.
+    pub fn execute_script(&self, sql: &str) -> HranaResult<()> {
+        self.pipeline(vec![StreamRequest::Sequence {
+            sql: sql.to_string(),
+        }])?;
+        Ok(())
+    }
+
+    /// Run statements atomically: each step only runs if the previous one
+    /// succeeded, and a failed commit rolls everything back.
+    pub fn transaction(&self, stmts: Vec<Stmt>) -> HranaResult<()> {
+        match self.run_transaction(stmts)? {
+            Some((_, message)) => Err(HranaError::Remote(message)),
+            None => Ok(()),
+        }
+    }
+
+    /// Like `transaction`, but starting with `guard`. Returns `false`, with
+    /// nothing changed, if the guard fails.
+    pub fn guarded_transaction(&self, guard: Stmt, stmts: Vec<Stmt>) -> HranaResult<bool> {
+        let stmts = std::iter::once(guard).chain(stmts).collect();
+        match self.run_transaction(stmts)? {
+            // Step 0 is BEGIN
+            Some((1, _)) => Ok(false),
+            Some((_, message)) => Err(HranaError::Remote(message)),
+            None => Ok(true),
+        }
+    }
+
+    /// The first step that failed and its error, if any.
+    fn run_transaction(&self, stmts: Vec<Stmt>) -> HranaResult<Option<(usize, String)>> {
+        let mut steps = vec![BatchStep {
+            condition: None,
+            stmt: Stmt::new("BEGIN").into(),
+        }];
+        for stmt in stmts.into_iter().chain([Stmt::new("COMMIT")]) {
+            steps.push(BatchStep {
+                condition: Some(BatchCond::Ok {
+                    step: steps.len() - 1,
+                }),
+                stmt: stmt.into(),
+            });
+        }
+        let commit = steps.len() - 1;
+        steps.push(BatchStep {
+            condition: Some(BatchCond::Not {
+                cond: Box::new(BatchCond::Ok { step: commit }),
+            }),
+            stmt: Stmt::new("ROLLBACK").into(),
+        });
+
+        let response = self
+            .pipeline(vec![StreamRequest::Batch {
+                batch: Batch { steps },
+            }])?
+            .remove(0);
+
+        match response {
+            StreamResponse::Batch { result } => Ok(result
+                .step_errors
+                .into_iter()
+                .enumerate()
+                .find_map(|(step, error)| error.map(|e| (step, e.message)))),
+            other => Err(unexpected(&other)),
+        }
+    }
+
+    /// Send `requests` followed by a close, returning one response per request.
+    fn pipeline(&self, mut requests: Vec<StreamRequest>) -> HranaResult<Vec<StreamResponse>> {
+        let count = requests.len();
+        requests.push(StreamRequest::Close);
+
+        let mut request = self.client.post(&self.pipeline_url);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request
+            .json(&PipelineRequest {
+                baton: None,
+                requests,
+            })
+            .send()
+            .map_err(|e| HranaError::Offline(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(HranaError::Remote(format!(
+                "sqld returned {}: {}",
+                status.as_u16(),
+                response.text().unwrap_or_default()
+            )));
+        }
+
+        let body: PipelineResponse = response
+            .json()
+            .map_err(|e| HranaError::Remote(format!("Invalid sqld response: {}", e)))?;
+
+        let mut responses = Vec::with_capacity(count);
+        for result in body.results.into_iter().take(count) {
+            match result {
+                StreamResult::Ok { response } => responses.push(response),
+                StreamResult::Error { error } => return Err(HranaError::Remote(error.message)),
+            }
+        }
+
+        if responses.len() != count {
+            return Err(HranaError::Remote(
+                "sqld returned fewer results than requests".to_string(),
+            ));
+        }
+
+        Ok(responses)
+    }
+}
+
+fn unexpected(response: &StreamResponse) -> HranaError {
+    HranaError::Remote(format!("Unexpected sqld response: {:?}", response))
+}
+
+#[derive(Serialize)]
+struct PipelineRequest {
+    baton: Option<String>,
+    requests: Vec<StreamRequest>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamRequest {
+    Execute { stmt: WireStmt },
+    Sequence { sql: String },
+    Batch { batch: Batch },
+    Close,
+}
+
+#[derive(Serialize)]
+struct WireStmt {
+    sql: String,
+    args: Vec<WireValue>,
+    want_rows: bool,
+}
+
+impl From<Stmt> for WireStmt {
+    fn from(stmt: Stmt) -> Self {
+        WireStmt {
+            sql: stmt.sql,
+            args: stmt.args.into_iter().map(WireValue::from).collect(),
+            want_rows: true,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Batch {
+    steps: Vec<BatchStep>,
+}
+
+#[derive(Serialize)]
+struct BatchStep {
+    condition: Option<BatchCond>,
+    stmt: WireStmt,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum BatchCond {
+    Ok { step: usize },
+    Not { cond: Box<BatchCond> },
+}
+
+/// Values on the wire. Integers travel as strings so 64-bit values survive
+/// JSON; tensile stores no blobs, so they map to NULL.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum WireValue {
+    Null,
+    Integer { value: String },
+    Float { value: f64 },
+    Text { value: String },
+    Blob { base64: String },
+}
+
+impl From<Value> for WireValue {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Null => WireValue::Null,
+            Value::Integer(i) => WireValue::Integer {
+                value: i.to_string(),
+            },
+            Value::Real(f) => WireValue::Float { value: f },
+            Value::Text(s) => WireValue::Text { value: s },
+            Value::Blob(_) => WireValue::Null,
+        }
+    }
+}
+
+impl From<WireValue> for Value {
+    fn from(value: WireValue) -> Self {
+        match value {
+            WireValue::Null | WireValue::Blob { .. } => Value::Null,
+            WireValue::Integer { value } => {
+                value.parse().map(Value::Integer).unwrap_or(Value::Null)
+            }
+            WireValue::Float { value } => Value::Real(value),
+            WireValue::Text { value } => Value::Text(value),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct PipelineResponse {
+    results: Vec<StreamResult>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamResult {
+    Ok { response: StreamResponse },
+    Error { error: WireError },
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamResponse {
+    Execute { result: StmtResult },
+    Sequence,
+    Batch { result: BatchResult },
+    Close,
+}
+
+#[derive(Deserialize, Debug)]
+struct StmtResult {
+    cols: Vec<WireCol>,
+    rows: Vec<Vec<WireValue>>,
+}
+
+impl From<StmtResult> for Rows {
+    fn from(result: StmtResult) -> Self {
+        Rows {
+            columns: result
+                .cols
+                .into_iter()
+                .map(|c| c.name.unwrap_or_default())
+                .collect(),
+            rows: result
+                .rows
+                .into_iter()
+                .map(|row| row.into_iter().map(Value::from).collect())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct WireCol {
+    name: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct BatchResult {
+    step_errors: Vec<Option<WireError>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct WireError {
+    message: String,
+}
@@ -0,0 +1,206 @@
+use crate::{
+    models::{Database, RecordKind},
+    sync::{Record, SyncRecord},
+};
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// How imported records combine with the local database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ImportMode {
+    /// Make the local database match the import, deleting everything else.
+    Replace,
+    /// Add new records; where both sides have a record, keep the newer one.
+    Merge,
+    /// Only add records the local database doesn't have.
+    #[value(alias = "skip_existing", alias = "skip")]
+    SkipExisting,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub skipped: usize,
+    pub removed: usize,
+    /// Imported ids that already named a different local record, with the
+    /// fresh id each was given.
+    pub remapped: Vec<(RecordKind, Uuid, Uuid)>,
+}
+
+/// Import `incoming` into `local` according to `mode`.
+///
+/// An id counts as a collision when the local record with that id is of a
+/// different kind or has a different identity (see `identity`), i.e. it is
+/// not another version of the same record. Colliding records get a fresh id (references
+/// inside the import are rewritten) instead of overwriting unrelated data.
+pub fn import(local: &mut Database, mut incoming: Database, mode: ImportMode) -> ImportSummary {
+    let mut summary = ImportSummary {
+        remapped: remap_collisions(local, &mut incoming),
+        ..Default::default()
+    };
+
+    if mode == ImportMode::Replace {
+        let keep: HashSet<Uuid> = records(&incoming).map(|r| r.id()).collect();
+        let stale: Vec<(RecordKind, Uuid)> = records(local)
+            .filter(|r| !keep.contains(&r.id()))
+            .map(|r| (r.kind(), r.id()))
+            .collect();
+        for (kind, id) in stale {
+            // Deleting a vision already took its entries with it
+            if records(local).any(|r| r.id() == id) {
+                local.remove_record(kind, id);
+                summary.removed += 1;
+            }
+        }
+        local.user = incoming.user.clone();
+    } else if local.user.is_none() {
+        local.user = incoming.user.clone();
+    }
+
+    let deleted: HashMap<Uuid, DateTime<Utc>> = local
+        .tombstones
+        .iter()
+        .map(|t| (t.id, t.deleted_at))
+        .collect();
+    let existing: HashMap<Uuid, Record> = records(local).map(|r| (r.id(), r)).collect();
+
+    for record in records(&incoming).collect::<Vec<_>>() {
+        let current = existing.get(&record.id());
+        if current.is_some_and(|c| same_content(c, &record)) {
+            summary.unchanged += 1;
+            continue;
+        }
+
+        let apply = match (current, mode) {
+            (_, ImportMode::Replace) => true,
+            (Some(_), ImportMode::SkipExisting) => false,
+            (Some(current), ImportMode::Merge) => current.updated_at() < record.updated_at(),
+            // A local deletion newer than the imported version wins
+            (None, _) => deleted
+                .get(&record.id())
+                .is_none_or(|deleted_at| *deleted_at < record.updated_at()),
+        };
+        if !apply {
+            summary.skipped += 1;
+            continue;
+        }
+
+        if current.is_some() {
+            summary.updated += 1;
+        } else {
+            summary.added += 1;
+        }
+        record.apply_to(local);
+    }
+
+    if mode != ImportMode::SkipExisting {
+        for tombstone in &incoming.tombstones {
+            let stale = records(local)
+                .find(|r| r.id() == tombstone.id)
+                .is_some_and(|r| {
+                    mode == ImportMode::Replace || r.updated_at() <= tombstone.deleted_at
+                });
+            if stale {
+                local.remove_record(tombstone.kind, tombstone.id);
+                summary.removed += 1;
+            }
+        }
+    }
+
+    local.rebuild_children();
+    summary
+}
+
+fn remap_collisions(local: &Database, incoming: &mut Database) -> Vec<(RecordKind, Uuid, Uuid)> {
+    let identities: HashMap<Uuid, Identity> =
+        records(local).map(|r| (r.id(), identity(&r))).collect();
+
+    let mut remap: HashMap<Uuid, Uuid> = HashMap::new();
+    let mut remapped = Vec::new();
+    for record in records(incoming) {
+        let collides = identities
+            .get(&record.id())
+            .is_some_and(|local| *local != identity(&record));
+        if collides {
+            let fresh = Uuid::new_v4();
+            remap.insert(record.id(), fresh);
+            remapped.push((record.kind(), record.id(), fresh));
+        }
+    }
+
+    if remap.is_empty() {
+        return remapped;
+    }
+
+    let map = |id: &mut Uuid| {
+        if let Some(fresh) = remap.get(id) {
+            *id = *fresh;
+        }
+    };
+    for vision in &mut incoming.visions {
+        map(&mut vision.id);
+        if let Some(parent) = vision.parent.as_mut() {
+            map(parent);
+        }
+        vision.depends_on.iter_mut().for_each(map);
+    }
+    for reality in &mut incoming.realities {
+        map(&mut reality.id);
+        map(&mut reality.vision_id);
+    }
+    for action in &mut incoming.actions {
+        map(&mut action.id);
+        map(&mut action.vision_id);
+    }
+    if let Some(focus) = incoming
+        .user
+        .as_mut()
+        .and_then(|u| u.current_focus.as_mut())
+    {
+        map(focus);
+    }
+    // Tombstones name local records by id; a remapped one refers to nothing here
+    incoming.tombstones.retain(|t| !remap.contains_key(&t.id));
+    incoming.rebuild_children();
+
+    remapped
+}
+
+/// What makes two records with the same id the same record: a vision's
+/// creation time, or the vision an entry belongs to. Entry timestamps can be
+/// amended, so they don't identify the entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Identity {
+    Vision(DateTime<Utc>),
+    Entry(RecordKind, Uuid),
+}
+
+fn identity(record: &Record) -> Identity {
+    match record {
+        Record::Vision(v) => Identity::Vision(v.created_at),
+        Record::Reality(r) => Identity::Entry(RecordKind::Reality, r.vision_id),
+        Record::Action(a) => Identity::Entry(RecordKind::Action, a.vision_id),
+    }
+}
+
+fn same_content(a: &Record, b: &Record) -> bool {
+    match (a, b) {
+        (Record::Vision(a), Record::Vision(b)) => a.same_content(b),
+        (Record::Reality(a), Record::Reality(b)) => a.same_content(b),
+        (Record::Action(a), Record::Action(b)) => a.same_content(b),
+        _ => false,
+    }
+}
+
+/// Every vision, reality and action in `db` as a `Record`.
+fn records(db: &Database) -> impl Iterator<Item = Record> + '_ {
+    db.visions
+        .iter()
+        .cloned()
+        .map(SyncRecord::into_record)
+        .chain(db.realities.iter().cloned().map(SyncRecord::into_record))
+        .chain(db.actions.iter().cloned().map(SyncRecord::into_record))
+}
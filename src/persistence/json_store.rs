@@ -0,0 +1,189 @@
+use crate::{
+    error::{TensileError, TensileResult},
+    models::Database,
+};
+use chrono::{DateTime, Utc};
+use jsonschema::{Draft, JSONSchema};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Version of the export format written by this build.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Published schema for each supported version, indexed by version - 1.
+/// Older versions stay frozen so their exports keep validating; what they
+/// lack takes its default when read.
+const SCHEMAS: &[&str] = &[
+    include_str!("../../schema/export.v1.json"),
+    include_str!("../../schema/export.v2.json"),
+];
+
+/// Errors beyond this are summarized rather than listed.
+const MAX_REPORTED_ERRORS: usize = 20;
+
+/// Full-fidelity JSON export: the whole database plus a schema version.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExportFile {
+    pub schema_version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub database: Database,
+}
+
+/// The JSON schema describing exports of the given version.
+pub fn schema(version: u32) -> Option<&'static str> {
+    SCHEMAS.get((version as usize).checked_sub(1)?).copied()
+}
+
+pub fn to_json(db: &Database) -> TensileResult<String> {
+    let file = ExportFile {
+        schema_version: SCHEMA_VERSION,
+        exported_at: Utc::now(),
+        database: db.clone(),
+    };
+
+    serde_json::to_string_pretty(&file).map_err(|e| TensileError::Serialization(e.to_string()))
+}
+
+/// Parse an export, validating it against the schema of its version first so
+/// problems are reported with their location rather than as a serde error.
+pub fn from_json(contents: &str) -> TensileResult<Database> {
+    let value: serde_json::Value = serde_json::from_str(contents)
+        .map_err(|e| TensileError::Parse(format!("Invalid JSON: {}", e)))?;
+
+    let version = value
+        .get("schema_version")
+        .and_then(|v| v.as_u64())
+        .ok_or_else(|| {
+            TensileError::Validation("Not a tensile export: missing schema_version".to_string())
+        })?;
+    let schema_source = u32::try_from(version)
+        .ok()
+        .and_then(schema)
+        .ok_or_else(|| {
+            TensileError::Validation(format!(
+                "Unsupported schema_version {} (this tensile reads up to {})",
+                version, SCHEMA_VERSION
+            ))
+        })?;
+
+    validate(schema_source, &value)?;
+
+    let file: ExportFile =
+        serde_json::from_value(value).map_err(|e| TensileError::Serialization(e.to_string()))?;
+    check_references(&file.database)?;
+
+    let mut db = file.database;
+    db.rebuild_children();
+    Ok(db)
+}
+
+fn validate(schema_source: &str, value: &serde_json::Value) -> TensileResult<()> {
+    let schema: serde_json::Value = serde_json::from_str(schema_source)
+        .map_err(|e| TensileError::Serialization(format!("Bundled schema is invalid: {}", e)))?;
+    let compiled = JSONSchema::options()
+        .with_draft(Draft::Draft7)
+        .should_validate_formats(true)
+        .compile(&schema)
+        .map_err(|e| TensileError::Serialization(format!("Bundled schema is invalid: {}", e)))?;
+
+    let result = compiled.validate(value);
+    if let Err(errors) = result {
+        let errors: Vec<String> = errors
+            .map(|e| format!("  at {}: {}", pointer(&e.instance_path.to_string()), e))
+            .collect();
+        return Err(report("Import does not match the export schema", errors));
+    }
+
+    Ok(())
+}
+
+/// Ids must be unique and every reference must point into the same export.
+fn check_references(db: &Database) -> TensileResult<()> {
+    let mut errors = Vec::new();
+    let mut ids = HashSet::new();
+
+    let all_ids = db
+        .visions
+        .iter()
+        .enumerate()
+        .map(|(i, v)| (format!("/database/visions/{}/id", i), v.id))
+        .chain(
+            db.realities
+                .iter()
+                .enumerate()
+                .map(|(i, r)| (format!("/database/realities/{}/id", i), r.id)),
+        )
+        .chain(
+            db.actions
+                .iter()
+                .enumerate()
+                .map(|(i, a)| (format!("/database/actions/{}/id", i), a.id)),
+        );
+    for (path, id) in all_ids {
+        if !ids.insert(id) {
+            errors.push(format!("  at {}: duplicate id {}", path, id));
+        }
+    }
+
+    let visions: HashSet<_> = db.visions.iter().map(|v| v.id).collect();
+    let references = db
+        .visions
+        .iter()
+        .enumerate()
+        .filter_map(|(i, v)| {
+            v.parent
+                .map(|p| (format!("/database/visions/{}/parent", i), p))
+        })
+        .chain(db.visions.iter().enumerate().flat_map(|(i, v)| {
+            v.depends_on
+                .iter()
+                .enumerate()
+                .map(move |(j, d)| (format!("/database/visions/{}/depends_on/{}", i, j), *d))
+        }))
+        .chain(
+            db.realities
+                .iter()
+                .enumerate()
+                .map(|(i, r)| (format!("/database/realities/{}/vision_id", i), r.vision_id)),
+        )
+        .chain(
+            db.actions
+                .iter()
+                .enumerate()
+                .map(|(i, a)| (format!("/database/actions/{}/vision_id", i), a.vision_id)),
+        );
+    for (path, id) in references {
+        if !visions.contains(&id) {
+            errors.push(format!("  at {}: no vision {} in this export", path, id));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(report("Import has inconsistent ids", errors))
+    }
+}
+
+fn pointer(path: &str) -> &str {
+    if path.is_empty() {
+        "/"
+    } else {
+        path
+    }
+}
+
+fn report(heading: &str, mut errors: Vec<String>) -> TensileError {
+    let total = errors.len();
+    if total > MAX_REPORTED_ERRORS {
+        errors.truncate(MAX_REPORTED_ERRORS);
+        errors.push(format!("  ... and {} more", total - MAX_REPORTED_ERRORS));
+    }
+
+    TensileError::Validation(format!(
+        "{} ({} error(s)):\n{}",
+        heading,
+        total,
+        errors.join("\n")
+    ))
+}
@@ -0,0 +1,345 @@
+use super::hrana::{HranaClient, HranaError, HranaResult, Rows, Stmt};
+use super::sqlite_store::{migrate, read_database, write_database, MIGRATIONS};
+use super::SqliteStore;
+use crate::{
+    error::{TensileError, TensileResult},
+    models::Database,
+    sync::{three_way_merge, MergeOutcome, SyncStore},
+};
+use rusqlite::{types::Value, Connection};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard, OnceLock, PoisonError};
+
+/// Tables holding user data, in an order that satisfies foreign keys.
+const TABLES: &[&str] = &[
+    "visions",
+    "vision_dependencies",
+    "users",
+    "reality_assessments",
+    "action_logs",
+    "tombstones",
+];
+
+/// Name of the sync base kept for the libSQL server.
+pub const CHANNEL: &str = "turso";
+
+/// Server-only schema, applied after the shared `MIGRATIONS`.
+const REMOTE_SCHEMA: &str = include_str!("../../migrations/remote/sync_generation.sql");
+
+/// How often to re-merge when other devices keep uploading in between.
+const UPLOAD_ATTEMPTS: usize = 3;
+
+/// What this process learned about servers, so repeated loads and saves skip
+/// round trips: the servers whose schema is up to date, and the generation
+/// each replica (by path) last synced with.
+#[derive(Default)]
+struct Known {
+    migrated: HashSet<String>,
+    generations: HashMap<(String, PathBuf), i64>,
+}
+
+static KNOWN: OnceLock<Mutex<Known>> = OnceLock::new();
+
+fn known() -> MutexGuard<'static, Known> {
+    KNOWN
+        .get_or_init(Mutex::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Stores the database on a libSQL server (`sqld` or Turso) and keeps
+/// `tensile.db` as a local replica.
+///
+/// Every load merges the server copy into the replica, unless the server is
+/// still where this replica last synced it; every save writes the replica
+/// first and then uploads. An upload only goes through if no other
+/// device uploaded since the fetch it was merged with (tracked by the
+/// server's `sync_generation`); otherwise it is merged again. When the
+/// server cannot be reached the
+/// replica is used on its own and local changes stay pending until the next
+/// successful sync, where they are three-way merged like any other channel.
+pub struct LibsqlStore {
+    url: String,
+    client: HranaClient,
+    replica: SqliteStore,
+    sync: SyncStore,
+}
+
+impl LibsqlStore {
+    pub fn new(url: &str, token: Option<String>, replica: SqliteStore, sync: SyncStore) -> Self {
+        LibsqlStore {
+            url: url.to_string(),
+            client: HranaClient::new(url, token),
+            replica,
+            sync,
+        }
+    }
+
+    /// Configure from `TURSO_URL` and `TURSO_TOKEN`; `None` if no URL is set.
+    pub fn from_env() -> TensileResult<Option<Self>> {
+        let url = match std::env::var("TURSO_URL") {
+            Ok(url) if !url.trim().is_empty() => url,
+            _ => return Ok(None),
+        };
+        let token = std::env::var("TURSO_TOKEN")
+            .ok()
+            .filter(|t| !t.trim().is_empty());
+
+        Ok(Some(LibsqlStore::new(
+            url.trim(),
+            token,
+            SqliteStore::new(None)?,
+            SyncStore::new(),
+        )))
+    }
+
+    pub fn load(&self) -> TensileResult<Database> {
+        if !self.is_current() {
+            self.sync()?;
+        }
+        self.replica.read()
+    }
+
+    pub fn save(&self, db: &Database) -> TensileResult<()> {
+        self.replica.write(db)?;
+        self.sync.set_pending(CHANNEL, true)?;
+        self.sync()?;
+        Ok(())
+    }
+
+    /// Merge the server copy into the replica, then upload local changes
+    /// unless conflicts are pending. Returns `None` when offline.
+    pub fn sync(&self) -> TensileResult<Option<MergeOutcome>> {
+        for _ in 0..UPLOAD_ATTEMPTS {
+            let (generation, remote) = match self.fetch_rows() {
+                Ok((generation, tables)) => (generation, to_database(tables)?),
+                Err(e) => return offline(e).map(|_| None),
+            };
+
+            let local = self.replica.read()?;
+            let base = self.sync.load_base(CHANNEL)?;
+            let outcome = three_way_merge(&base, &local, &remote);
+
+            // Same order as `sync::reconcile`: replica first, then the new base
+            self.replica.write(&outcome.merged)?;
+            self.sync.save_base(CHANNEL, &remote)?;
+            self.sync.record_conflicts(&outcome.conflicts)?;
+
+            // A replica that never synced may hold data the server lacks
+            let pending = self.sync.is_pending(CHANNEL) || !self.sync.has_base(CHANNEL);
+            if !pending || !self.sync.load_conflicts()?.is_empty() {
+                self.synced_at(generation);
+                return Ok(Some(outcome));
+            }
+            match self.upload(&outcome.merged, generation) {
+                Ok(true) => {
+                    self.sync.save_base(CHANNEL, &outcome.merged)?;
+                    self.sync.set_pending(CHANNEL, false)?;
+                    self.synced_at(generation + 1);
+                    return Ok(Some(outcome));
+                }
+                // Another device uploaded since the fetch: merge with that
+                Ok(false) => continue,
+                Err(e) => return offline(e).map(|_| Some(outcome)),
+            }
+        }
+
+        Err(TensileError::Sync(
+            "The server kept changing while uploading; local changes are kept, try again"
+                .to_string(),
+        ))
+    }
+
+    /// Read the server copy without touching the replica.
+    pub fn fetch(&self) -> TensileResult<Database> {
+        to_database(self.fetch_rows()?.1)
+    }
+
+    /// Replace the server copy with `db` in a single transaction, whatever
+    /// other devices uploaded.
+    pub fn push(&self, db: &Database) -> TensileResult<()> {
+        let mut stmts = to_statements(db)?;
+        stmts.push(Stmt::new(
+            "UPDATE sync_generation SET generation = generation + 1",
+        ));
+        self.migrate_remote()?;
+        Ok(self.client.transaction(stmts)?)
+    }
+
+    /// Replace the server copy with `db` if the server is still at
+    /// `generation`. Returns `false`, uploading nothing, if it moved on.
+    fn upload(&self, db: &Database, generation: i64) -> HranaResult<bool> {
+        let stmts = to_statements(db).map_err(|e| HranaError::Remote(e.to_string()))?;
+        self.migrate_remote()?;
+        // A mismatch sets NULL, which the NOT NULL column refuses
+        let guard = Stmt::with_args(
+            "UPDATE sync_generation SET generation = \
+             CASE WHEN generation = ? THEN generation + 1 ELSE NULL END",
+            vec![Value::Integer(generation)],
+        );
+        self.client.guarded_transaction(guard, stmts)
+    }
+
+    /// Whether the server is still at the generation this replica last synced
+    /// with in this process, with nothing left to upload. Costs one round
+    /// trip, or none for a replica that has not synced yet.
+    fn is_current(&self) -> bool {
+        let key = (self.url.clone(), self.replica.path().to_path_buf());
+        let Some(synced) = known().generations.get(&key).copied() else {
+            return false;
+        };
+        if self.sync.is_pending(CHANNEL) {
+            return false;
+        }
+        self.client
+            .query_all(vec![Stmt::new("SELECT generation FROM sync_generation")])
+            .is_ok_and(|mut rows| generation(rows.remove(0)) == synced)
+    }
+
+    fn synced_at(&self, generation: i64) {
+        let key = (self.url.clone(), self.replica.path().to_path_buf());
+        known().generations.insert(key, generation);
+    }
+
+    /// The server's generation and the rows of each table. The generation is
+    /// read first, so rows written after it only ever make an upload retry.
+    fn fetch_rows(&self) -> HranaResult<(i64, Vec<Rows>)> {
+        self.migrate_remote()?;
+        let mut rows = self.client.query_all(
+            std::iter::once(Stmt::new("SELECT generation FROM sync_generation"))
+                .chain(
+                    TABLES
+                        .iter()
+                        .map(|table| Stmt::new(format!("SELECT * FROM {}", table))),
+                )
+                .collect(),
+        )?;
+        let generation = generation(rows.remove(0));
+        Ok((generation, rows))
+    }
+
+    /// Bring the server schema up to date, tracked with `PRAGMA user_version`
+    /// exactly like the local database, then add the server-only tables.
+    /// Checked once per server and process.
+    fn migrate_remote(&self) -> HranaResult<()> {
+        if known().migrated.contains(&self.url) {
+            return Ok(());
+        }
+
+        let rows = self.client.query_all(vec![
+            Stmt::new("PRAGMA user_version"),
+            Stmt::new("SELECT 1 FROM sqlite_master WHERE name = 'sync_generation'"),
+        ])?;
+        let version = match rows[0].rows.first().and_then(|row| row.first()) {
+            Some(Value::Integer(v)) => *v as usize,
+            _ => 0,
+        };
+        let has_remote_schema = !rows[1].rows.is_empty();
+
+        if version > MIGRATIONS.len() {
+            return Err(HranaError::Remote(format!(
+                "Server schema version {} is newer than this tensile ({}); please upgrade",
+                version,
+                MIGRATIONS.len()
+            )));
+        }
+
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            self.client.execute_script(&format!(
+                "BEGIN;\n{}\nPRAGMA user_version = {};\nCOMMIT;",
+                migration,
+                index + 1
+            ))?;
+        }
+
+        if !has_remote_schema {
+            self.client
+                .execute_script(&format!("BEGIN;\n{}\nCOMMIT;", REMOTE_SCHEMA))?;
+        }
+
+        known().migrated.insert(self.url.clone());
+        Ok(())
+    }
+}
+
+/// The value of `SELECT generation FROM sync_generation`.
+fn generation(rows: Rows) -> i64 {
+    match rows.rows.first().and_then(|row| row.first()) {
+        Some(Value::Integer(generation)) => *generation,
+        _ => 0,
+    }
+}
+
+/// Being offline is not an error for a local-first store: warn and carry on.
+fn offline(e: HranaError) -> TensileResult<()> {
+    match e {
+        HranaError::Offline(msg) => {
+            eprintln!("⚠ Turso unreachable, using local replica ({})", msg);
+            Ok(())
+        }
+        e => Err(e.into()),
+    }
+}
+
+/// Load server rows into a scratch SQLite database and read them back, so
+/// the server and the replica share one row mapping.
+fn to_database(tables: Vec<Rows>) -> TensileResult<Database> {
+    let mut conn = Connection::open_in_memory()?;
+    migrate(&mut conn)?;
+
+    for (table, rows) in TABLES.iter().zip(tables) {
+        if rows.rows.is_empty() {
+            continue;
+        }
+        let mut stmt = conn.prepare(&insert_sql(table, &rows.columns))?;
+        for row in rows.rows {
+            stmt.execute(rusqlite::params_from_iter(row))?;
+        }
+    }
+
+    read_database(&conn)
+}
+
+/// Statements that replace the server copy with `db`.
+fn to_statements(db: &Database) -> TensileResult<Vec<Stmt>> {
+    let mut conn = Connection::open_in_memory()?;
+    migrate(&mut conn)?;
+    write_database(&mut conn, db)?;
+
+    let mut stmts: Vec<Stmt> = TABLES
+        .iter()
+        .rev()
+        .map(|table| Stmt::new(format!("DELETE FROM {}", table)))
+        .collect();
+
+    for table in TABLES {
+        let mut select = conn.prepare(&format!("SELECT * FROM {}", table))?;
+        let columns: Vec<String> = select
+            .column_names()
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let sql = insert_sql(table, &columns);
+
+        let rows = select.query_map([], |row| {
+            (0..columns.len())
+                .map(|i| row.get::<_, Value>(i))
+                .collect::<Result<Vec<_>, _>>()
+        })?;
+        for row in rows {
+            stmts.push(Stmt::with_args(sql.clone(), row?));
+        }
+    }
+
+    Ok(stmts)
+}
+
+fn insert_sql(table: &str, columns: &[String]) -> String {
+    format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        table,
+        columns.join(", "),
+        vec!["?"; columns.len()].join(", ")
+    )
+}
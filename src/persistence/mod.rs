@@ -1,13 +1,58 @@
+pub mod event_journal;
+#[cfg(feature = "turso")]
+mod hrana;
+pub mod import;
+pub mod json_store;
+#[cfg(feature = "turso")]
+pub mod libsql_store;
+pub mod ron_store;
 pub mod sqlite_store;
+pub mod template_store;
 
+pub use event_journal::{EventJournal, JournalEntry};
+pub use import::{import, ImportMode, ImportSummary};
+#[cfg(feature = "turso")]
+pub use libsql_store::LibsqlStore;
+pub use ron_store::RonStore;
 pub use sqlite_store::SqliteStore;
+pub use template_store::TemplateStore;
 
 use crate::{error::TensileResult, models::Database};
 
+/// Load from the libSQL server when `TURSO_URL` is set (and the `turso`
+/// feature is enabled), otherwise from the local SQLite database.
 pub fn load_database() -> TensileResult<Database> {
+    #[cfg(feature = "turso")]
+    {
+        if let Some(db) = with_libsql(|store| store.load())? {
+            return Ok(db);
+        }
+    }
+
     SqliteStore::load()
 }
 
 pub fn save_database(db: &Database) -> TensileResult<()> {
+    #[cfg(feature = "turso")]
+    {
+        if let Some(()) = with_libsql(|store| store.save(db))? {
+            return Ok(());
+        }
+    }
+
     SqliteStore::save(db)
 }
+
+/// Run `f` against the libSQL store, or return `None` if none is configured.
+///
+/// Its HTTP client blocks, which tokio forbids on a runtime thread (as in
+/// `tensile sync push`), so the store is created, used and dropped off the
+/// runtime when running on one.
+#[cfg(feature = "turso")]
+fn with_libsql<T>(f: impl FnOnce(&LibsqlStore) -> TensileResult<T>) -> TensileResult<Option<T>> {
+    let run = || LibsqlStore::from_env()?.as_ref().map(f).transpose();
+    match tokio::runtime::Handle::try_current().map(|h| h.runtime_flavor()) {
+        Ok(tokio::runtime::RuntimeFlavor::MultiThread) => tokio::task::block_in_place(run),
+        _ => run(),
+    }
+}
@@ -1,46 +1,163 @@
 use crate::{
-    config,
     error::{TensileError, TensileResult},
-    models::Database,
+    models::{ActionLog, Database, RealityAssessment, Vision},
 };
-use std::fs::{self, OpenOptions};
-use std::io::{Read, Write};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
 
-pub struct RonStore;
+const EXTENSION: &str = "ron";
+
+/// One vision with its entries, as stored in `<vision id>.ron`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VisionFile {
+    pub vision: Vision,
+    pub realities: Vec<RealityAssessment>,
+    pub actions: Vec<ActionLog>,
+}
+
+/// What an export changed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DirSummary {
+    pub written: usize,
+    pub unchanged: usize,
+    pub removed: usize,
+}
+
+/// Text export for version control: one RON file per vision, named by its
+/// id so renames don't move files. Entries are sorted by timestamp and the
+/// formatting is fixed, so exporting unchanged data produces identical bytes.
+pub struct RonStore {
+    dir: PathBuf,
+}
 
 impl RonStore {
-    pub fn load() -> TensileResult<Database> {
-        let db_path = config::db_path();
+    pub fn new(dir: PathBuf) -> Self {
+        RonStore { dir }
+    }
+
+    /// Write every vision, skipping files whose contents are unchanged and
+    /// removing files of visions that no longer exist.
+    pub fn export(&self, db: &Database) -> TensileResult<DirSummary> {
+        fs::create_dir_all(&self.dir)?;
+        let mut summary = DirSummary::default();
 
-        if !db_path.exists() {
-            return Ok(Database::default());
+        let mut visions: Vec<&Vision> = db.visions.iter().collect();
+        visions.sort_by_key(|v| v.id);
+
+        for vision in &visions {
+            let contents = to_ron(&vision_file(db, vision))?;
+            let path = self.path(vision.id);
+
+            if fs::read_to_string(&path).ok().as_deref() == Some(contents.as_str()) {
+                summary.unchanged += 1;
+                continue;
+            }
+
+            let tmp = self.dir.join(format!(".{}.tmp", vision.id));
+            fs::write(&tmp, contents)?;
+            fs::rename(&tmp, &path)?;
+            summary.written += 1;
         }
 
-        let mut file = fs::File::open(&db_path)?;
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
+        for (id, path) in self.files()? {
+            if !visions.iter().any(|v| v.id == id) {
+                fs::remove_file(path)?;
+                summary.removed += 1;
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Read every vision file into a database.
+    pub fn read(&self) -> TensileResult<Database> {
+        let mut db = Database::default();
 
-        let db: Database =
-            ron::from_str(&contents).map_err(|e| TensileError::Serialization(e.to_string()))?;
+        for (_, path) in self.files()? {
+            let contents = fs::read_to_string(&path)?;
+            let file: VisionFile = ron::from_str(&contents)
+                .map_err(|e| TensileError::Serialization(format!("{}: {}", path.display(), e)))?;
 
+            db.visions.push(file.vision);
+            db.realities.extend(file.realities);
+            db.actions.extend(file.actions);
+        }
+
+        db.rebuild_children();
         Ok(db)
     }
 
-    pub fn save(db: &Database) -> TensileResult<()> {
-        config::ensure_db_dir()?;
+    fn path(&self, id: Uuid) -> PathBuf {
+        self.dir.join(format!("{}.{}", id, EXTENSION))
+    }
+
+    /// Vision files in the directory, sorted by id. Other files are ignored.
+    fn files(&self) -> TensileResult<Vec<(Uuid, PathBuf)>> {
+        let mut files = Vec::new();
+
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(EXTENSION) {
+                continue;
+            }
+            if let Some(id) = vision_id(&path) {
+                files.push((id, path));
+            }
+        }
+
+        files.sort();
+        Ok(files)
+    }
+}
+
+fn vision_id(path: &Path) -> Option<Uuid> {
+    path.file_stem()
+        .and_then(|s| s.to_str())
+        .and_then(|s| Uuid::parse_str(s).ok())
+}
 
-        let db_path = config::db_path();
-        let mut file = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .truncate(true)
-            .open(&db_path)?;
+fn vision_file(db: &Database, vision: &Vision) -> VisionFile {
+    let mut vision = vision.clone();
+    // Derived from `parent` links on import
+    vision.children.clear();
 
-        let contents = ron::ser::to_string_pretty(db, Default::default())
-            .map_err(|e| TensileError::Serialization(e.to_string()))?;
+    let mut realities: Vec<RealityAssessment> = db
+        .realities
+        .iter()
+        .filter(|r| r.vision_id == vision.id)
+        .cloned()
+        .collect();
+    realities.sort_by_key(|r| (r.timestamp, r.id));
 
-        file.write_all(contents.as_bytes())?;
+    let mut actions: Vec<ActionLog> = db
+        .actions
+        .iter()
+        .filter(|a| a.vision_id == vision.id)
+        .cloned()
+        .collect();
+    actions.sort_by_key(|a| (a.timestamp, a.id));
 
-        Ok(())
+    VisionFile {
+        vision,
+        realities,
+        actions,
     }
 }
+
+fn to_ron(file: &VisionFile) -> TensileResult<String> {
+    // Pin every option so output doesn't depend on platform or ron defaults
+    let config = PrettyConfig::new()
+        .new_line("\n".to_string())
+        .indentor("    ".to_string())
+        .struct_names(false)
+        .separate_tuple_members(false)
+        .enumerate_arrays(false);
+
+    let mut contents = ron::ser::to_string_pretty(file, config)
+        .map_err(|e| TensileError::Serialization(e.to_string()))?;
+    contents.push('\n');
+    Ok(contents)
+}
@@ -1,20 +1,32 @@
+use crate::config;
 use crate::error::{TensileError, TensileResult};
 use crate::models::*;
-use rusqlite::{params, Connection};
-use std::path::PathBuf;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
+/// Schema migrations, applied in order and tracked with `PRAGMA user_version`.
+pub(crate) const MIGRATIONS: &[&str] = &[
+    include_str!("../../migrations/001_initial_schema.sql"),
+    include_str!("../../migrations/002_sync_metadata.sql"),
+    include_str!("../../migrations/003_vision_position.sql"),
+    include_str!("../../migrations/004_event_journal.sql"),
+    include_str!("../../migrations/005_vision_achievement.sql"),
+    include_str!("../../migrations/006_vision_stakes.sql"),
+    include_str!("../../migrations/007_vision_dependencies.sql"),
+    include_str!("../../migrations/008_vision_blocker.sql"),
+];
+
 pub struct SqliteStore {
     db_path: PathBuf,
 }
 
 impl SqliteStore {
     pub fn new(db_path: Option<PathBuf>) -> TensileResult<Self> {
-        let path = db_path.unwrap_or_else(|| {
-            dirs::home_dir()
-                .map(|h| h.join(".tensile").join("tensile.db"))
-                .unwrap_or_else(|| PathBuf::from("tensile.db"))
-        });
+        let path = match db_path {
+            Some(path) => path,
+            None => config::db_path()?,
+        };
 
         // Ensure directory exists
         if let Some(parent) = path.parent() {
@@ -27,224 +39,413 @@ impl SqliteStore {
     }
 
     fn init_db(&self) -> TensileResult<()> {
-        let conn = Connection::open(&self.db_path)?;
-        let schema = include_str!("../../migrations/001_initial_schema.sql");
-        conn.execute_batch(schema)
-            .map_err(|e| TensileError::Serialization(e.to_string()))?;
-        Ok(())
+        let mut conn = self.get_connection()?;
+        migrate(&mut conn)
     }
 
+    /// Wait for a lock like `EventJournal` does, since `events --follow`
+    /// polls the same file while commands write to it.
     fn get_connection(&self) -> TensileResult<Connection> {
-        Connection::open(&self.db_path).map_err(|e| e.into())
+        let conn = Connection::open(&self.db_path)?;
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        Ok(conn)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.db_path
+    }
+
+    pub fn read(&self) -> TensileResult<Database> {
+        read_database(&self.get_connection()?)
+    }
+
+    pub fn write(&self, db: &Database) -> TensileResult<()> {
+        write_database(&mut self.get_connection()?, db)
     }
 }
 
 impl SqliteStore {
     pub fn load() -> TensileResult<Database> {
-        let store = SqliteStore::new(None)?;
-        let conn = store.get_connection()?;
+        SqliteStore::new(None)?.read()
+    }
 
-        // Load visions
-        let mut stmt = conn
-            .prepare("SELECT id, title, description, parent_id, created_at, completed, state FROM visions")
-            .map_err(|e| TensileError::Serialization(e.to_string()))?;
+    pub fn save(db: &Database) -> TensileResult<()> {
+        SqliteStore::new(None)?.write(db)
+    }
+}
 
-        let visions = stmt
-            .query_map([], |row| {
-                let id_str: String = row.get(0)?;
-                let title: String = row.get(1)?;
-                let description: Option<String> = row.get(2)?;
-                let parent_id: Option<String> = row.get(3)?;
-                let created_at: String = row.get(4)?;
-                let completed: bool = row.get(5)?;
-                let state_str: String = row.get(6)?;
-
-                let id = Uuid::parse_str(&id_str).map_err(|_| rusqlite::Error::InvalidQuery)?;
-                let parent = parent_id.and_then(|p| Uuid::parse_str(&p).ok());
-                let created_at = chrono::DateTime::parse_from_rfc3339(&created_at)
-                    .ok()
-                    .map(|dt| dt.with_timezone(&chrono::Utc))
-                    .ok_or(rusqlite::Error::InvalidQuery)?;
-
-                let state = match state_str.as_str() {
-                    "Conceived" => VisionState::Conceived,
-                    "InProgress" => VisionState::InProgress,
-                    "Blocked" => VisionState::Blocked,
-                    "Reassessed" => VisionState::Reassessed,
-                    "Achieved" => VisionState::Achieved,
-                    _ => VisionState::Conceived,
-                };
-
-                Ok(Vision {
-                    id,
-                    title,
-                    description,
-                    parent,
-                    children: vec![],
-                    created_at,
-                    completed,
-                    state,
-                })
-            })
-            .map_err(|e| TensileError::Serialization(e.to_string()))?
-            .collect::<Result<Vec<_>, _>>()
+pub(crate) fn migrate(conn: &mut Connection) -> TensileResult<()> {
+    let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration)
             .map_err(|e| TensileError::Serialization(e.to_string()))?;
+        tx.pragma_update(None, "user_version", index + 1)?;
+        tx.commit()?;
+    }
 
-        // Rebuild parent-child relationships
-        let mut visions_map: std::collections::HashMap<Uuid, Vision> =
-            visions.into_iter().map(|v| (v.id, v)).collect();
+    Ok(())
+}
 
-        let child_ids: Vec<(Uuid, Option<Uuid>)> =
-            visions_map.values().map(|v| (v.id, v.parent)).collect();
+pub(crate) fn read_database(conn: &Connection) -> TensileResult<Database> {
+    // Load visions
+    let mut stmt = conn
+        .prepare("SELECT id, title, description, parent_id, created_at, state, updated_at, updated_by, position, achieved_at, retrospective, stakes, blocked_reason, unblock_when, unblock_on, blocked_at FROM visions")
+        .map_err(|e| TensileError::Serialization(e.to_string()))?;
+
+    let mut visions = stmt
+        .query_map([], |row| {
+            let id_str: String = row.get(0)?;
+            let title: String = row.get(1)?;
+            let description: Option<String> = row.get(2)?;
+            let parent_id: Option<String> = row.get(3)?;
+            let created_at: String = row.get(4)?;
+            let state_str: String = row.get(5)?;
+            let updated_at: Option<String> = row.get(6)?;
+            let updated_by: Option<String> = row.get(7)?;
+            let position: u32 = row.get(8)?;
+            let achieved_at: Option<String> = row.get(9)?;
+            let retrospective: Option<String> = row.get(10)?;
+            let stakes: u8 = row.get(11)?;
+            let blocked_reason: Option<String> = row.get(12)?;
+            let unblock_when: Option<String> = row.get(13)?;
+            let unblock_on: Option<String> = row.get(14)?;
+            let blocked_at: Option<String> = row.get(15)?;
+
+            let id = Uuid::parse_str(&id_str).map_err(|_| rusqlite::Error::InvalidQuery)?;
+            let parent = parent_id.and_then(|p| Uuid::parse_str(&p).ok());
+            let created_at = chrono::DateTime::parse_from_rfc3339(&created_at)
+                .ok()
+                .map(|dt| dt.with_timezone(&chrono::Utc))
+                .ok_or(rusqlite::Error::InvalidQuery)?;
+
+            let state = match state_str.as_str() {
+                "Conceived" => VisionState::Conceived,
+                "InProgress" => VisionState::InProgress,
+                "Blocked" => VisionState::Blocked,
+                "Reassessed" => VisionState::Reassessed,
+                "Achieved" => VisionState::Achieved,
+                _ => VisionState::Conceived,
+            };
+
+            Ok(Vision {
+                id,
+                title,
+                description,
+                parent,
+                children: vec![],
+                depends_on: vec![],
+                position,
+                stakes,
+                created_at,
+                state,
+                achieved_at: parse_optional_timestamp(achieved_at),
+                retrospective,
+                blocker: blocked_reason.map(|reason| Blocker {
+                    reason,
+                    unblock_when,
+                    unblock_on: unblock_on
+                        .and_then(|d| chrono::NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok()),
+                    since: parse_optional_timestamp(blocked_at).unwrap_or(created_at),
+                }),
+                updated_at: parse_optional_timestamp(updated_at).unwrap_or(created_at),
+                updated_by: updated_by.and_then(|d| Uuid::parse_str(&d).ok()),
+            })
+        })
+        .map_err(|e| TensileError::Serialization(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| TensileError::Serialization(e.to_string()))?;
+
+    // Load dependencies, in the order they were added
+    let mut stmt = conn
+        .prepare("SELECT vision_id, depends_on FROM vision_dependencies ORDER BY rowid")
+        .map_err(|e| TensileError::Serialization(e.to_string()))?;
+
+    let dependencies = stmt
+        .query_map([], |row| {
+            let vision_id: String = row.get(0)?;
+            let depends_on: String = row.get(1)?;
+            Ok((
+                Uuid::parse_str(&vision_id).map_err(|_| rusqlite::Error::InvalidQuery)?,
+                Uuid::parse_str(&depends_on).map_err(|_| rusqlite::Error::InvalidQuery)?,
+            ))
+        })
+        .map_err(|e| TensileError::Serialization(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| TensileError::Serialization(e.to_string()))?;
 
-        for (child_id, parent_id) in child_ids {
-            if let Some(parent_uuid) = parent_id {
-                if let Some(parent) = visions_map.get_mut(&parent_uuid) {
-                    parent.children.push(child_id);
-                }
-            }
+    for (vision_id, depends_on) in dependencies {
+        if let Some(vision) = visions.iter_mut().find(|v| v.id == vision_id) {
+            vision.depends_on.push(depends_on);
         }
+    }
 
-        let visions: Vec<Vision> = visions_map.into_values().collect();
-
-        // Load reality assessments
-        let mut stmt = conn
-            .prepare("SELECT id, vision_id, entry, timestamp FROM reality_assessments ORDER BY timestamp DESC")
-            .map_err(|e| TensileError::Serialization(e.to_string()))?;
-
-        let realities = stmt
-            .query_map([], |row| {
-                let id_str: String = row.get(0)?;
-                let vision_id_str: String = row.get(1)?;
-                let entry: String = row.get(2)?;
-                let timestamp_str: String = row.get(3)?;
-
-                let id = Uuid::parse_str(&id_str).map_err(|_| rusqlite::Error::InvalidQuery)?;
-                let vision_id =
-                    Uuid::parse_str(&vision_id_str).map_err(|_| rusqlite::Error::InvalidQuery)?;
-                let timestamp = chrono::DateTime::parse_from_rfc3339(&timestamp_str)
-                    .ok()
-                    .map(|dt| dt.with_timezone(&chrono::Utc))
-                    .ok_or(rusqlite::Error::InvalidQuery)?;
-
-                Ok(RealityAssessment {
-                    id,
-                    vision_id,
-                    entry,
-                    timestamp,
-                })
+    // Load reality assessments
+    let mut stmt = conn
+        .prepare("SELECT id, vision_id, entry, timestamp, updated_at, updated_by FROM reality_assessments ORDER BY timestamp DESC")
+        .map_err(|e| TensileError::Serialization(e.to_string()))?;
+
+    let realities = stmt
+        .query_map([], |row| {
+            let id_str: String = row.get(0)?;
+            let vision_id_str: String = row.get(1)?;
+            let entry: String = row.get(2)?;
+            let timestamp_str: String = row.get(3)?;
+            let updated_at: Option<String> = row.get(4)?;
+            let updated_by: Option<String> = row.get(5)?;
+
+            let id = Uuid::parse_str(&id_str).map_err(|_| rusqlite::Error::InvalidQuery)?;
+            let vision_id =
+                Uuid::parse_str(&vision_id_str).map_err(|_| rusqlite::Error::InvalidQuery)?;
+            let timestamp = chrono::DateTime::parse_from_rfc3339(&timestamp_str)
+                .ok()
+                .map(|dt| dt.with_timezone(&chrono::Utc))
+                .ok_or(rusqlite::Error::InvalidQuery)?;
+
+            Ok(RealityAssessment {
+                id,
+                vision_id,
+                entry,
+                timestamp,
+                updated_at: parse_optional_timestamp(updated_at).unwrap_or(timestamp),
+                updated_by: updated_by.and_then(|d| Uuid::parse_str(&d).ok()),
             })
-            .map_err(|e| TensileError::Serialization(e.to_string()))?
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| TensileError::Serialization(e.to_string()))?;
-
-        // Load action logs
-        let mut stmt = conn
-            .prepare("SELECT id, vision_id, entry, timestamp, success FROM action_logs ORDER BY timestamp DESC")
-            .map_err(|e| TensileError::Serialization(e.to_string()))?;
-
-        let actions = stmt
-            .query_map([], |row| {
-                let id_str: String = row.get(0)?;
-                let vision_id_str: String = row.get(1)?;
-                let entry: String = row.get(2)?;
-                let timestamp_str: String = row.get(3)?;
-                let success: bool = row.get(4)?;
-
-                let id = Uuid::parse_str(&id_str).map_err(|_| rusqlite::Error::InvalidQuery)?;
-                let vision_id =
-                    Uuid::parse_str(&vision_id_str).map_err(|_| rusqlite::Error::InvalidQuery)?;
-                let timestamp = chrono::DateTime::parse_from_rfc3339(&timestamp_str)
-                    .ok()
-                    .map(|dt| dt.with_timezone(&chrono::Utc))
-                    .ok_or(rusqlite::Error::InvalidQuery)?;
-
-                Ok(ActionLog {
-                    id,
-                    vision_id,
-                    entry,
-                    timestamp,
-                    success,
-                })
+        })
+        .map_err(|e| TensileError::Serialization(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| TensileError::Serialization(e.to_string()))?;
+
+    // Load action logs
+    let mut stmt = conn
+        .prepare("SELECT id, vision_id, entry, timestamp, success, updated_at, updated_by FROM action_logs ORDER BY timestamp DESC")
+        .map_err(|e| TensileError::Serialization(e.to_string()))?;
+
+    let actions = stmt
+        .query_map([], |row| {
+            let id_str: String = row.get(0)?;
+            let vision_id_str: String = row.get(1)?;
+            let entry: String = row.get(2)?;
+            let timestamp_str: String = row.get(3)?;
+            let success: bool = row.get(4)?;
+            let updated_at: Option<String> = row.get(5)?;
+            let updated_by: Option<String> = row.get(6)?;
+
+            let id = Uuid::parse_str(&id_str).map_err(|_| rusqlite::Error::InvalidQuery)?;
+            let vision_id =
+                Uuid::parse_str(&vision_id_str).map_err(|_| rusqlite::Error::InvalidQuery)?;
+            let timestamp = chrono::DateTime::parse_from_rfc3339(&timestamp_str)
+                .ok()
+                .map(|dt| dt.with_timezone(&chrono::Utc))
+                .ok_or(rusqlite::Error::InvalidQuery)?;
+
+            Ok(ActionLog {
+                id,
+                vision_id,
+                entry,
+                timestamp,
+                success,
+                updated_at: parse_optional_timestamp(updated_at).unwrap_or(timestamp),
+                updated_by: updated_by.and_then(|d| Uuid::parse_str(&d).ok()),
             })
-            .map_err(|e| TensileError::Serialization(e.to_string()))?
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| TensileError::Serialization(e.to_string()))?;
-
-        Ok(Database {
-            visions,
-            realities,
-            actions,
-            user: None,
         })
-    }
-
-    pub fn save(db: &Database) -> TensileResult<()> {
-        let store = SqliteStore::new(None)?;
-        let mut conn = store.get_connection()?;
-
-        // Start transaction
-        let tx = conn
-            .transaction()
-            .map_err(|e| TensileError::Database(e.to_string()))?;
+        .map_err(|e| TensileError::Serialization(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| TensileError::Serialization(e.to_string()))?;
+
+    // Load tombstones
+    let mut stmt = conn
+        .prepare("SELECT id, kind, deleted_at, deleted_by FROM tombstones")
+        .map_err(|e| TensileError::Serialization(e.to_string()))?;
+
+    let tombstones = stmt
+        .query_map([], |row| {
+            let id_str: String = row.get(0)?;
+            let kind_str: String = row.get(1)?;
+            let deleted_at_str: String = row.get(2)?;
+            let deleted_by: Option<String> = row.get(3)?;
+
+            let id = Uuid::parse_str(&id_str).map_err(|_| rusqlite::Error::InvalidQuery)?;
+            let kind = kind_str
+                .parse::<RecordKind>()
+                .map_err(|_| rusqlite::Error::InvalidQuery)?;
+            let deleted_at = parse_optional_timestamp(Some(deleted_at_str))
+                .ok_or(rusqlite::Error::InvalidQuery)?;
+
+            Ok(Tombstone {
+                id,
+                kind,
+                deleted_at,
+                deleted_by: deleted_by.and_then(|d| Uuid::parse_str(&d).ok()),
+            })
+        })
+        .map_err(|e| TensileError::Serialization(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| TensileError::Serialization(e.to_string()))?;
+
+    // Load user state (a single row)
+    let user = conn
+        .query_row(
+            "SELECT current_focus, last_reviewed FROM users WHERE id = 1",
+            [],
+            |row| {
+                let current_focus: Option<String> = row.get(0)?;
+                let last_reviewed: Option<String> = row.get(1)?;
+                Ok(User {
+                    current_focus: current_focus.and_then(|f| Uuid::parse_str(&f).ok()),
+                    last_reviewed: parse_optional_timestamp(last_reviewed),
+                })
+            },
+        )
+        .optional()
+        .map_err(|e| TensileError::Serialization(e.to_string()))?;
+
+    let mut db = Database {
+        visions,
+        realities,
+        actions,
+        user,
+        tombstones,
+    };
+
+    // Rebuild parent-child relationships
+    db.rebuild_children();
+
+    Ok(db)
+}
 
-        // Clear existing data
-        tx.execute("DELETE FROM action_logs", [])
-            .map_err(|e| TensileError::Serialization(e.to_string()))?;
-        tx.execute("DELETE FROM reality_assessments", [])
-            .map_err(|e| TensileError::Serialization(e.to_string()))?;
-        tx.execute("DELETE FROM visions", [])
-            .map_err(|e| TensileError::Serialization(e.to_string()))?;
+pub(crate) fn write_database(conn: &mut Connection, db: &Database) -> TensileResult<()> {
+    // Records modified locally carry no device yet; stamp them with ours
+    let device = config::device_id()?.to_string();
+    let stamp = |by: Option<Uuid>| by.map(|d| d.to_string()).unwrap_or_else(|| device.clone());
+
+    // Start transaction
+    let tx = conn
+        .transaction()
+        .map_err(|e| TensileError::Database(e.to_string()))?;
+
+    // Clear existing data
+    tx.execute("DELETE FROM users", [])
+        .map_err(|e| TensileError::Serialization(e.to_string()))?;
+    tx.execute("DELETE FROM action_logs", [])
+        .map_err(|e| TensileError::Serialization(e.to_string()))?;
+    tx.execute("DELETE FROM reality_assessments", [])
+        .map_err(|e| TensileError::Serialization(e.to_string()))?;
+    tx.execute("DELETE FROM vision_dependencies", [])
+        .map_err(|e| TensileError::Serialization(e.to_string()))?;
+    tx.execute("DELETE FROM visions", [])
+        .map_err(|e| TensileError::Serialization(e.to_string()))?;
+    tx.execute("DELETE FROM tombstones", [])
+        .map_err(|e| TensileError::Serialization(e.to_string()))?;
+
+    // Insert visions
+    for vision in &db.visions {
+        tx.execute(
+            "INSERT INTO visions (id, title, description, parent_id, created_at, state, updated_at, updated_by, position, achieved_at, retrospective, stakes, blocked_reason, unblock_when, unblock_on, blocked_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+            params![
+                vision.id.to_string(),
+                &vision.title,
+                &vision.description,
+                vision.parent.map(|p| p.to_string()),
+                vision.created_at.to_rfc3339(),
+                vision.state.to_string(),
+                vision.updated_at.to_rfc3339(),
+                stamp(vision.updated_by),
+                vision.position,
+                vision.achieved_at.map(|t| t.to_rfc3339()),
+                &vision.retrospective,
+                vision.stakes,
+                vision.blocker.as_ref().map(|b| &b.reason),
+                vision.blocker.as_ref().and_then(|b| b.unblock_when.as_ref()),
+                vision
+                    .blocker
+                    .as_ref()
+                    .and_then(|b| b.unblock_on)
+                    .map(|d| d.format("%Y-%m-%d").to_string()),
+                vision.blocker.as_ref().map(|b| b.since.to_rfc3339()),
+            ],
+        )
+        .map_err(|e| TensileError::Serialization(e.to_string()))?;
+    }
 
-        // Insert visions
-        for vision in &db.visions {
+    // Insert dependencies
+    for vision in &db.visions {
+        for depends_on in &vision.depends_on {
             tx.execute(
-                "INSERT INTO visions (id, title, description, parent_id, created_at, completed, state) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-                params![
-                    vision.id.to_string(),
-                    &vision.title,
-                    &vision.description,
-                    vision.parent.map(|p| p.to_string()),
-                    vision.created_at.to_rfc3339(),
-                    vision.completed,
-                    vision.state.to_string(),
-                ],
+                "INSERT OR IGNORE INTO vision_dependencies (vision_id, depends_on) VALUES (?1, ?2)",
+                params![vision.id.to_string(), depends_on.to_string()],
             )
             .map_err(|e| TensileError::Serialization(e.to_string()))?;
         }
+    }
 
-        // Insert reality assessments
-        for reality in &db.realities {
-            tx.execute(
-                "INSERT INTO reality_assessments (id, vision_id, entry, timestamp) VALUES (?1, ?2, ?3, ?4)",
-                params![
-                    reality.id.to_string(),
-                    reality.vision_id.to_string(),
-                    &reality.entry,
-                    reality.timestamp.to_rfc3339(),
-                ],
-            )
-            .map_err(|e| TensileError::Serialization(e.to_string()))?;
-        }
+    // Insert reality assessments
+    for reality in &db.realities {
+        tx.execute(
+            "INSERT INTO reality_assessments (id, vision_id, entry, timestamp, updated_at, updated_by) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                reality.id.to_string(),
+                reality.vision_id.to_string(),
+                &reality.entry,
+                reality.timestamp.to_rfc3339(),
+                reality.updated_at.to_rfc3339(),
+                stamp(reality.updated_by),
+            ],
+        )
+        .map_err(|e| TensileError::Serialization(e.to_string()))?;
+    }
 
-        // Insert action logs
-        for action in &db.actions {
-            tx.execute(
-                "INSERT INTO action_logs (id, vision_id, entry, timestamp, success) VALUES (?1, ?2, ?3, ?4, ?5)",
-                params![
-                    action.id.to_string(),
-                    action.vision_id.to_string(),
-                    &action.entry,
-                    action.timestamp.to_rfc3339(),
-                    action.success,
-                ],
-            )
-            .map_err(|e| TensileError::Serialization(e.to_string()))?;
-        }
+    // Insert action logs
+    for action in &db.actions {
+        tx.execute(
+            "INSERT INTO action_logs (id, vision_id, entry, timestamp, success, updated_at, updated_by) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                action.id.to_string(),
+                action.vision_id.to_string(),
+                &action.entry,
+                action.timestamp.to_rfc3339(),
+                action.success,
+                action.updated_at.to_rfc3339(),
+                stamp(action.updated_by),
+            ],
+        )
+        .map_err(|e| TensileError::Serialization(e.to_string()))?;
+    }
 
-        tx.commit()
-            .map_err(|e| TensileError::Serialization(e.to_string()))?;
+    // Insert tombstones
+    for tombstone in &db.tombstones {
+        tx.execute(
+            "INSERT OR REPLACE INTO tombstones (id, kind, deleted_at, deleted_by) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                tombstone.id.to_string(),
+                tombstone.kind.to_string(),
+                tombstone.deleted_at.to_rfc3339(),
+                stamp(tombstone.deleted_by),
+            ],
+        )
+        .map_err(|e| TensileError::Serialization(e.to_string()))?;
+    }
 
-        Ok(())
+    // Insert user state
+    if let Some(user) = &db.user {
+        tx.execute(
+            "INSERT INTO users (id, current_focus, last_reviewed) VALUES (1, ?1, ?2)",
+            params![
+                user.current_focus.map(|f| f.to_string()),
+                user.last_reviewed.map(|t| t.to_rfc3339()),
+            ],
+        )
+        .map_err(|e| TensileError::Serialization(e.to_string()))?;
     }
+
+    tx.commit()
+        .map_err(|e| TensileError::Serialization(e.to_string()))?;
+
+    Ok(())
+}
+
+fn parse_optional_timestamp(value: Option<String>) -> Option<chrono::DateTime<chrono::Utc>> {
+    value
+        .and_then(|v| chrono::DateTime::parse_from_rfc3339(&v).ok())
+        .map(|dt| dt.with_timezone(&chrono::Utc))
 }
@@ -0,0 +1,152 @@
+use crate::{
+    config,
+    error::{TensileError, TensileResult},
+    models::VisionTemplate,
+};
+use ron::{extensions::Extensions, ser::PrettyConfig};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const EXTENSION: &str = "ron";
+
+/// What a template file holds: the name as typed, which the file name only
+/// approximates, and the vision tree.
+#[derive(Serialize, Deserialize)]
+struct TemplateFile {
+    name: String,
+    template: VisionTemplate,
+}
+
+/// Vision templates, one `<name>.ron` file each, meant to be readable and
+/// editable by hand. Spaces in a name become `-` in the file name.
+pub struct TemplateStore {
+    dir: PathBuf,
+}
+
+impl TemplateStore {
+    pub fn new(dir: PathBuf) -> Self {
+        TemplateStore { dir }
+    }
+
+    /// The `templates` directory in the data directory.
+    pub fn open() -> Self {
+        TemplateStore::new(config::data_dir().join("templates"))
+    }
+
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}", file_stem(name), EXTENSION))
+    }
+
+    /// Write a template, refusing to overwrite one of the same name unless
+    /// `replace` is set. Returns the file written.
+    pub fn save(
+        &self,
+        name: &str,
+        template: &VisionTemplate,
+        replace: bool,
+    ) -> TensileResult<PathBuf> {
+        check_name(name)?;
+        let path = self.path(name);
+        if path.exists() && !replace {
+            return Err(TensileError::StateConflict(format!(
+                "Template {} already exists (use --force to replace it)",
+                name
+            )));
+        }
+
+        let config = PrettyConfig::new()
+            .new_line("\n".to_string())
+            .indentor("    ".to_string())
+            .struct_names(false)
+            .extensions(Extensions::IMPLICIT_SOME);
+        let file = TemplateFile {
+            name: name.trim().to_string(),
+            template: template.clone(),
+        };
+        let mut contents = ron::ser::to_string_pretty(&file, config)
+            .map_err(|e| TensileError::Serialization(e.to_string()))?;
+        contents.push('\n');
+
+        fs::create_dir_all(&self.dir)?;
+        fs::write(&path, contents)?;
+        Ok(path)
+    }
+
+    pub fn load(&self, name: &str) -> TensileResult<VisionTemplate> {
+        check_name(name)?;
+        read(&self.path(name))
+            .map_err(|e| match e {
+                TensileError::Io(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    TensileError::NotFound(format!(
+                        "Template not found: {} (see `tensile template list`)",
+                        name
+                    ))
+                }
+                e => e,
+            })
+            .map(|file| file.template)
+    }
+
+    /// Every template with its name, sorted by name.
+    pub fn list(&self) -> TensileResult<Vec<(String, VisionTemplate)>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut templates = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == EXTENSION))
+            .map(|path| read(&path).map(|file| (file.name, file.template)))
+            .collect::<TensileResult<Vec<_>>>()?;
+        templates.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(templates)
+    }
+
+    pub fn delete(&self, name: &str) -> TensileResult<()> {
+        self.load(name)?;
+        fs::remove_file(self.path(name))?;
+        Ok(())
+    }
+}
+
+/// Read a template file. A file holding just the tree, as is handy to write
+/// by hand, is named after the file.
+fn read(path: &Path) -> TensileResult<TemplateFile> {
+    let contents = fs::read_to_string(path)?;
+    ron::from_str(&contents)
+        .or_else(|e| {
+            let template = ron::from_str(&contents).map_err(|_| e)?;
+            let name = path.file_stem().unwrap_or_default().to_string_lossy();
+            Ok(TemplateFile {
+                name: name.into_owned(),
+                template,
+            })
+        })
+        .map_err(|e: ron::error::SpannedError| {
+            TensileError::Parse(format!("{}: {}", path.display(), e))
+        })
+}
+
+/// The file name for a template name: runs of spaces become `-`.
+fn file_stem(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join("-")
+}
+
+fn check_name(name: &str) -> TensileResult<()> {
+    let valid = !name.trim().is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == ' ' || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(TensileError::Validation(format!(
+            "Invalid template name: {:?} (use letters, digits, spaces, '-' and '_')",
+            name
+        )))
+    }
+}
@@ -0,0 +1,359 @@
+use super::Session;
+use crate::{
+    error::{TensileError, TensileResult},
+    events::Event,
+    models::{ActionLog, RecordKind},
+};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use uuid::Uuid;
+
+/// The calendar period an action review covers, up to now.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewPeriod {
+    #[default]
+    #[value(alias = "day")]
+    Daily,
+    #[value(alias = "week")]
+    Weekly,
+    #[value(alias = "month")]
+    Monthly,
+}
+
+impl ReviewPeriod {
+    /// Start of the day, week (from `week_start`) or month containing
+    /// `now`, in `now`'s time zone.
+    pub fn start<Tz: TimeZone>(self, now: &DateTime<Tz>, week_start: Weekday) -> DateTime<Utc> {
+        let today = now.date_naive();
+        let first = match self {
+            ReviewPeriod::Daily => today,
+            ReviewPeriod::Weekly => week_of(today, week_start),
+            ReviewPeriod::Monthly => today.with_day(1).unwrap_or(today),
+        };
+        start_of_day(&now.timezone(), first)
+    }
+}
+
+/// How `action review` groups actions.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    Vision,
+    Day,
+    Week,
+}
+
+/// Which actions a review covers. Days and weeks for grouping are taken in
+/// `Local` time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReviewQuery {
+    /// Earliest time included; `None` for no lower bound.
+    pub since: Option<DateTime<Utc>>,
+    /// Time the review ends, excluded; `None` for no upper bound.
+    pub until: Option<DateTime<Utc>>,
+    pub vision_id: Option<Uuid>,
+    pub group_by: Option<GroupBy>,
+}
+
+impl std::str::FromStr for ReviewPeriod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "daily" | "day" => Ok(ReviewPeriod::Daily),
+            "weekly" | "week" => Ok(ReviewPeriod::Weekly),
+            "monthly" | "month" => Ok(ReviewPeriod::Monthly),
+            _ => Err(format!(
+                "Unknown period: {} (expected daily, weekly or monthly)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ReviewPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReviewPeriod::Daily => write!(f, "daily"),
+            ReviewPeriod::Weekly => write!(f, "weekly"),
+            ReviewPeriod::Monthly => write!(f, "monthly"),
+        }
+    }
+}
+
+/// Changes to a logged action; `None` leaves a field as it is.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActionAmendment {
+    pub entry: Option<String>,
+    pub success: Option<bool>,
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+/// Actions logged within a review range, newest first.
+#[derive(Serialize, Debug, Clone)]
+pub struct ActionReview {
+    /// The named period, when the range is one.
+    pub period: Option<ReviewPeriod>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub total: usize,
+    pub successful: usize,
+    pub actions: Vec<ActionLog>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_by: Option<GroupBy>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<ActionGroup>,
+}
+
+/// Actions sharing a vision, day or week, newest group first.
+#[derive(Serialize, Debug, Clone)]
+pub struct ActionGroup {
+    /// Vision id, or the `YYYY-MM-DD` of the day or first day of the week.
+    pub key: String,
+    /// Vision title, or the first day of the group.
+    pub label: String,
+    pub total: usize,
+    pub successful: usize,
+    pub actions: Vec<ActionLog>,
+}
+
+impl Session {
+    /// Log a successful action taken now.
+    pub fn log_action(
+        &mut self,
+        vision_id: Uuid,
+        entry: impl Into<String>,
+    ) -> TensileResult<ActionLog> {
+        self.log_action_at(vision_id, entry, true, Utc::now())
+    }
+
+    /// Log an action with its outcome, possibly backdated.
+    pub fn log_action_at(
+        &mut self,
+        vision_id: Uuid,
+        entry: impl Into<String>,
+        success: bool,
+        timestamp: DateTime<Utc>,
+    ) -> TensileResult<ActionLog> {
+        self.vision(vision_id)?;
+        check_not_future(timestamp)?;
+
+        let mut action = ActionLog::new(vision_id, entry.into());
+        action.success = success;
+        action.timestamp = timestamp;
+        self.db.actions.push(action.clone());
+        self.changed();
+        self.record(Event::ActionLogged {
+            vision_id,
+            vision_title: self.vision(vision_id)?.title.clone(),
+            action: action.clone(),
+        });
+        Ok(action)
+    }
+
+    pub fn action(&self, id: Uuid) -> TensileResult<&ActionLog> {
+        self.db
+            .actions
+            .iter()
+            .find(|a| a.id == id)
+            .ok_or_else(|| TensileError::NotFound(format!("Action not found: {}", id)))
+    }
+
+    pub fn amend_action(
+        &mut self,
+        id: Uuid,
+        amendment: ActionAmendment,
+    ) -> TensileResult<ActionLog> {
+        if let Some(timestamp) = amendment.timestamp {
+            check_not_future(timestamp)?;
+        }
+
+        let action = self
+            .db
+            .actions
+            .iter_mut()
+            .find(|a| a.id == id)
+            .ok_or_else(|| TensileError::NotFound(format!("Action not found: {}", id)))?;
+        if let Some(entry) = amendment.entry {
+            action.entry = entry;
+        }
+        if let Some(success) = amendment.success {
+            action.success = success;
+        }
+        if let Some(timestamp) = amendment.timestamp {
+            action.timestamp = timestamp;
+        }
+        action.touch();
+        let action = action.clone();
+        self.changed();
+        self.record(Event::ActionAmended {
+            action: action.clone(),
+        });
+        Ok(action)
+    }
+
+    /// Delete an action, returning what was deleted.
+    pub fn delete_action(&mut self, id: Uuid) -> TensileResult<ActionLog> {
+        let action = self.action(id)?.clone();
+        self.db.remove_record(RecordKind::Action, id);
+        self.changed();
+        self.record(Event::ActionDeleted {
+            action: action.clone(),
+        });
+        Ok(action)
+    }
+
+    /// Start of the current calendar period, with weeks starting on the
+    /// configured day.
+    pub fn period_start(&self, period: ReviewPeriod) -> DateTime<Utc> {
+        period.start(&Local::now(), self.config.review.week_starts_on)
+    }
+
+    /// Actions from the current calendar period, optionally for one vision.
+    pub fn review(&self, period: ReviewPeriod, vision_id: Option<Uuid>) -> ActionReview {
+        let mut review = self.review_range(&ReviewQuery {
+            since: Some(self.period_start(period)),
+            vision_id,
+            ..Default::default()
+        });
+        review.period = Some(period);
+        review
+    }
+
+    /// Actions within an arbitrary range, optionally grouped.
+    pub fn review_range(&self, query: &ReviewQuery) -> ActionReview {
+        let mut actions: Vec<ActionLog> = self
+            .db
+            .actions
+            .iter()
+            .filter(|a| query.vision_id.is_none_or(|id| a.vision_id == id))
+            .filter(|a| query.since.is_none_or(|since| a.timestamp >= since))
+            .filter(|a| query.until.is_none_or(|until| a.timestamp < until))
+            .cloned()
+            .collect();
+        actions.sort_by_key(|a| std::cmp::Reverse(a.timestamp));
+
+        let groups = match query.group_by {
+            Some(group_by) => self.group(&actions, group_by),
+            None => Vec::new(),
+        };
+
+        ActionReview {
+            period: None,
+            since: query.since,
+            until: query.until,
+            total: actions.len(),
+            successful: actions.iter().filter(|a| a.success).count(),
+            actions,
+            group_by: query.group_by,
+            groups,
+        }
+    }
+
+    fn group(&self, actions: &[ActionLog], group_by: GroupBy) -> Vec<ActionGroup> {
+        let mut groups: Vec<ActionGroup> = Vec::new();
+
+        for action in actions {
+            let day = action.timestamp.with_timezone(&Local).date_naive();
+            let (key, label) = match group_by {
+                GroupBy::Vision => (
+                    action.vision_id.to_string(),
+                    self.vision(action.vision_id)
+                        .map(|v| v.title.clone())
+                        .unwrap_or_else(|_| "(deleted vision)".to_string()),
+                ),
+                GroupBy::Day => (day.to_string(), day.format("%a %Y-%m-%d").to_string()),
+                GroupBy::Week => {
+                    let first = week_of(day, self.config.review.week_starts_on);
+                    (first.to_string(), format!("Week of {}", first))
+                }
+            };
+
+            let index = match groups.iter().position(|g| g.key == key) {
+                Some(index) => index,
+                None => {
+                    groups.push(ActionGroup {
+                        key,
+                        label,
+                        total: 0,
+                        successful: 0,
+                        actions: Vec::new(),
+                    });
+                    groups.len() - 1
+                }
+            };
+            let group = &mut groups[index];
+            group.total += 1;
+            group.successful += usize::from(action.success);
+            group.actions.push(action.clone());
+        }
+        groups
+    }
+}
+
+/// The first day of the week containing `day`.
+fn week_of(day: NaiveDate, week_start: Weekday) -> NaiveDate {
+    day.week(week_start).first_day()
+}
+
+/// Midnight at the start of `day` in `tz`, or the first time after it when
+/// a clock change skips midnight.
+fn start_of_day<Tz: TimeZone>(tz: &Tz, day: NaiveDate) -> DateTime<Utc> {
+    let midnight = day.and_hms_opt(0, 0, 0).unwrap_or_default();
+    (0..24)
+        .find_map(|hour| {
+            tz.from_local_datetime(&(midnight + Duration::hours(hour)))
+                .earliest()
+        })
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&midnight))
+}
+
+/// Allow a minute of clock skew between devices.
+fn check_not_future(timestamp: DateTime<Utc>) -> TensileResult<()> {
+    if timestamp > Utc::now() + Duration::minutes(1) {
+        return Err(TensileError::Validation(format!(
+            "Action time is in the future: {}",
+            timestamp.to_rfc3339()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    #[test]
+    fn periods_start_on_calendar_boundaries_in_local_time() {
+        let tz = FixedOffset::east_opt(2 * 3600).unwrap();
+        // Thursday 2026-10-15 at 01:30 local is still the 14th in UTC.
+        let now = tz.with_ymd_and_hms(2026, 10, 15, 1, 30, 0).unwrap();
+        let local_midnight = |d| {
+            tz.with_ymd_and_hms(2026, 10, d, 0, 0, 0)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+
+        assert_eq!(
+            ReviewPeriod::Daily.start(&now, Weekday::Mon),
+            local_midnight(15)
+        );
+        assert_eq!(
+            ReviewPeriod::Weekly.start(&now, Weekday::Mon),
+            local_midnight(12)
+        );
+        assert_eq!(
+            ReviewPeriod::Weekly.start(&now, Weekday::Sun),
+            local_midnight(11)
+        );
+        assert_eq!(
+            ReviewPeriod::Monthly.start(&now, Weekday::Mon),
+            local_midnight(1)
+        );
+        assert!("fortnightly".parse::<ReviewPeriod>().is_err());
+    }
+}
@@ -0,0 +1,242 @@
+use crate::{
+    error::{TensileError, TensileResult},
+    models::{ActionLog, Database, Vision},
+    sync::Conflict,
+};
+use uuid::Uuid;
+
+/// Shortest id prefix accepted, so short words are treated as titles.
+const MIN_PREFIX_LEN: usize = 4;
+
+/// Length of the abbreviated ids shown in listings and errors.
+const SHORT_ID_LEN: usize = 8;
+
+/// Abbreviated id, like git's short hashes.
+pub fn short_id(id: &Uuid) -> String {
+    id.to_string()[..SHORT_ID_LEN].to_string()
+}
+
+/// Resolve a vision argument to an id. Tried in order:
+///
+/// 1. `.` for the current focus
+/// 2. a full id
+/// 3. a unique id prefix of at least four characters
+/// 4. an exact title (case-insensitive)
+/// 5. a fuzzy title match: substring, then all words, then letters in order
+///
+/// Several matches at the first step that finds any is an ambiguity error
+/// listing the candidates.
+pub fn resolve_vision(db: &Database, input: &str) -> TensileResult<Uuid> {
+    let query = input.trim();
+    if query.is_empty() {
+        return Err(TensileError::Parse("Empty vision reference".to_string()));
+    }
+
+    if query == "." {
+        let focus = db
+            .user
+            .as_ref()
+            .and_then(|u| u.current_focus)
+            .ok_or_else(|| {
+                TensileError::NotFound(
+                    "No current focus; set one with `tensile focus <vision>`".to_string(),
+                )
+            })?;
+        if !db.visions.iter().any(|v| v.id == focus) {
+            return Err(TensileError::NotFound(format!(
+                "Focused vision no longer exists: {}",
+                focus
+            )));
+        }
+        return Ok(focus);
+    }
+
+    if let Ok(id) = Uuid::parse_str(query) {
+        if !db.visions.iter().any(|v| v.id == id) {
+            return Err(TensileError::NotFound(format!(
+                "Vision not found: {}",
+                query
+            )));
+        }
+        return Ok(id);
+    }
+
+    let lower = query.to_lowercase();
+    let is_prefix =
+        lower.len() >= MIN_PREFIX_LEN && lower.chars().all(|c| c.is_ascii_hexdigit() || c == '-');
+
+    let matchers: [&dyn Fn(&Vision) -> bool; 5] = [
+        &|v| is_prefix && v.id.to_string().starts_with(&lower),
+        &|v| v.title.to_lowercase() == lower,
+        &|v| v.title.to_lowercase().contains(&lower),
+        &|v| {
+            let title = v.title.to_lowercase();
+            lower.split_whitespace().all(|word| title.contains(word))
+        },
+        &|v| is_subsequence(&lower, &v.title.to_lowercase()),
+    ];
+
+    for matches in matchers {
+        let candidates: Vec<&Vision> = db.visions.iter().filter(|v| matches(v)).collect();
+        match candidates.as_slice() {
+            [] => continue,
+            [vision] => return Ok(vision.id),
+            _ => return Err(ambiguous(query, &candidates)),
+        }
+    }
+
+    Err(TensileError::NotFound(format!(
+        "Vision not found: {}",
+        query
+    )))
+}
+
+/// Resolve an action argument to an id: a full id or a unique id prefix of
+/// at least four characters, as shown by `action review`.
+pub fn resolve_action(db: &Database, input: &str) -> TensileResult<Uuid> {
+    let query = input.trim().to_lowercase();
+    if query.len() < MIN_PREFIX_LEN {
+        return Err(TensileError::Parse(format!(
+            "Action id must be at least {} characters: {}",
+            MIN_PREFIX_LEN, input
+        )));
+    }
+
+    let candidates: Vec<&ActionLog> = db
+        .actions
+        .iter()
+        .filter(|a| a.id.to_string().starts_with(&query))
+        .collect();
+
+    match candidates.as_slice() {
+        [] => Err(TensileError::NotFound(format!(
+            "Action not found: {}",
+            input
+        ))),
+        [action] => Ok(action.id),
+        _ => {
+            let list: Vec<String> = candidates
+                .iter()
+                .map(|a| format!("  {}  {}", short_id(&a.id), a.entry))
+                .collect();
+            Err(TensileError::Ambiguous(format!(
+                "'{}' matches {} actions:\n{}",
+                input,
+                candidates.len(),
+                list.join("\n")
+            )))
+        }
+    }
+}
+
+/// Resolve a conflict argument to an id: a full id or a unique id prefix
+/// of at least four characters, as with actions.
+pub fn resolve_conflict(conflicts: &[Conflict], input: &str) -> TensileResult<Uuid> {
+    let query = input.trim().to_lowercase();
+    if query.len() < MIN_PREFIX_LEN {
+        return Err(TensileError::Parse(format!(
+            "Conflict id must be at least {} characters: {}",
+            MIN_PREFIX_LEN, input
+        )));
+    }
+
+    let candidates: Vec<&Conflict> = conflicts
+        .iter()
+        .filter(|c| c.id.to_string().starts_with(&query))
+        .collect();
+
+    match candidates.as_slice() {
+        [] => Err(TensileError::NotFound(format!(
+            "No sync conflict for {}",
+            input
+        ))),
+        [conflict] => Ok(conflict.id),
+        _ => {
+            let list: Vec<String> = candidates
+                .iter()
+                .map(|c| format!("  {}  {}", short_id(&c.id), c.kind))
+                .collect();
+            Err(TensileError::Ambiguous(format!(
+                "'{}' matches {} conflicts:\n{}",
+                input,
+                candidates.len(),
+                list.join("\n")
+            )))
+        }
+    }
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut chars = haystack.chars();
+    needle
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .all(|c| chars.any(|h| h == c))
+}
+
+fn ambiguous(query: &str, candidates: &[&Vision]) -> TensileError {
+    let list: Vec<String> = candidates
+        .iter()
+        .map(|v| format!("  {}  {}", short_id(&v.id), v.title))
+        .collect();
+
+    TensileError::Ambiguous(format!(
+        "'{}' matches {} visions:\n{}",
+        query,
+        candidates.len(),
+        list.join("\n")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::User;
+
+    fn database(titles: &[&str]) -> Database {
+        let mut db = Database::default();
+        for title in titles {
+            db.visions.push(Vision::new(title.to_string()));
+        }
+        db
+    }
+
+    #[test]
+    fn resolves_prefixes_titles_and_focus() {
+        let mut db = database(&["Learn Rust", "Run a marathon", "Learn the cello"]);
+        let rust = db.visions[0].id;
+        let marathon = db.visions[1].id;
+
+        assert_eq!(resolve_vision(&db, &rust.to_string()).unwrap(), rust);
+        assert_eq!(resolve_vision(&db, &short_id(&rust)).unwrap(), rust);
+        assert_eq!(resolve_vision(&db, "learn rust").unwrap(), rust);
+        assert_eq!(resolve_vision(&db, "marathon").unwrap(), marathon);
+        assert_eq!(resolve_vision(&db, "rn rst").unwrap(), rust);
+
+        assert!(resolve_vision(&db, ".").is_err());
+        db.user = Some(User {
+            current_focus: Some(marathon),
+            last_reviewed: None,
+        });
+        assert_eq!(resolve_vision(&db, ".").unwrap(), marathon);
+    }
+
+    #[test]
+    fn ambiguity_lists_candidates() {
+        let db = database(&["Learn Rust", "Run a marathon", "Learn the cello"]);
+
+        match resolve_vision(&db, "learn") {
+            Err(TensileError::Ambiguous(message)) => {
+                assert!(message.contains("Learn Rust"));
+                assert!(message.contains("Learn the cello"));
+                assert!(!message.contains("marathon"));
+            }
+            other => panic!("expected ambiguity, got {:?}", other),
+        }
+
+        assert!(matches!(
+            resolve_vision(&db, "knitting"),
+            Err(TensileError::NotFound(_))
+        ));
+    }
+}
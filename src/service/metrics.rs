@@ -0,0 +1,168 @@
+use super::Session;
+use crate::{
+    engine::{
+        dependency::CriticalPath,
+        pattern::{AchievementStats, Pattern, PatternMetrics},
+        rollup::Rollup,
+        tension::VisionTension,
+        DependencyAnalyzer, PatternAnalyzer, RollupCalculator, TensionCalculator,
+    },
+    error::TensileResult,
+    models::{Vision, VisionState},
+};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Tension and action metrics for one vision.
+#[derive(Serialize, Debug, Clone)]
+pub struct VisionDetail {
+    pub vision_id: Uuid,
+    pub title: String,
+    pub state: VisionState,
+    pub description: Option<String>,
+    pub tension: Option<VisionTension>,
+    pub metrics: Option<PatternMetrics>,
+    pub stakes: u8,
+    /// Progress and tension of its sub-visions; absent without any
+    pub rollup: Option<Rollup>,
+}
+
+/// Totals across every vision.
+#[derive(Serialize, Debug, Clone)]
+pub struct Summary {
+    pub total_visions: usize,
+    pub active_visions: usize,
+    pub total_actions: usize,
+    pub total_realities: usize,
+    pub average_tension: f32,
+    pub top_priority: Option<VisionTension>,
+    pub achievements: AchievementStats,
+}
+
+/// Pattern of one vision's actions.
+#[derive(Serialize, Debug, Clone)]
+pub struct PatternReport {
+    pub vision_id: Uuid,
+    pub title: String,
+    pub pattern: Pattern,
+    /// Share of successful actions, from 0 to 1; absent without actions
+    pub success_rate: Option<f32>,
+    pub total_actions: usize,
+    /// Days from creation to achievement, for achieved visions
+    pub days_to_achieve: Option<i64>,
+    pub retrospective: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct VisionPattern {
+    pub vision_id: Uuid,
+    pub title: String,
+    pub pattern: Pattern,
+}
+
+impl Session {
+    /// Every vision's tension, highest first.
+    pub fn priorities(&self) -> Vec<VisionTension> {
+        TensionCalculator::sort_by_tension(TensionCalculator::calculate_all_tensions(&self.db))
+    }
+
+    pub fn vision_detail(&self, id: Uuid) -> TensileResult<VisionDetail> {
+        let vision = self.vision(id)?;
+
+        Ok(VisionDetail {
+            vision_id: id,
+            title: vision.title.clone(),
+            state: vision.state.clone(),
+            description: vision.description.clone(),
+            tension: TensionCalculator::calculate_vision_tension(&self.db, id),
+            metrics: PatternAnalyzer::get_detailed_metrics(&self.db, id, &self.config.patterns),
+            stakes: vision.stakes,
+            rollup: RollupCalculator::calculate(&self.db, id),
+        })
+    }
+
+    /// Upstream visions to attack first, most consequential first; see
+    /// `DependencyAnalyzer::critical_paths`.
+    pub fn critical_paths(&self, target: Option<Uuid>) -> TensileResult<Vec<CriticalPath>> {
+        if let Some(target) = target {
+            self.vision(target)?;
+        }
+        Ok(DependencyAnalyzer::critical_paths(&self.db, target))
+    }
+
+    /// The parent of `id` when all its sub-visions are achieved but it is
+    /// not, so it may be ready to mark achieved too.
+    pub fn completable_parent(&self, id: Uuid) -> Option<&Vision> {
+        let parent = self.vision(self.vision(id).ok()?.parent?).ok()?;
+        let rollup = RollupCalculator::calculate(&self.db, parent.id)?;
+        (!parent.is_achieved() && rollup.all_achieved()).then_some(parent)
+    }
+
+    pub fn summary(&self) -> Summary {
+        let tensions = TensionCalculator::calculate_all_tensions(&self.db);
+        let average_tension = if tensions.is_empty() {
+            0.0
+        } else {
+            tensions.iter().map(|t| t.tension_score).sum::<f32>() / tensions.len() as f32
+        };
+
+        Summary {
+            total_visions: self.db.visions.len(),
+            active_visions: self
+                .db
+                .visions
+                .iter()
+                .filter(|v| matches!(v.state, VisionState::InProgress))
+                .count(),
+            total_actions: self.db.actions.len(),
+            total_realities: self.db.realities.len(),
+            average_tension,
+            top_priority: TensionCalculator::get_priority_vision(&self.db),
+            achievements: self.achievements(),
+        }
+    }
+
+    pub fn pattern(&self, id: Uuid) -> TensileResult<PatternReport> {
+        let vision = self.vision(id)?;
+
+        let actions: Vec<_> = self
+            .db
+            .actions
+            .iter()
+            .filter(|a| a.vision_id == id)
+            .collect();
+        let success_rate = (!actions.is_empty())
+            .then(|| actions.iter().filter(|a| a.success).count() as f32 / actions.len() as f32);
+
+        Ok(PatternReport {
+            vision_id: id,
+            title: vision.title.clone(),
+            pattern: PatternAnalyzer::analyze_vision_pattern(&self.db, id, &self.config.patterns),
+            success_rate,
+            total_actions: actions.len(),
+            days_to_achieve: vision
+                .achieved_at
+                .map(|at| (at - vision.created_at).num_days()),
+            retrospective: vision.retrospective.clone(),
+        })
+    }
+
+    /// How long achieved visions took, and the actions behind them.
+    pub fn achievements(&self) -> AchievementStats {
+        PatternAnalyzer::achievement_stats(&self.db)
+    }
+
+    pub fn patterns(&self) -> Vec<VisionPattern> {
+        PatternAnalyzer::analyze_all_patterns(&self.db, &self.config.patterns)
+            .into_iter()
+            .filter_map(|(vision_id, pattern)| {
+                let vision = self.vision(vision_id).ok()?;
+                Some(VisionPattern {
+                    vision_id,
+                    title: vision.title.clone(),
+                    pattern,
+                })
+            })
+            .collect()
+    }
+}
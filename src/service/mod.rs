@@ -0,0 +1,284 @@
+//! Typed operations on a Tensile database.
+//!
+//! The CLI and TUI are front-ends over these; other programs can use them
+//! directly:
+//!
+//! ```no_run
+//! use tensile::service::Session;
+//!
+//! let mut session = Session::open()?;
+//! let vision = session.create_vision("Learn the cello")?;
+//! session.log_action(vision.id, "Booked a first lesson")?;
+//! session.commit()?;
+//! # Ok::<(), tensile::TensileError>(())
+//! ```
+
+mod action;
+mod lookup;
+mod metrics;
+mod reality;
+mod template;
+mod tree;
+mod vision;
+
+pub use action::{ActionAmendment, ActionGroup, ActionReview, GroupBy, ReviewPeriod, ReviewQuery};
+pub use lookup::{resolve_action, resolve_conflict, resolve_vision, short_id};
+pub use metrics::{PatternReport, Summary, VisionDetail, VisionPattern};
+pub use tree::{TreeNode, TreeOptions};
+pub use vision::{BlockedVision, Deletion, Transition};
+
+use crate::{
+    config::Config,
+    engine::TensionCalculator,
+    error::{TensileError, TensileResult},
+    events::{Event, EventBus},
+    hooks,
+    models::{Database, Vision},
+    persistence::{self, EventJournal, ImportMode, ImportSummary},
+};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Check the settings whose values this layer defines, such as the names of
+/// review periods. `Config::validate` covers the rest.
+pub fn check_config(config: &Config) -> TensileResult<()> {
+    config
+        .review
+        .default_period
+        .parse::<ReviewPeriod>()
+        .map(|_| ())
+        .map_err(|e| TensileError::Validation(format!("review.default_period: {}", e)))
+}
+
+/// The state of a `Session` to return to with `Session::rollback`.
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    db: Database,
+    dirty: bool,
+    events: usize,
+}
+
+/// A database and the operations on it. Changes stay in memory until
+/// `commit`.
+#[derive(Debug, Clone, Default)]
+pub struct Session {
+    db: Database,
+    dirty: bool,
+    config: Config,
+    /// Events for changes not yet published
+    events: Vec<Event>,
+    bus: EventBus,
+    /// Tension scores as of the last save, when a threshold hook needs them
+    tensions: HashMap<Uuid, f32>,
+}
+
+impl Session {
+    /// Load the configuration and the database it points to. Published
+    /// events go to the event journal and the configured hooks.
+    pub fn open() -> TensileResult<Self> {
+        let config = Config::load()?;
+        check_config(&config).map_err(|e| match e {
+            TensileError::Validation(message) => {
+                TensileError::Validation(format!("{}: {}", Config::path().display(), message))
+            }
+            other => other,
+        })?;
+        let mut session = Session::new(persistence::load_database()?).with_config(config);
+
+        let journal = EventJournal::new(None)?;
+        session.subscribe(move |event| {
+            if let Err(e) = journal.append(event) {
+                eprintln!("⚠ Could not journal {}: {}", event.name(), e);
+            }
+        });
+        hooks::subscribe(&mut session.bus, &session.config.hooks);
+        Ok(session)
+    }
+
+    /// Work on a database held in memory, e.g. one read from an export.
+    /// Uses the default configuration.
+    pub fn new(db: Database) -> Self {
+        Session {
+            db,
+            dirty: false,
+            config: Config::default(),
+            events: Vec::new(),
+            bus: EventBus::new(),
+            tensions: HashMap::new(),
+        }
+    }
+
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.config = config;
+        self.tensions = self.tension_scores();
+        self
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// The configured period of `action review`; daily if it names none.
+    pub fn default_review_period(&self) -> ReviewPeriod {
+        self.config
+            .review
+            .default_period
+            .parse()
+            .unwrap_or_default()
+    }
+
+    pub fn database(&self) -> &Database {
+        &self.db
+    }
+
+    pub fn into_database(self) -> Database {
+        self.db
+    }
+
+    /// Whether there are changes `commit` would save.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn changed(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Remember the data and queued events, so that changes made after this
+    /// can be undone with `rollback`.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            db: self.db.clone(),
+            dirty: self.dirty,
+            events: self.events.len(),
+        }
+    }
+
+    /// Undo every change since `checkpoint`, dropping the events they queued.
+    pub fn rollback(&mut self, checkpoint: Checkpoint) {
+        self.db = checkpoint.db;
+        self.dirty = checkpoint.dirty;
+        self.events.truncate(checkpoint.events);
+    }
+
+    /// Save changes to the configured database, then publish their events.
+    /// Does nothing if there are none.
+    pub fn commit(&mut self) -> TensileResult<()> {
+        if self.dirty {
+            persistence::save_database(&self.db)?;
+            self.dirty = false;
+        }
+        self.publish();
+        Ok(())
+    }
+
+    /// Call `subscriber` with each event this session publishes.
+    pub fn subscribe(&mut self, subscriber: impl Fn(&Event) + Send + Sync + 'static) {
+        self.bus.subscribe(subscriber);
+    }
+
+    /// Events for changes made since the last publish.
+    pub fn pending_events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Deliver pending events to subscribers without saving. `commit` does
+    /// this after saving; sessions that are never saved can call it directly.
+    pub fn publish(&mut self) {
+        let mut events = std::mem::take(&mut self.events);
+        events.extend(self.tension_crossings());
+        for event in &events {
+            self.bus.publish(event);
+        }
+    }
+
+    /// Queue an event for the next publish.
+    fn record(&mut self, event: Event) {
+        self.events.push(event);
+    }
+
+    /// Scores to compare against `hooks.tension_threshold`; empty unless a
+    /// hook is watching it.
+    fn tension_scores(&self) -> HashMap<Uuid, f32> {
+        if self.config.hooks.on_tension_threshold_crossed.is_none() {
+            return HashMap::new();
+        }
+        TensionCalculator::calculate_all_tensions(&self.db)
+            .into_iter()
+            .map(|t| (t.vision_id, t.tension_score))
+            .collect()
+    }
+
+    /// Visions whose tension moved across the threshold since the last save.
+    fn tension_crossings(&mut self) -> Vec<Event> {
+        let threshold = self.config.hooks.tension_threshold;
+        let current = self.tension_scores();
+        let crossings = current
+            .iter()
+            .filter_map(|(id, &to)| {
+                let from = *self.tensions.get(id)?;
+                let direction = match (f64::from(from) >= threshold, f64::from(to) >= threshold) {
+                    (false, true) => "above",
+                    (true, false) => "below",
+                    _ => return None,
+                };
+                Some(Event::TensionThresholdCrossed {
+                    vision_id: *id,
+                    vision_title: self.vision(*id).ok()?.title.clone(),
+                    threshold,
+                    from,
+                    to,
+                    direction,
+                })
+            })
+            .collect();
+        self.tensions = current;
+        crossings
+    }
+
+    /// Reload the configured database, dropping uncommitted changes.
+    pub fn reload(&mut self) -> TensileResult<()> {
+        *self = Session::open()?;
+        Ok(())
+    }
+
+    /// Combine another database, e.g. a parsed export, into this one.
+    pub fn import(&mut self, incoming: Database, mode: ImportMode) -> ImportSummary {
+        self.changed();
+        let summary = persistence::import(&mut self.db, incoming, mode);
+        self.record(Event::DataImported {
+            added: summary.added,
+            updated: summary.updated,
+            removed: summary.removed,
+        });
+        summary
+    }
+
+    /// Resolve a vision reference: an id, id prefix, title or `.`.
+    pub fn resolve(&self, input: &str) -> TensileResult<Uuid> {
+        resolve_vision(&self.db, input)
+    }
+
+    pub fn vision(&self, id: Uuid) -> TensileResult<&Vision> {
+        self.db
+            .visions
+            .iter()
+            .find(|v| v.id == id)
+            .ok_or_else(|| not_found(id))
+    }
+
+    fn vision_mut(&mut self, id: Uuid) -> TensileResult<&mut Vision> {
+        let vision = self
+            .db
+            .visions
+            .iter_mut()
+            .find(|v| v.id == id)
+            .ok_or_else(|| not_found(id))?;
+        self.dirty = true;
+        Ok(vision)
+    }
+}
+
+fn not_found(id: Uuid) -> TensileError {
+    TensileError::NotFound(format!("Vision not found: {}", id))
+}
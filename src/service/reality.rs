@@ -0,0 +1,37 @@
+use super::Session;
+use crate::{error::TensileResult, events::Event, models::RealityAssessment};
+use uuid::Uuid;
+
+impl Session {
+    pub fn record_reality(
+        &mut self,
+        vision_id: Uuid,
+        entry: impl Into<String>,
+    ) -> TensileResult<RealityAssessment> {
+        self.vision(vision_id)?;
+
+        let assessment = RealityAssessment::new(vision_id, entry.into());
+        self.db.realities.push(assessment.clone());
+        self.changed();
+        self.record(Event::RealityRecorded {
+            assessment: assessment.clone(),
+        });
+        Ok(assessment)
+    }
+
+    /// Assessments in recorded order, optionally for one vision.
+    pub fn realities(&self, vision_id: Option<Uuid>) -> Vec<&RealityAssessment> {
+        self.db
+            .realities
+            .iter()
+            .filter(|r| vision_id.is_none_or(|id| r.vision_id == id))
+            .collect()
+    }
+
+    /// The most recent assessment, optionally for one vision.
+    pub fn latest_reality(&self, vision_id: Option<Uuid>) -> Option<&RealityAssessment> {
+        self.realities(vision_id)
+            .into_iter()
+            .min_by_key(|r| std::cmp::Reverse(r.timestamp))
+    }
+}
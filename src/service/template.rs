@@ -0,0 +1,65 @@
+use super::Session;
+use crate::{
+    error::{TensileError, TensileResult},
+    models::{Vision, VisionTemplate},
+};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+impl Session {
+    /// The subtree rooted at `id` as a template.
+    pub fn capture_template(&self, id: Uuid) -> TensileResult<VisionTemplate> {
+        self.template_of(self.vision(id)?, &mut HashSet::new())
+    }
+
+    fn template_of(
+        &self,
+        vision: &Vision,
+        seen: &mut HashSet<Uuid>,
+    ) -> TensileResult<VisionTemplate> {
+        if !seen.insert(vision.id) {
+            return Err(TensileError::Validation(format!(
+                "Vision hierarchy has a cycle at: {}",
+                vision.title
+            )));
+        }
+        Ok(VisionTemplate {
+            title: vision.title.clone(),
+            description: vision.description.clone(),
+            children: self
+                .children(Some(vision.id))
+                .into_iter()
+                .map(|child| self.template_of(child, seen))
+                .collect::<TensileResult<_>>()?,
+        })
+    }
+
+    /// Create the template's visions with fresh ids, placing its root last
+    /// among the children of `parent`. Returns them root first, in tree
+    /// order.
+    pub fn apply_template(
+        &mut self,
+        template: &VisionTemplate,
+        parent: Option<Uuid>,
+    ) -> TensileResult<Vec<Vision>> {
+        let mut created = Vec::with_capacity(template.size());
+        self.add_template(template, parent, &mut created)?;
+        Ok(created)
+    }
+
+    fn add_template(
+        &mut self,
+        template: &VisionTemplate,
+        parent: Option<Uuid>,
+        created: &mut Vec<Vision>,
+    ) -> TensileResult<()> {
+        let vision =
+            self.add_vision(template.title.clone(), template.description.clone(), parent)?;
+        let id = vision.id;
+        created.push(vision);
+        for child in &template.children {
+            self.add_template(child, Some(id), created)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+use super::Session;
+use crate::{
+    engine::{
+        pattern::Pattern, rollup::Rollup, PatternAnalyzer, RollupCalculator, TensionCalculator,
+    },
+    error::TensileResult,
+    models::{Vision, VisionState},
+};
+use serde::Serialize;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Which part of the hierarchy `Session::tree` returns.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TreeOptions {
+    /// Start from this vision instead of every top-level vision
+    pub root: Option<Uuid>,
+    /// Number of levels to include; `None` for all
+    pub depth: Option<usize>,
+    /// Leave out achieved visions; their sub-visions take their place
+    pub hide_achieved: bool,
+}
+
+/// A vision with its annotations and sub-visions.
+#[derive(Serialize, Debug, Clone)]
+pub struct TreeNode {
+    pub id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub state: VisionState,
+    /// Tension score; absent once the vision is achieved
+    pub tension: Option<f32>,
+    pub pattern: Pattern,
+    pub action_count: usize,
+    pub stakes: u8,
+    /// Visions it depends on that are not achieved yet
+    pub open_blockers: usize,
+    /// Progress and tension of its sub-visions, including any not shown;
+    /// absent without any
+    pub rollup: Option<Rollup>,
+    pub children: Vec<TreeNode>,
+}
+
+impl TreeNode {
+    /// This node and its descendants depth-first, with their depth.
+    pub fn walk(&self) -> Vec<(usize, &TreeNode)> {
+        let mut nodes = Vec::new();
+        let mut stack = vec![(0, self)];
+        while let Some((depth, node)) = stack.pop() {
+            nodes.push((depth, node));
+            stack.extend(node.children.iter().rev().map(|c| (depth + 1, c)));
+        }
+        nodes
+    }
+}
+
+impl Session {
+    /// The vision hierarchy in sibling order.
+    pub fn tree(&self, options: &TreeOptions) -> TensileResult<Vec<TreeNode>> {
+        let top: Vec<&Vision> = match options.root {
+            Some(root) => vec![self.vision(root)?],
+            None => self.children(None),
+        };
+
+        let mut seen = HashSet::new();
+        Ok(self.nodes(top, options, 1, &mut seen))
+    }
+
+    fn nodes(
+        &self,
+        visions: Vec<&Vision>,
+        options: &TreeOptions,
+        depth: usize,
+        seen: &mut HashSet<Uuid>,
+    ) -> Vec<TreeNode> {
+        let mut nodes = Vec::new();
+
+        for vision in visions {
+            // Guards against cycles made outside the CLI
+            if !seen.insert(vision.id) {
+                continue;
+            }
+
+            let children = self.children(Some(vision.id));
+            if options.hide_achieved && vision.state == VisionState::Achieved {
+                nodes.extend(self.nodes(children, options, depth, seen));
+                continue;
+            }
+
+            let tension = TensionCalculator::calculate_vision_tension(&self.db, vision.id);
+            nodes.push(TreeNode {
+                id: vision.id,
+                title: vision.title.clone(),
+                description: vision.description.clone(),
+                state: vision.state.clone(),
+                tension: tension
+                    .as_ref()
+                    .filter(|_| vision.state != VisionState::Achieved)
+                    .map(|t| t.tension_score),
+                pattern: PatternAnalyzer::analyze_vision_pattern(
+                    &self.db,
+                    vision.id,
+                    &self.config.patterns,
+                ),
+                action_count: tension.as_ref().map_or(0, |t| t.action_count),
+                open_blockers: tension.map_or(0, |t| t.open_blockers),
+                stakes: vision.stakes,
+                rollup: RollupCalculator::calculate(&self.db, vision.id),
+                children: if options.depth.is_some_and(|max| depth >= max) {
+                    vec![]
+                } else {
+                    self.nodes(children, options, depth + 1, seen)
+                },
+            });
+        }
+
+        nodes
+    }
+}
@@ -0,0 +1,559 @@
+use super::Session;
+use crate::{
+    engine::{DependencyAnalyzer, StateMachine},
+    error::{TensileError, TensileResult},
+    events::Event,
+    models::{Blocker, RecordKind, User, Vision, VisionState},
+};
+use chrono::{NaiveDate, Utc};
+use serde::Serialize;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// A vision's move from one state to another.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Transition {
+    pub vision_id: Uuid,
+    pub from: VisionState,
+    pub to: VisionState,
+}
+
+/// A blocked vision, with what blocks it.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct BlockedVision {
+    pub vision_id: Uuid,
+    pub title: String,
+    pub blocker: Option<Blocker>,
+    /// Unachieved visions it depends on
+    pub waiting_on: Vec<Uuid>,
+    /// Whether it is time to look at it again: its unblock date has come,
+    /// or all it waited on is achieved and nothing else was recorded
+    pub due: bool,
+}
+
+/// What deleting a vision removed.
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Deletion {
+    pub title: String,
+    /// The deleted vision, followed by its descendants if deleted with them
+    pub visions: Vec<Uuid>,
+    pub realities: usize,
+    pub actions: usize,
+    /// Children moved up to the deleted vision's parent
+    pub reparented: Vec<Uuid>,
+}
+
+impl Session {
+    pub fn visions(&self) -> &[Vision] {
+        &self.db.visions
+    }
+
+    /// Sub-visions of `parent` (top-level visions for `None`) in sibling
+    /// order. A vision whose parent no longer exists counts as top-level.
+    pub fn children(&self, parent: Option<Uuid>) -> Vec<&Vision> {
+        let mut children: Vec<&Vision> = self
+            .db
+            .visions
+            .iter()
+            .filter(|v| self.parent_of(v) == parent)
+            .collect();
+        children.sort_by_key(|v| (v.position, v.created_at));
+        children
+    }
+
+    /// Every vision, depth-first in sibling order.
+    pub fn tree_order(&self) -> Vec<&Vision> {
+        let mut ordered = Vec::new();
+        let mut stack: Vec<&Vision> = self.children(None).into_iter().rev().collect();
+        let mut seen = HashSet::new();
+
+        while let Some(vision) = stack.pop() {
+            if !seen.insert(vision.id) {
+                continue;
+            }
+            ordered.push(vision);
+            stack.extend(self.children(Some(vision.id)).into_iter().rev());
+        }
+
+        // Visions caught in a cycle are unreachable from the top level
+        ordered.extend(self.db.visions.iter().filter(|v| !seen.contains(&v.id)));
+        ordered
+    }
+
+    pub fn create_vision(&mut self, title: impl Into<String>) -> TensileResult<Vision> {
+        self.add_vision(title.into(), None, None)
+    }
+
+    /// Add a vision last among the children of `parent`.
+    pub(super) fn add_vision(
+        &mut self,
+        title: String,
+        description: Option<String>,
+        parent: Option<Uuid>,
+    ) -> TensileResult<Vision> {
+        let title = validate_title(title)?;
+        if let Some(parent) = parent {
+            self.vision(parent)?;
+        }
+
+        let mut vision = Vision::new(title);
+        vision.description = description;
+        vision.parent = parent;
+        vision.position = self.next_position(parent);
+        self.db.visions.push(vision.clone());
+        if parent.is_some() {
+            self.db.rebuild_children();
+        }
+        self.changed();
+        self.record(Event::VisionCreated {
+            vision: vision.clone(),
+        });
+        Ok(vision)
+    }
+
+    pub fn rename_vision(&mut self, id: Uuid, title: impl Into<String>) -> TensileResult<Vision> {
+        let title = validate_title(title.into())?;
+
+        let vision = self.vision_mut(id)?;
+        vision.title = title;
+        vision.touch();
+        let vision = vision.clone();
+        Ok(self.updated(vision))
+    }
+
+    /// Make `id` depend on `on`, which then blocks it until achieved.
+    /// Depending on a vision twice changes nothing.
+    pub fn add_dependency(&mut self, id: Uuid, on: Uuid) -> TensileResult<Vision> {
+        let vision = self.vision(id)?;
+        let blocker = self.vision(on)?;
+        if id == on {
+            return Err(TensileError::Validation(format!(
+                "'{}' cannot depend on itself",
+                vision.title
+            )));
+        }
+        if DependencyAnalyzer::depends_on(&self.db, on, id) {
+            return Err(TensileError::Validation(format!(
+                "Making '{}' depend on '{}' would create a cycle",
+                vision.title, blocker.title
+            )));
+        }
+        if vision.depends_on.contains(&on) {
+            return Ok(vision.clone());
+        }
+
+        let vision = self.vision_mut(id)?;
+        vision.depends_on.push(on);
+        vision.touch();
+        let vision = vision.clone();
+        Ok(self.updated(vision))
+    }
+
+    pub fn remove_dependency(&mut self, id: Uuid, on: Uuid) -> TensileResult<Vision> {
+        let vision = self.vision(id)?;
+        if !vision.depends_on.contains(&on) {
+            return Err(TensileError::NotFound(format!(
+                "'{}' does not depend on '{}'",
+                vision.title,
+                self.vision(on)?.title
+            )));
+        }
+
+        let vision = self.vision_mut(id)?;
+        vision.depends_on.retain(|d| *d != on);
+        vision.touch();
+        let vision = vision.clone();
+        Ok(self.updated(vision))
+    }
+
+    /// The visions `id` depends on, achieved or not, in the order added.
+    pub fn dependencies(&self, id: Uuid) -> TensileResult<Vec<&Vision>> {
+        Ok(self
+            .vision(id)?
+            .depends_on
+            .iter()
+            .filter_map(|d| self.vision(*d).ok())
+            .collect())
+    }
+
+    /// The visions that depend on `id`.
+    pub fn dependents(&self, id: Uuid) -> Vec<&Vision> {
+        DependencyAnalyzer::dependents(&self.db, id)
+    }
+
+    /// Set what is at stake, from 1 to `Vision::MAX_STAKES`.
+    pub fn set_stakes(&mut self, id: Uuid, stakes: u8) -> TensileResult<Vision> {
+        if !(1..=Vision::MAX_STAKES).contains(&stakes) {
+            return Err(TensileError::Validation(format!(
+                "Stakes must be between 1 and {}, not {}",
+                Vision::MAX_STAKES,
+                stakes
+            )));
+        }
+
+        let vision = self.vision_mut(id)?;
+        vision.stakes = stakes;
+        vision.touch();
+        let vision = vision.clone();
+        Ok(self.updated(vision))
+    }
+
+    pub fn describe_vision(&mut self, id: Uuid, text: impl Into<String>) -> TensileResult<Vision> {
+        let vision = self.vision_mut(id)?;
+        vision.description = Some(text.into());
+        vision.touch();
+        let vision = vision.clone();
+        Ok(self.updated(vision))
+    }
+
+    /// Add a paragraph to the end of the description.
+    pub fn append_description(
+        &mut self,
+        id: Uuid,
+        text: impl Into<String>,
+    ) -> TensileResult<Vision> {
+        let text = text.into();
+        let vision = self.vision_mut(id)?;
+        vision.description = Some(match vision.description.take() {
+            Some(existing) if !existing.is_empty() => format!("{}\n{}", existing, text),
+            _ => text,
+        });
+        vision.touch();
+        let vision = vision.clone();
+        Ok(self.updated(vision))
+    }
+
+    /// Make `child` a sub-vision of `parent`. Same as `move_vision`.
+    pub fn link_visions(&mut self, parent: Uuid, child: Uuid) -> TensileResult<Vision> {
+        self.move_vision(child, Some(parent))
+    }
+
+    /// Detach a vision from its parent, making it top-level.
+    pub fn unlink_vision(&mut self, id: Uuid) -> TensileResult<Vision> {
+        self.move_vision(id, None)
+    }
+
+    /// Give a vision a new parent (or none), placing it last among its new
+    /// siblings. Fails if `parent` is the vision itself or one of its
+    /// descendants.
+    pub fn move_vision(&mut self, id: Uuid, parent: Option<Uuid>) -> TensileResult<Vision> {
+        self.vision(id)?;
+        if let Some(parent) = parent {
+            self.check_parent(id, parent)?;
+        }
+        if self.vision(id)?.parent == parent {
+            return Ok(self.vision(id)?.clone());
+        }
+
+        let position = self.next_position(parent);
+        let moved = {
+            let vision = self.vision_mut(id)?;
+            vision.parent = parent;
+            vision.position = position;
+            vision.touch();
+            vision.clone()
+        };
+
+        self.db.rebuild_children();
+        Ok(self.updated(moved))
+    }
+
+    /// Move a vision to `position` (0-based) among its siblings, shifting
+    /// the others. Positions past the end place it last.
+    pub fn reorder_vision(&mut self, id: Uuid, position: usize) -> TensileResult<Vision> {
+        let parent = self.parent_of(self.vision(id)?);
+        let mut order: Vec<Uuid> = self
+            .children(parent)
+            .iter()
+            .map(|v| v.id)
+            .filter(|v| *v != id)
+            .collect();
+        order.insert(position.min(order.len()), id);
+
+        for (index, sibling) in order.into_iter().enumerate() {
+            let index = index as u32;
+            if self.vision(sibling)?.position != index {
+                let vision = self.vision_mut(sibling)?;
+                vision.position = index;
+                vision.touch();
+            }
+        }
+
+        let vision = self.vision(id)?.clone();
+        Ok(self.updated(vision))
+    }
+
+    /// Delete a vision with its realities and actions. Its children move up
+    /// to its parent, or are deleted too when `recursive` is set.
+    pub fn delete_vision(&mut self, id: Uuid, recursive: bool) -> TensileResult<Deletion> {
+        let vision = self.vision(id)?;
+        let parent = self.parent_of(vision);
+
+        let mut deletion = Deletion {
+            title: vision.title.clone(),
+            ..Default::default()
+        };
+        if recursive {
+            deletion.visions = self.descendants(id);
+        } else {
+            for child in self
+                .children(Some(id))
+                .iter()
+                .map(|v| v.id)
+                .collect::<Vec<_>>()
+            {
+                self.move_vision(child, parent)?;
+                deletion.reparented.push(child);
+            }
+        }
+        deletion.visions.insert(0, id);
+
+        for vision in &deletion.visions {
+            deletion.realities += self
+                .db
+                .realities
+                .iter()
+                .filter(|r| r.vision_id == *vision)
+                .count();
+            deletion.actions += self
+                .db
+                .actions
+                .iter()
+                .filter(|a| a.vision_id == *vision)
+                .count();
+        }
+
+        // Deepest first, so no vision is detached from a parent about to go
+        for vision in deletion.visions.iter().rev() {
+            self.db.remove_record(RecordKind::Vision, *vision);
+        }
+        self.changed();
+        self.record(Event::VisionDeleted {
+            deletion: deletion.clone(),
+        });
+        Ok(deletion)
+    }
+
+    /// Mark a vision achieved, if the state machine allows it. The closing
+    /// reality is recorded as its last assessment; the retrospective, what
+    /// made it work, is kept with the vision. Completing an achieved vision
+    /// again only updates its retrospective.
+    pub fn complete_vision(
+        &mut self,
+        id: Uuid,
+        closing_reality: Option<String>,
+        retrospective: Option<String>,
+    ) -> TensileResult<Vision> {
+        let from = self.vision(id)?.state.clone();
+        StateMachine::validate_transition(&from, &VisionState::Achieved)?;
+
+        if let Some(entry) = closing_reality {
+            self.record_reality(id, entry)?;
+        }
+        if retrospective.is_some() {
+            self.vision_mut(id)?.retrospective = retrospective;
+        }
+        let vision = self.enter_state(id, VisionState::Achieved)?;
+
+        if from == VisionState::Achieved {
+            return Ok(self.updated(vision));
+        }
+        self.record_transition(&vision, from);
+        Ok(vision)
+    }
+
+    /// Move a vision to a new state, if the state machine allows it.
+    pub fn transition(&mut self, id: Uuid, to: VisionState) -> TensileResult<Transition> {
+        let from = self.vision(id)?.state.clone();
+        StateMachine::validate_transition(&from, &to)?;
+
+        let vision = self.enter_state(id, to.clone())?;
+        self.record_transition(&vision, from.clone());
+        Ok(Transition {
+            vision_id: id,
+            from,
+            to,
+        })
+    }
+
+    /// Move a vision to Blocked, recording what blocks it. Blocking a
+    /// vision that is already blocked replaces its blocker.
+    pub fn block_vision(
+        &mut self,
+        id: Uuid,
+        blocker: Option<Blocker>,
+    ) -> TensileResult<Transition> {
+        let from = self.vision(id)?.state.clone();
+        let to = VisionState::Blocked;
+        StateMachine::validate_transition(&from, &to)?;
+
+        self.enter_state(id, to.clone())?;
+        let vision = self.vision_mut(id)?;
+        vision.blocker = blocker;
+        let vision = vision.clone();
+        if from == to {
+            self.updated(vision);
+        } else {
+            self.record_transition(&vision, from.clone());
+        }
+        Ok(Transition {
+            vision_id: id,
+            from,
+            to,
+        })
+    }
+
+    /// Blocked visions in tree order, with what blocks them as of `today`.
+    pub fn blocked(&self, today: NaiveDate) -> Vec<BlockedVision> {
+        self.tree_order()
+            .into_iter()
+            .filter(|v| v.state == VisionState::Blocked)
+            .map(|vision| {
+                let waiting_on: Vec<Uuid> = DependencyAnalyzer::open_blockers(&self.db, vision.id)
+                    .iter()
+                    .map(|v| v.id)
+                    .collect();
+                let due = match &vision.blocker {
+                    Some(blocker) => blocker.is_due(today),
+                    None => waiting_on.is_empty() && !vision.depends_on.is_empty(),
+                };
+                BlockedVision {
+                    vision_id: vision.id,
+                    title: vision.title.clone(),
+                    blocker: vision.blocker.clone(),
+                    waiting_on,
+                    due,
+                }
+            })
+            .collect()
+    }
+
+    /// Set a vision's state, noting when it becomes achieved and dropping its
+    /// blocker once it is no longer blocked. Callers check the transition
+    /// first.
+    fn enter_state(&mut self, id: Uuid, to: VisionState) -> TensileResult<Vision> {
+        let vision = self.vision_mut(id)?;
+        if to == VisionState::Achieved && !vision.is_achieved() {
+            vision.achieved_at = Some(Utc::now());
+        }
+        if to != VisionState::Blocked {
+            vision.blocker = None;
+        }
+        vision.state = to;
+        vision.touch();
+        Ok(vision.clone())
+    }
+
+    /// Record that `vision` changed, and hand it back.
+    fn updated(&mut self, vision: Vision) -> Vision {
+        self.record(Event::VisionUpdated {
+            vision: vision.clone(),
+        });
+        vision
+    }
+
+    fn record_transition(&mut self, vision: &Vision, from: VisionState) {
+        if from == vision.state {
+            return;
+        }
+        self.record(Event::StateChanged {
+            vision_id: vision.id,
+            vision_title: vision.title.clone(),
+            from,
+            to: vision.state.clone(),
+        });
+        if vision.state == VisionState::Achieved {
+            self.record(Event::VisionAchieved {
+                vision_id: vision.id,
+                vision_title: vision.title.clone(),
+                retrospective: vision.retrospective.clone(),
+            });
+        }
+    }
+
+    /// The vision `.` refers to, if it still exists.
+    pub fn current_focus(&self) -> Option<&Vision> {
+        let focus = self.db.user.as_ref()?.current_focus?;
+        self.vision(focus).ok()
+    }
+
+    pub fn focus(&mut self, id: Uuid) -> TensileResult<Vision> {
+        let vision = self.vision(id)?.clone();
+        self.db.user.get_or_insert_with(User::new).current_focus = Some(id);
+        self.changed();
+        self.record(Event::FocusChanged {
+            vision_id: Some(id),
+        });
+        Ok(vision)
+    }
+
+    pub fn clear_focus(&mut self) {
+        if let Some(user) = self.db.user.as_mut() {
+            user.current_focus = None;
+            self.changed();
+            self.record(Event::FocusChanged { vision_id: None });
+        }
+    }
+
+    /// The vision's parent, if it exists.
+    fn parent_of(&self, vision: &Vision) -> Option<Uuid> {
+        vision
+            .parent
+            .filter(|parent| self.db.visions.iter().any(|v| v.id == *parent))
+    }
+
+    /// Position after the last current child of `parent`.
+    fn next_position(&self, parent: Option<Uuid>) -> u32 {
+        self.children(parent)
+            .last()
+            .map_or(0, |last| last.position + 1)
+    }
+
+    /// All descendants of a vision, parents before their children.
+    fn descendants(&self, id: Uuid) -> Vec<Uuid> {
+        let mut found = Vec::new();
+        let mut queue = vec![id];
+        while let Some(next) = queue.pop() {
+            for child in self.children(Some(next)) {
+                if child.id != id && !found.contains(&child.id) {
+                    found.push(child.id);
+                    queue.push(child.id);
+                }
+            }
+        }
+        found
+    }
+
+    /// `parent` may not be `child` itself or any of its descendants.
+    fn check_parent(&self, child: Uuid, parent: Uuid) -> TensileResult<()> {
+        self.vision(parent)?;
+
+        let mut seen = HashSet::new();
+        let mut current = Some(parent);
+        while let Some(id) = current {
+            if id == child {
+                return Err(TensileError::Validation(format!(
+                    "Making '{}' a sub-vision of '{}' would create a cycle",
+                    self.vision(child)?.title,
+                    self.vision(parent)?.title
+                )));
+            }
+            if !seen.insert(id) {
+                break;
+            }
+            current = self.vision(id).ok().and_then(|v| self.parent_of(v));
+        }
+
+        Ok(())
+    }
+}
+
+fn validate_title(title: String) -> TensileResult<String> {
+    let title = title.trim().to_string();
+    if title.is_empty() {
+        return Err(TensileError::Validation(
+            "A vision needs a title".to_string(),
+        ));
+    }
+    Ok(title)
+}
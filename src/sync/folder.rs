@@ -0,0 +1,275 @@
+use super::{merge::SyncRecord, reconcile, MergeOutcome, Record, SyncStore};
+use crate::{
+    config,
+    error::{TensileError, TensileResult},
+    models::{Database, Tombstone},
+    persistence,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+const LOG_PREFIX: &str = "tensile-";
+const LOG_SUFFIX: &str = ".jsonl";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Change {
+    Upsert(Box<Record>),
+    Delete(Tombstone),
+}
+
+impl Change {
+    fn id(&self) -> Uuid {
+        match self {
+            Change::Upsert(record) => record.id(),
+            Change::Delete(tombstone) => tombstone.id,
+        }
+    }
+}
+
+/// One line of a device's change log.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    pub seq: u64,
+    pub at: DateTime<Utc>,
+    pub change: Change,
+}
+
+#[derive(Debug, Clone)]
+pub struct PeerMerge {
+    pub device: Uuid,
+    pub outcome: MergeOutcome,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FolderSyncReport {
+    pub peers: Vec<PeerMerge>,
+    /// Changes appended to this device's log, or `None` if export was
+    /// skipped because conflicts are pending.
+    pub exported: Option<usize>,
+}
+
+/// Sync through a shared directory (Syncthing, Dropbox, ...).
+///
+/// Each device only ever writes its own `tensile-<device>.jsonl` change log,
+/// replacing it atomically, and reads everyone else's. The local database
+/// never lives in the shared folder, so concurrent writers cannot corrupt it.
+pub struct FolderSync {
+    dir: PathBuf,
+    device: Uuid,
+}
+
+impl FolderSync {
+    pub fn new(dir: PathBuf, device: Uuid) -> TensileResult<Self> {
+        if !dir.is_dir() {
+            return Err(TensileError::NotFound(format!(
+                "Sync folder does not exist: {}",
+                dir.display()
+            )));
+        }
+
+        let data_dir = config::data_dir();
+        if let (Ok(dir), Ok(data)) = (dir.canonicalize(), data_dir.canonicalize()) {
+            if dir.starts_with(&data) || data.starts_with(&dir) {
+                return Err(TensileError::Validation(format!(
+                    "Sync folder must not contain or be inside {}",
+                    data.display()
+                )));
+            }
+        }
+
+        Ok(FolderSync { dir, device })
+    }
+
+    /// Use `TENSILE_SYNC_DIR` as the shared folder.
+    pub fn from_env() -> TensileResult<Self> {
+        let dir = std::env::var("TENSILE_SYNC_DIR").map_err(|_| {
+            TensileError::Validation("TENSILE_SYNC_DIR not set (or pass --dir)".to_string())
+        })?;
+        FolderSync::new(PathBuf::from(dir), config::device_id()?)
+    }
+
+    /// Merge every other device's log into the local database, then append
+    /// local changes to this device's log.
+    pub fn run(&self, store: &SyncStore) -> TensileResult<FolderSyncReport> {
+        let _lock = store.lock()?;
+
+        let mut report = FolderSyncReport::default();
+
+        for (device, path) in self.peer_logs()? {
+            let peer_state = replay(&read_log(&path)?);
+            let outcome = reconcile(store, &channel(device), &peer_state)?;
+            report.peers.push(PeerMerge { device, outcome });
+        }
+
+        if store.load_conflicts()?.is_empty() {
+            report.exported = Some(self.export()?);
+        }
+
+        Ok(report)
+    }
+
+    pub fn log_path(&self, device: Uuid) -> PathBuf {
+        self.dir
+            .join(format!("{}{}{}", LOG_PREFIX, device, LOG_SUFFIX))
+    }
+
+    fn peer_logs(&self) -> TensileResult<Vec<(Uuid, PathBuf)>> {
+        let mut peers = Vec::new();
+
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let name = match path.file_name().and_then(|n| n.to_str()) {
+                Some(name) => name,
+                None => continue,
+            };
+
+            // Sync-tool conflict copies and temp files don't match this pattern
+            let device = name
+                .strip_prefix(LOG_PREFIX)
+                .and_then(|rest| rest.strip_suffix(LOG_SUFFIX))
+                .and_then(|id| Uuid::parse_str(id).ok());
+
+            if let Some(device) = device {
+                if device != self.device {
+                    peers.push((device, path));
+                }
+            }
+        }
+
+        peers.sort();
+        Ok(peers)
+    }
+
+    /// Append records that differ from what this device last published.
+    fn export(&self) -> TensileResult<usize> {
+        let path = self.log_path(self.device);
+        let mut log = read_log(&path)?;
+        let published = replay(&log);
+        let local = persistence::load_database()?;
+
+        let changes = diff(&published, &local);
+        if changes.is_empty() {
+            return Ok(0);
+        }
+
+        let now = Utc::now();
+        let next_seq = log.last().map(|e| e.seq).unwrap_or(0) + 1;
+        let count = changes.len();
+        for (seq, change) in (next_seq..).zip(changes) {
+            log.push(LogEntry {
+                seq,
+                at: now,
+                change,
+            });
+        }
+
+        write_log(&path, &compact(log))?;
+        Ok(count)
+    }
+}
+
+fn channel(device: Uuid) -> String {
+    format!("folder-{}", device)
+}
+
+pub fn read_log(path: &Path) -> TensileResult<Vec<LogEntry>> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+
+    let contents = fs::read_to_string(path)?;
+    let mut entries = Vec::new();
+    for line in contents.lines().filter(|l| !l.trim().is_empty()) {
+        // A torn tail means the file is still arriving; use what is complete
+        match serde_json::from_str::<LogEntry>(line) {
+            Ok(entry) => entries.push(entry),
+            Err(_) => break,
+        }
+    }
+
+    Ok(entries)
+}
+
+fn write_log(path: &Path, entries: &[LogEntry]) -> TensileResult<()> {
+    let mut contents = String::new();
+    for entry in entries {
+        let line =
+            serde_json::to_string(entry).map_err(|e| TensileError::Serialization(e.to_string()))?;
+        contents.push_str(&line);
+        contents.push('\n');
+    }
+
+    // Dot-prefixed temp file in the same folder, then an atomic rename
+    let tmp = path.with_file_name(format!(".{}.tmp", Uuid::new_v4()));
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Rebuild a device's published state by applying its log in order.
+pub fn replay(entries: &[LogEntry]) -> Database {
+    let mut db = Database::default();
+
+    for entry in entries {
+        match &entry.change {
+            Change::Upsert(record) => (**record).clone().apply_to(&mut db),
+            Change::Delete(tombstone) => {
+                db.remove_record(tombstone.kind, tombstone.id);
+                if let Some(t) = db.tombstones.iter_mut().find(|t| t.id == tombstone.id) {
+                    *t = tombstone.clone();
+                }
+            }
+        }
+    }
+
+    db.rebuild_children();
+    db
+}
+
+fn diff(published: &Database, local: &Database) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_collection(&published.visions, &local.visions, &mut changes);
+    diff_collection(&published.realities, &local.realities, &mut changes);
+    diff_collection(&published.actions, &local.actions, &mut changes);
+
+    for tombstone in &local.tombstones {
+        if !published.tombstones.iter().any(|t| t.id == tombstone.id) {
+            changes.push(Change::Delete(tombstone.clone()));
+        }
+    }
+
+    changes
+}
+
+fn diff_collection<T: SyncRecord>(published: &[T], local: &[T], changes: &mut Vec<Change>) {
+    let published: HashMap<Uuid, &T> = published.iter().map(|r| (r.id(), r)).collect();
+
+    for record in local {
+        let unchanged = published
+            .get(&record.id())
+            .is_some_and(|p| p.same_content(record));
+        if !unchanged {
+            changes.push(Change::Upsert(Box::new(record.clone().into_record())));
+        }
+    }
+}
+
+/// Drop superseded entries once they make up most of the log.
+fn compact(log: Vec<LogEntry>) -> Vec<LogEntry> {
+    let mut latest: HashMap<Uuid, u64> = HashMap::new();
+    for entry in &log {
+        latest.insert(entry.change.id(), entry.seq);
+    }
+
+    if log.len() <= latest.len() * 2 {
+        return log;
+    }
+
+    log.into_iter()
+        .filter(|e| latest.get(&e.change.id()) == Some(&e.seq))
+        .collect()
+}
@@ -0,0 +1,52 @@
+use crate::error::{TensileError, TensileResult};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// A lock older than this is assumed to belong to a crashed process.
+const STALE_LOCK_SECS: u64 = 600;
+
+/// Prevents two local processes from syncing at the same time. Released
+/// when dropped.
+pub struct SyncLock {
+    path: PathBuf,
+}
+
+impl SyncLock {
+    pub fn acquire(path: &Path) -> TensileResult<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let stale = fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.elapsed().ok())
+            .is_some_and(|age| age.as_secs() > STALE_LOCK_SECS);
+        if stale {
+            let _ = fs::remove_file(path);
+        }
+
+        match OpenOptions::new().write(true).create_new(true).open(path) {
+            Ok(mut file) => {
+                let _ = writeln!(file, "{}", std::process::id());
+                Ok(SyncLock {
+                    path: path.to_path_buf(),
+                })
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                Err(TensileError::StateConflict(format!(
+                    "Another sync is running (lock: {})",
+                    path.display()
+                )))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl Drop for SyncLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
@@ -0,0 +1,494 @@
+use crate::models::{ActionLog, Database, RealityAssessment, RecordKind, Tombstone, Vision};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// A single synchronized record of any kind.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Record {
+    Vision(Vision),
+    Reality(RealityAssessment),
+    Action(ActionLog),
+}
+
+impl Record {
+    pub fn kind(&self) -> RecordKind {
+        match self {
+            Record::Vision(_) => RecordKind::Vision,
+            Record::Reality(_) => RecordKind::Reality,
+            Record::Action(_) => RecordKind::Action,
+        }
+    }
+
+    pub fn id(&self) -> Uuid {
+        match self {
+            Record::Vision(v) => v.id,
+            Record::Reality(r) => r.id,
+            Record::Action(a) => a.id,
+        }
+    }
+
+    pub fn updated_at(&self) -> DateTime<Utc> {
+        match self {
+            Record::Vision(v) => v.updated_at,
+            Record::Reality(r) => r.updated_at,
+            Record::Action(a) => a.updated_at,
+        }
+    }
+
+    /// One-line description used when listing conflicts.
+    pub fn summary(&self) -> String {
+        match self {
+            Record::Vision(v) => format!("{} [{}]", v.title, v.state),
+            Record::Reality(r) => r.entry.clone(),
+            Record::Action(a) => a.entry.clone(),
+        }
+    }
+
+    /// Insert or replace this record in `db`, clearing any tombstone for it.
+    pub fn apply_to(self, db: &mut Database) {
+        let id = self.id();
+        db.tombstones.retain(|t| t.id != id);
+
+        match self {
+            Record::Vision(v) => upsert(&mut db.visions, v),
+            Record::Reality(r) => upsert(&mut db.realities, r),
+            Record::Action(a) => upsert(&mut db.actions, a),
+        }
+    }
+}
+
+/// The same record was changed differently on both sides since the last sync.
+/// A missing side means the record was deleted there.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub kind: RecordKind,
+    pub id: Uuid,
+    pub local: Option<Record>,
+    pub remote: Option<Record>,
+    pub detected_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MergeOutcome {
+    pub merged: Database,
+    pub conflicts: Vec<Conflict>,
+}
+
+/// Records that take part in three-way merging.
+pub trait SyncRecord: Clone + PartialEq {
+    const KIND: RecordKind;
+
+    fn id(&self) -> Uuid;
+
+    fn updated_at(&self) -> DateTime<Utc>;
+
+    /// Copy with bookkeeping fields cleared, so only user-visible content is compared.
+    fn content(&self) -> Self;
+
+    fn into_record(self) -> Record;
+
+    fn same_content(&self, other: &Self) -> bool {
+        self.content() == other.content()
+    }
+}
+
+impl SyncRecord for Vision {
+    const KIND: RecordKind = RecordKind::Vision;
+
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn updated_at(&self) -> DateTime<Utc> {
+        self.updated_at
+    }
+
+    fn content(&self) -> Self {
+        Vision {
+            children: vec![],
+            updated_at: DateTime::<Utc>::default(),
+            updated_by: None,
+            ..self.clone()
+        }
+    }
+
+    fn into_record(self) -> Record {
+        Record::Vision(self)
+    }
+}
+
+impl SyncRecord for RealityAssessment {
+    const KIND: RecordKind = RecordKind::Reality;
+
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn updated_at(&self) -> DateTime<Utc> {
+        self.updated_at
+    }
+
+    fn content(&self) -> Self {
+        RealityAssessment {
+            updated_at: DateTime::<Utc>::default(),
+            updated_by: None,
+            ..self.clone()
+        }
+    }
+
+    fn into_record(self) -> Record {
+        Record::Reality(self)
+    }
+}
+
+impl SyncRecord for ActionLog {
+    const KIND: RecordKind = RecordKind::Action;
+
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn updated_at(&self) -> DateTime<Utc> {
+        self.updated_at
+    }
+
+    fn content(&self) -> Self {
+        ActionLog {
+            updated_at: DateTime::<Utc>::default(),
+            updated_by: None,
+            ..self.clone()
+        }
+    }
+
+    fn into_record(self) -> Record {
+        Record::Action(self)
+    }
+}
+
+/// Merge `local` and `remote`, both descended from `base`.
+///
+/// Changes made on only one side win. Records changed differently on both
+/// sides (including edit-vs-delete) keep the local version and are reported
+/// as conflicts. A record missing from `base` that one side has a tombstone
+/// for stays deleted unless the other side changed it after the deletion.
+pub fn three_way_merge(base: &Database, local: &Database, remote: &Database) -> MergeOutcome {
+    let mut conflicts = Vec::new();
+    let deletions = Deletions {
+        local: deleted_at(local),
+        remote: deleted_at(remote),
+    };
+
+    let visions = merge_collection(
+        &base.visions,
+        &local.visions,
+        &remote.visions,
+        &deletions,
+        &mut conflicts,
+    );
+    let realities = merge_collection(
+        &base.realities,
+        &local.realities,
+        &remote.realities,
+        &deletions,
+        &mut conflicts,
+    );
+    let actions = merge_collection(
+        &base.actions,
+        &local.actions,
+        &remote.actions,
+        &deletions,
+        &mut conflicts,
+    );
+
+    let mut merged = Database {
+        visions,
+        realities,
+        actions,
+        user: local.user.clone(),
+        tombstones: vec![],
+    };
+
+    restore_referenced_visions(local, remote, &mut merged, &mut conflicts);
+
+    let live: HashSet<Uuid> = merged
+        .visions
+        .iter()
+        .map(|v| v.id)
+        .chain(merged.realities.iter().map(|r| r.id))
+        .chain(merged.actions.iter().map(|a| a.id))
+        .collect();
+
+    let mut seen = HashSet::new();
+    merged.tombstones = local
+        .tombstones
+        .iter()
+        .chain(remote.tombstones.iter())
+        .filter(|t| !live.contains(&t.id) && seen.insert(t.id))
+        .cloned()
+        .collect::<Vec<Tombstone>>();
+
+    merged.rebuild_children();
+
+    MergeOutcome { merged, conflicts }
+}
+
+/// When each side deleted a record, by id.
+struct Deletions {
+    local: HashMap<Uuid, DateTime<Utc>>,
+    remote: HashMap<Uuid, DateTime<Utc>>,
+}
+
+fn deleted_at(db: &Database) -> HashMap<Uuid, DateTime<Utc>> {
+    db.tombstones.iter().map(|t| (t.id, t.deleted_at)).collect()
+}
+
+fn merge_collection<T: SyncRecord>(
+    base: &[T],
+    local: &[T],
+    remote: &[T],
+    deletions: &Deletions,
+    conflicts: &mut Vec<Conflict>,
+) -> Vec<T> {
+    let base_map: HashMap<Uuid, &T> = base.iter().map(|r| (r.id(), r)).collect();
+    let local_map: HashMap<Uuid, &T> = local.iter().map(|r| (r.id(), r)).collect();
+    let remote_map: HashMap<Uuid, &T> = remote.iter().map(|r| (r.id(), r)).collect();
+
+    // Keep local ordering, followed by records that only exist remotely
+    let mut ids: Vec<Uuid> = local.iter().map(|r| r.id()).collect();
+    ids.extend(
+        remote
+            .iter()
+            .map(|r| r.id())
+            .filter(|id| !local_map.contains_key(id)),
+    );
+    ids.extend(
+        base.iter()
+            .map(|r| r.id())
+            .filter(|id| !local_map.contains_key(id) && !remote_map.contains_key(id)),
+    );
+
+    let mut merged = Vec::new();
+
+    for id in ids {
+        let b = base_map.get(&id).copied();
+        let l = local_map.get(&id).copied();
+        let r = remote_map.get(&id).copied();
+
+        let unchanged = |side: Option<&T>| match (b, side) {
+            (Some(b), Some(s)) => b.same_content(s),
+            (None, None) => true,
+            _ => false,
+        };
+
+        // Without a base, like an import: a deletion wins over older changes
+        let deleted_since = |deleted: &HashMap<Uuid, DateTime<Utc>>, other: &T| {
+            b.is_none() && deleted.get(&id).is_some_and(|at| other.updated_at() <= *at)
+        };
+
+        let resolved = match (l, r) {
+            (Some(l), Some(r)) if l.same_content(r) => Some(newer(l, r)),
+            (None, None) => None,
+            (None, Some(r)) if deleted_since(&deletions.local, r) => None,
+            (Some(l), None) if deleted_since(&deletions.remote, l) => None,
+            _ if unchanged(l) => r.cloned(),
+            _ if unchanged(r) => l.cloned(),
+            _ => {
+                conflicts.push(Conflict {
+                    kind: T::KIND,
+                    id,
+                    local: l.cloned().map(SyncRecord::into_record),
+                    remote: r.cloned().map(SyncRecord::into_record),
+                    detected_at: Utc::now(),
+                });
+                l.cloned()
+            }
+        };
+
+        if let Some(record) = resolved {
+            merged.push(record);
+        }
+    }
+
+    merged
+}
+
+/// Equal content on both sides: keep whichever carries the later metadata.
+fn newer<T: SyncRecord>(local: &T, remote: &T) -> T {
+    if remote.updated_at() > local.updated_at() {
+        remote.clone()
+    } else {
+        local.clone()
+    }
+}
+
+/// Entries that survived the merge must not point at a deleted vision.
+/// If one side deleted a vision while the other added to it, bring the
+/// vision back and flag the deletion as a conflict.
+fn restore_referenced_visions(
+    local: &Database,
+    remote: &Database,
+    merged: &mut Database,
+    conflicts: &mut Vec<Conflict>,
+) {
+    let referenced: HashSet<Uuid> = merged
+        .realities
+        .iter()
+        .map(|r| r.vision_id)
+        .chain(merged.actions.iter().map(|a| a.vision_id))
+        .chain(merged.visions.iter().filter_map(|v| v.parent))
+        .chain(merged.visions.iter().flat_map(|v| v.depends_on.clone()))
+        .collect();
+
+    for id in referenced {
+        if merged.visions.iter().any(|v| v.id == id) {
+            continue;
+        }
+
+        let local_version = local.visions.iter().find(|v| v.id == id);
+        let remote_version = remote.visions.iter().find(|v| v.id == id);
+
+        if let Some(vision) = local_version.or(remote_version) {
+            merged.visions.push(vision.clone());
+            if !conflicts.iter().any(|c| c.id == id) {
+                conflicts.push(Conflict {
+                    kind: RecordKind::Vision,
+                    id,
+                    local: local_version.cloned().map(Record::Vision),
+                    remote: remote_version.cloned().map(Record::Vision),
+                    detected_at: Utc::now(),
+                });
+            }
+        }
+    }
+}
+
+fn upsert<T: SyncRecord>(records: &mut Vec<T>, record: T) {
+    match records.iter_mut().find(|r| r.id() == record.id()) {
+        Some(existing) => *existing = record,
+        None => records.push(record),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base_with_vision() -> (Database, Uuid) {
+        let mut db = Database::default();
+        let vision = Vision::new("Write a book".to_string());
+        let id = vision.id;
+        db.visions.push(vision);
+        (db, id)
+    }
+
+    #[test]
+    fn test_one_sided_changes_merge_cleanly() {
+        let (base, id) = base_with_vision();
+
+        let mut local = base.clone();
+        local
+            .actions
+            .push(ActionLog::new(id, "Outline".to_string()));
+
+        let mut remote = base.clone();
+        remote.visions[0].description = Some("A novel".to_string());
+        remote.visions[0].touch();
+
+        let outcome = three_way_merge(&base, &local, &remote);
+        assert!(outcome.conflicts.is_empty());
+        assert_eq!(outcome.merged.actions.len(), 1);
+        assert_eq!(
+            outcome.merged.visions[0].description.as_deref(),
+            Some("A novel")
+        );
+    }
+
+    #[test]
+    fn test_concurrent_edits_conflict_and_keep_local() {
+        let (base, _) = base_with_vision();
+
+        let mut local = base.clone();
+        local.visions[0].title = "Write two books".to_string();
+
+        let mut remote = base.clone();
+        remote.visions[0].title = "Write a short story".to_string();
+
+        let outcome = three_way_merge(&base, &local, &remote);
+        assert_eq!(outcome.conflicts.len(), 1);
+        assert_eq!(outcome.merged.visions[0].title, "Write two books");
+    }
+
+    #[test]
+    fn test_remote_deletion_propagates_with_tombstone() {
+        let (mut base, id) = base_with_vision();
+        let action = ActionLog::new(id, "Outline".to_string());
+        let action_id = action.id;
+        base.actions.push(action);
+
+        let local = base.clone();
+        let mut remote = base.clone();
+        remote.actions.clear();
+        remote
+            .tombstones
+            .push(Tombstone::new(RecordKind::Action, action_id));
+
+        let outcome = three_way_merge(&base, &local, &remote);
+        assert!(outcome.conflicts.is_empty());
+        assert!(outcome.merged.actions.is_empty());
+        assert_eq!(outcome.merged.tombstones.len(), 1);
+    }
+
+    #[test]
+    fn test_tombstones_count_without_a_base() {
+        let (remote, id) = base_with_vision();
+        let mut remote = remote;
+        let deleted = ActionLog::new(id, "Outline".to_string());
+        let mut edited = ActionLog::new(id, "Draft".to_string());
+        remote.actions.push(deleted.clone());
+        remote.actions.push(edited.clone());
+
+        let mut local = remote.clone();
+        local.actions.clear();
+        local
+            .tombstones
+            .push(Tombstone::new(RecordKind::Action, deleted.id));
+        let tombstone = Tombstone::new(RecordKind::Action, edited.id);
+        edited.updated_at = tombstone.deleted_at + chrono::Duration::seconds(1);
+        remote.actions[1] = edited.clone();
+        local.tombstones.push(tombstone);
+
+        // A first sync: the deletion sticks, the later edit survives it
+        let outcome = three_way_merge(&Database::default(), &local, &remote);
+        assert!(outcome.conflicts.is_empty());
+        assert_eq!(outcome.merged.actions, [edited]);
+        assert_eq!(outcome.merged.tombstones.len(), 1);
+
+        // And the same the other way round
+        let outcome = three_way_merge(&Database::default(), &remote, &local);
+        assert_eq!(outcome.merged.actions.len(), 1);
+        assert_eq!(outcome.merged.tombstones[0].id, deleted.id);
+    }
+
+    #[test]
+    fn test_deleted_vision_with_new_entries_is_restored_as_conflict() {
+        let (base, id) = base_with_vision();
+
+        let mut local = base.clone();
+        local.visions.clear();
+        local
+            .tombstones
+            .push(Tombstone::new(RecordKind::Vision, id));
+
+        let mut remote = base.clone();
+        remote
+            .actions
+            .push(ActionLog::new(id, "Chapter one".to_string()));
+
+        let outcome = three_way_merge(&base, &local, &remote);
+        assert_eq!(outcome.merged.visions.len(), 1);
+        assert_eq!(outcome.conflicts.len(), 1);
+        assert!(outcome.merged.tombstones.is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+pub mod folder;
+pub mod lock;
+pub mod merge;
+pub mod store;
+
+pub use folder::FolderSync;
+pub use lock::SyncLock;
+pub use merge::{three_way_merge, Conflict, MergeOutcome, Record, SyncRecord};
+pub use store::SyncStore;
+
+use crate::{
+    error::{TensileError, TensileResult},
+    persistence,
+};
+use uuid::Uuid;
+
+/// How often `reconcile` merges again when local data changes under it.
+const RECONCILE_ATTEMPTS: usize = 3;
+
+/// Which side of a conflict to keep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Side {
+    #[value(alias = "mine")]
+    Local,
+    #[value(alias = "theirs")]
+    Remote,
+}
+
+/// Three-way merge `remote` into the local database over `channel`.
+///
+/// The merged result is saved locally before `remote` becomes the new base,
+/// so an interrupted sync never makes remote changes look like local edits.
+/// Callers hold `SyncStore::lock`; a command that saves while the merge runs
+/// makes it start over rather than be overwritten.
+pub fn reconcile(
+    store: &SyncStore,
+    channel: &str,
+    remote: &crate::models::Database,
+) -> TensileResult<MergeOutcome> {
+    for _ in 0..RECONCILE_ATTEMPTS {
+        let local = persistence::load_database()?;
+        let base = store.load_base(channel)?;
+
+        let outcome = three_way_merge(&base, &local, remote);
+        if !unchanged(&local, &persistence::load_database()?)? {
+            continue;
+        }
+
+        persistence::save_database(&outcome.merged)?;
+        store.save_base(channel, remote)?;
+        store.record_conflicts(&outcome.conflicts)?;
+
+        return Ok(outcome);
+    }
+
+    Err(TensileError::Sync(
+        "Local data kept changing while syncing; try again".to_string(),
+    ))
+}
+
+/// Whether two loads of the local database hold the same data.
+fn unchanged(
+    before: &crate::models::Database,
+    after: &crate::models::Database,
+) -> TensileResult<bool> {
+    let json =
+        |db| serde_json::to_value(db).map_err(|e| TensileError::Serialization(e.to_string()));
+    Ok(json(before)? == json(after)?)
+}
+
+/// Fail if conflicts from an earlier sync still need `tensile sync resolve`.
+pub fn ensure_no_conflicts(store: &SyncStore) -> TensileResult<()> {
+    let pending = store.load_conflicts()?.len();
+    if pending > 0 {
+        return Err(TensileError::StateConflict(format!(
+            "{} unresolved sync conflict(s); run `tensile sync conflicts`",
+            pending
+        )));
+    }
+    Ok(())
+}
+
+/// Settle a pending conflict by keeping one side. Keeping the remote side
+/// rewrites (or deletes) the local record; keeping the local side leaves it
+/// as is so the next push propagates it.
+pub fn resolve(store: &SyncStore, id: Uuid, keep: Side) -> TensileResult<Conflict> {
+    let _lock = store.lock()?;
+    let mut conflicts = store.load_conflicts()?;
+    let index = conflicts
+        .iter()
+        .position(|c| c.id == id)
+        .ok_or_else(|| TensileError::NotFound(format!("No sync conflict for {}", id)))?;
+    let conflict = conflicts.remove(index);
+
+    if keep == Side::Remote {
+        let mut db = persistence::load_database()?;
+        match conflict.remote.clone() {
+            Some(record) => record.apply_to(&mut db),
+            None => db.remove_record(conflict.kind, conflict.id),
+        }
+        db.rebuild_children();
+        persistence::save_database(&db)?;
+    }
+
+    store.save_conflicts(&conflicts)?;
+    Ok(conflict)
+}
@@ -0,0 +1,117 @@
+use super::{lock::SyncLock, merge::Conflict};
+use crate::{
+    config,
+    error::{TensileError, TensileResult},
+    models::Database,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Local sync bookkeeping: the last-synced base per channel and pending conflicts.
+pub struct SyncStore {
+    dir: PathBuf,
+}
+
+impl SyncStore {
+    pub fn new() -> Self {
+        SyncStore {
+            dir: config::data_dir().join("sync"),
+        }
+    }
+
+    pub fn at(dir: PathBuf) -> Self {
+        SyncStore { dir }
+    }
+
+    /// Snapshot both sides agreed on at the end of the last sync over `channel`.
+    pub fn load_base(&self, channel: &str) -> TensileResult<Database> {
+        Ok(read_json(&self.base_path(channel))?.unwrap_or_default())
+    }
+
+    pub fn save_base(&self, channel: &str, db: &Database) -> TensileResult<()> {
+        write_json(&self.base_path(channel), db)
+    }
+
+    /// Whether `channel` has completed at least one sync.
+    pub fn has_base(&self, channel: &str) -> bool {
+        self.base_path(channel).exists()
+    }
+
+    /// Whether local changes still have to be sent over `channel`.
+    pub fn is_pending(&self, channel: &str) -> bool {
+        self.pending_path(channel).exists()
+    }
+
+    pub fn set_pending(&self, channel: &str, pending: bool) -> TensileResult<()> {
+        let path = self.pending_path(channel);
+        if pending {
+            std::fs::create_dir_all(&self.dir)?;
+            std::fs::write(path, "")?;
+        } else if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    pub fn load_conflicts(&self) -> TensileResult<Vec<Conflict>> {
+        Ok(read_json(&self.dir.join("conflicts.json"))?.unwrap_or_default())
+    }
+
+    pub fn save_conflicts(&self, conflicts: &[Conflict]) -> TensileResult<()> {
+        write_json(&self.dir.join("conflicts.json"), &conflicts)
+    }
+
+    /// Add newly detected conflicts, replacing older ones for the same record.
+    pub fn record_conflicts(&self, new: &[Conflict]) -> TensileResult<()> {
+        let mut conflicts = self.load_conflicts()?;
+        conflicts.retain(|c| !new.iter().any(|n| n.id == c.id));
+        conflicts.extend_from_slice(new);
+        self.save_conflicts(&conflicts)
+    }
+
+    /// Hold this around any read-merge-write of the local database and the
+    /// bookkeeping above, so two syncs never interleave.
+    pub fn lock(&self) -> TensileResult<SyncLock> {
+        SyncLock::acquire(&self.dir.join("sync.lock"))
+    }
+
+    fn base_path(&self, channel: &str) -> PathBuf {
+        self.dir.join(format!("base-{}.json", channel))
+    }
+
+    fn pending_path(&self, channel: &str) -> PathBuf {
+        self.dir.join(format!("pending-{}", channel))
+    }
+}
+
+impl Default for SyncStore {
+    fn default() -> Self {
+        SyncStore::new()
+    }
+}
+
+pub(crate) fn read_json<T: DeserializeOwned>(path: &Path) -> TensileResult<Option<T>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let contents = std::fs::read_to_string(path)?;
+    serde_json::from_str(&contents)
+        .map(Some)
+        .map_err(|e| TensileError::Serialization(format!("{}: {}", path.display(), e)))
+}
+
+/// Write via a temporary file and rename, so readers never see a partial file.
+pub(crate) fn write_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> TensileResult<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let contents = serde_json::to_string_pretty(value)
+        .map_err(|e| TensileError::Serialization(e.to_string()))?;
+
+    let tmp = path.with_extension(format!("tmp-{}", uuid::Uuid::new_v4()));
+    std::fs::write(&tmp, contents)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use crate::{models::Database, persistence, TensileResult};
+use crate::{models::Vision, service::Session, TensileResult};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -16,7 +16,7 @@ pub enum InputMode {
 }
 
 pub struct App {
-    pub db: Database,
+    pub session: Session,
     pub screen: Screen,
     pub input_mode: InputMode,
     pub selected_vision: Option<Uuid>,
@@ -26,9 +26,8 @@ pub struct App {
 
 impl App {
     pub fn new() -> TensileResult<Self> {
-        let db = persistence::load_database()?;
         Ok(App {
-            db,
+            session: Session::open()?,
             screen: Screen::VisionList,
             input_mode: InputMode::Normal,
             selected_vision: None,
@@ -38,12 +37,11 @@ impl App {
     }
 
     pub fn refresh(&mut self) -> TensileResult<()> {
-        self.db = persistence::load_database()?;
-        Ok(())
+        self.session.reload()
     }
 
-    pub fn save(&self) -> TensileResult<()> {
-        persistence::save_database(&self.db)
+    pub fn save(&mut self) -> TensileResult<()> {
+        self.session.commit()
     }
 
     pub fn handle_events(
@@ -120,15 +118,13 @@ impl App {
         match self.screen {
             Screen::ActionForm => {
                 if let Some(vision_id) = self.selected_vision {
-                    let action =
-                        crate::models::ActionLog::new(vision_id, self.input_buffer.clone());
-                    self.db.actions.push(action);
+                    self.session
+                        .log_action(vision_id, self.input_buffer.clone())?;
                     self.save()?;
                 }
             }
             Screen::VisionList if self.input_mode == InputMode::Editing => {
-                let vision = crate::models::Vision::new(self.input_buffer.clone());
-                self.db.visions.push(vision);
+                self.session.create_vision(self.input_buffer.clone())?;
                 self.save()?;
             }
             _ => {}
@@ -138,7 +134,7 @@ impl App {
     }
 
     fn select_previous_vision(&mut self) {
-        let visions: Vec<Uuid> = self.db.visions.iter().map(|v| v.id).collect();
+        let visions: Vec<Uuid> = self.session.visions().iter().map(|v| v.id).collect();
         if let Some(selected) = self.selected_vision {
             if let Some(pos) = visions.iter().position(|&id| id == selected) {
                 if pos > 0 {
@@ -151,7 +147,7 @@ impl App {
     }
 
     fn select_next_vision(&mut self) {
-        let visions: Vec<Uuid> = self.db.visions.iter().map(|v| v.id).collect();
+        let visions: Vec<Uuid> = self.session.visions().iter().map(|v| v.id).collect();
         if let Some(selected) = self.selected_vision {
             if let Some(pos) = visions.iter().position(|&id| id == selected) {
                 if pos < visions.len() - 1 {
@@ -163,20 +159,16 @@ impl App {
         }
     }
 
-    pub fn get_selected_vision(&self) -> Option<&crate::models::Vision> {
+    pub fn get_selected_vision(&self) -> Option<&Vision> {
         self.selected_vision
-            .and_then(|id| self.db.visions.iter().find(|v| v.id == id))
+            .and_then(|id| self.session.vision(id).ok())
     }
 
     pub fn get_active_visions_count(&self) -> usize {
-        self.db
-            .visions
-            .iter()
-            .filter(|v| !v.completed && matches!(v.state, crate::models::VisionState::InProgress))
-            .count()
+        self.session.summary().active_visions
     }
 
     pub fn get_total_actions(&self) -> usize {
-        self.db.actions.len()
+        self.session.database().actions.len()
     }
 }
@@ -7,7 +7,6 @@ use ratatui::{
 };
 
 use super::app::{App, InputMode, Screen};
-use crate::engine::{PatternAnalyzer, TensionCalculator};
 
 pub fn draw_dashboard(f: &mut Frame, app: &App) {
     let size = f.area();
@@ -50,7 +49,7 @@ fn draw_header(f: &mut Frame, area: Rect, app: &App) {
             Screen::ActionForm => "A",
             Screen::VisionDetail => "D",
         },
-        app.db.visions.len(),
+        app.session.visions().len(),
         app.get_active_visions_count(),
         app.get_total_actions(),
     );
@@ -83,24 +82,19 @@ fn draw_footer(f: &mut Frame, area: Rect, app: &App) {
 
 fn draw_vision_list(f: &mut Frame, area: Rect, app: &App) {
     let visions: Vec<ListItem> = app
-        .db
-        .visions
+        .session
+        .visions()
         .iter()
         .enumerate()
         .map(|(_idx, vision)| {
             let is_selected = app.selected_vision == Some(vision.id);
             let marker = if is_selected { "> " } else { "  " };
 
-            let state_icon = match vision.state {
-                crate::models::VisionState::Achieved => "✓",
-                crate::models::VisionState::Blocked => "⊗",
-                crate::models::VisionState::InProgress => "→",
-                crate::models::VisionState::Reassessed => "◌",
-                crate::models::VisionState::Conceived => "○",
-            };
+            let state_icon = app.session.config().display.state_icons.get(&vision.state);
 
             let actions_count = app
-                .db
+                .session
+                .database()
                 .actions
                 .iter()
                 .filter(|a| a.vision_id == vision.id)
@@ -148,11 +142,13 @@ fn draw_vision_detail(f: &mut Frame, area: Rect, app: &App) {
                 Span::styled(vision.state.to_string(), Style::default().fg(Color::Yellow)),
             ]),
             Line::from(format!("Created: {}", vision.created_at.format("%Y-%m-%d"))),
-            Line::from(format!(
-                "Completed: {}",
-                if vision.completed { "Yes" } else { "No" }
-            )),
         ];
+        if let Some(achieved_at) = vision.achieved_at {
+            detail_text.push(Line::from(format!(
+                "Achieved: {}",
+                achieved_at.format("%Y-%m-%d")
+            )));
+        }
 
         if let Some(desc) = &vision.description {
             detail_text.push(Line::from(""));
@@ -160,9 +156,17 @@ fn draw_vision_detail(f: &mut Frame, area: Rect, app: &App) {
             detail_text.push(Line::from(desc.to_string()));
         }
 
+        if let Some(retrospective) = &vision.retrospective {
+            detail_text.push(Line::from(""));
+            detail_text.push(Line::from(vec![Span::raw("What made it work: ")]));
+            detail_text.push(Line::from(retrospective.to_string()));
+        }
+
+        let detail = app.session.vision_detail(vision.id).ok();
+
         // Add metrics
         detail_text.push(Line::from(""));
-        if let Some(tension) = TensionCalculator::calculate_vision_tension(&app.db, vision.id) {
+        if let Some(tension) = detail.as_ref().and_then(|d| d.tension.as_ref()) {
             detail_text.push(Line::from(vec![
                 Span::raw("Tension: "),
                 Span::styled(
@@ -177,8 +181,34 @@ fn draw_vision_detail(f: &mut Frame, area: Rect, app: &App) {
             )));
         }
 
+        // Add rollup of sub-visions
+        if let Some(rollup) = detail.as_ref().and_then(|d| d.rollup.as_ref()) {
+            detail_text.push(Line::from(""));
+            detail_text.push(Line::from(vec![
+                Span::raw("Progress: "),
+                Span::styled(
+                    format!("{:.0}%", rollup.progress * 100.0),
+                    Style::default().fg(Color::Green),
+                ),
+                Span::raw(format!(
+                    " ({}/{} sub-visions achieved)",
+                    rollup.achieved_children, rollup.children
+                )),
+            ]));
+            detail_text.push(Line::from(format!(
+                "Rolled-up Tension: {:.1}%",
+                rollup.tension
+            )));
+            if rollup.all_achieved() && !vision.is_achieved() {
+                detail_text.push(Line::from(Span::styled(
+                    "All sub-visions achieved: ready to complete?",
+                    Style::default().fg(Color::Yellow),
+                )));
+            }
+        }
+
         // Add pattern
-        if let Some(metrics) = PatternAnalyzer::get_detailed_metrics(&app.db, vision.id) {
+        if let Some(metrics) = detail.as_ref().and_then(|d| d.metrics.as_ref()) {
             detail_text.push(Line::from(""));
             detail_text.push(Line::from(vec![
                 Span::raw("Pattern: "),
@@ -233,8 +263,8 @@ fn draw_action_form(f: &mut Frame, area: Rect, app: &App) {
 }
 
 fn draw_metrics_summary(f: &mut Frame, area: Rect, app: &App) {
-    let tensions = TensionCalculator::calculate_all_tensions(&app.db);
-    let sorted_tensions = TensionCalculator::sort_by_tension(tensions);
+    let summary = app.session.summary();
+    let sorted_tensions = app.session.priorities();
 
     let mut metrics_text = vec![
         Line::from(vec![Span::styled(
@@ -242,21 +272,10 @@ fn draw_metrics_summary(f: &mut Frame, area: Rect, app: &App) {
             Style::default().add_modifier(Modifier::BOLD),
         )]),
         Line::from(""),
-        Line::from(format!("Total Visions: {}", app.db.visions.len())),
-        Line::from(format!(
-            "Active Visions: {}",
-            app.get_active_visions_count()
-        )),
-        Line::from(format!("Total Actions: {}", app.get_total_actions())),
-        Line::from(format!(
-            "Average Tension: {:.1}%",
-            if sorted_tensions.is_empty() {
-                0.0
-            } else {
-                sorted_tensions.iter().map(|t| t.tension_score).sum::<f32>()
-                    / sorted_tensions.len() as f32
-            }
-        )),
+        Line::from(format!("Total Visions: {}", summary.total_visions)),
+        Line::from(format!("Active Visions: {}", summary.active_visions)),
+        Line::from(format!("Total Actions: {}", summary.total_actions)),
+        Line::from(format!("Average Tension: {:.1}%", summary.average_tension)),
     ];
 
     metrics_text.push(Line::from(""));
@@ -266,12 +285,7 @@ fn draw_metrics_summary(f: &mut Frame, area: Rect, app: &App) {
     )]));
 
     for (rank, tension) in sorted_tensions.iter().take(5).enumerate() {
-        let icon = match rank {
-            0 => "🔴",
-            1 => "🟠",
-            2 => "🟡",
-            _ => "  ",
-        };
+        let icon = app.session.config().display.priority_icon(rank);
         metrics_text.push(Line::from(format!(
             "{} {}. {} ({:.0}%)",
             icon,
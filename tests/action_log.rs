@@ -0,0 +1,83 @@
+//! Integration tests for `tensile action log`, `action amend` and `action review`.
+
+mod common;
+
+use common::{json, run, stderr, stdout, tensile};
+
+#[test]
+fn flags_after_the_text_are_still_flags() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+    tensile(home, &["vision", "new", "Learn rust"]);
+
+    let logged = json(tensile(
+        home,
+        &[
+            "-o",
+            "json",
+            "action",
+            "log",
+            "rust",
+            "Read",
+            "chapter",
+            "one",
+            "--failed",
+            "--at",
+            "yesterday",
+        ],
+    ));
+    let id = logged["id"].as_str().unwrap();
+    tensile(
+        home,
+        &[
+            "action",
+            "amend",
+            id,
+            "Read",
+            "chapter",
+            "two",
+            "--succeeded",
+        ],
+    );
+
+    let review = json(tensile(
+        home,
+        &["-o", "json", "action", "review", "--since", "2 days ago"],
+    ));
+    let action = &review["actions"][0];
+    assert_eq!(action["entry"], "Read chapter two");
+    assert_eq!(action["success"], true);
+    let taken =
+        chrono::DateTime::parse_from_rfc3339(action["timestamp"].as_str().unwrap()).unwrap();
+    assert!(taken < chrono::Utc::now() - chrono::Duration::hours(20));
+}
+
+#[test]
+fn review_periods_and_groupings_are_checked_and_completed_by_the_parser() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+
+    let error = stderr(run(home, &["action", "review", "--period", "yearly"]));
+    assert!(error.contains("[possible values: daily, weekly, monthly]"));
+    let error = stderr(run(home, &["action", "review", "--group-by", "month"]));
+    assert!(error.contains("[possible values: vision, day, week]"));
+
+    let review = json(tensile(
+        home,
+        &[
+            "-o",
+            "json",
+            "action",
+            "review",
+            "--period",
+            "Week",
+            "--group-by",
+            "day",
+        ],
+    ));
+    assert_eq!(review["total"], 0);
+
+    let script = stdout(run(home, &["completions", "bash"]));
+    assert!(script.contains("daily weekly monthly"));
+    assert!(script.contains("vision day week"));
+}
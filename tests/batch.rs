@@ -0,0 +1,136 @@
+//! Integration tests for `tensile batch`, fed on stdin.
+
+mod common;
+
+use common::{command, json, run_with_input, tensile};
+use std::path::Path;
+
+fn visions(home: &Path) -> usize {
+    let tree = json(tensile(home, &["-o", "json", "vision", "tree"]));
+    tree.as_array().unwrap().len()
+}
+
+#[test]
+fn batches_apply_atomically_and_report_each_line() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+
+    let failing =
+        "vision new \"Learn the cello\"\naction log cello Scales\naction log knitting x\n";
+    let output = run_with_input(home, &["batch"], failing);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Rolled back"));
+    assert_eq!(visions(home), 0);
+
+    let output = run_with_input(
+        home,
+        &["-o", "json", "batch", "--continue-on-error"],
+        failing,
+    );
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["saved"], true);
+    assert_eq!(report["failed"], 1);
+    assert!(report["results"][0]["result"]["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("Created vision: Learn the cello"));
+    assert!(report["results"][2]["error"]
+        .as_str()
+        .unwrap()
+        .contains("knitting"));
+    assert_eq!(visions(home), 1);
+
+    let jsonl = concat!(
+        r#"["vision", "new", "Run a marathon"]"#,
+        "\n",
+        r#"{"command": "action log", "vision_id": "marathon", "entry": "Ran 5k", "failed": true}"#,
+        "\n",
+    );
+    let output = run_with_input(home, &["batch", "-"], jsonl);
+    assert!(output.status.success());
+    let review = json(tensile(home, &["-o", "json", "action", "review"]));
+    assert_eq!(review["total"], 2);
+    assert_eq!(review["successful"], 1);
+}
+
+#[test]
+fn a_line_that_fails_part_way_is_undone_and_templates_stay_out() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+
+    // The blank child fails after its parent has been created
+    std::fs::create_dir_all(home.join("templates")).unwrap();
+    std::fs::write(
+        home.join("templates/broken.ron"),
+        "(title: \"Album\", children: [(title: \" \")])",
+    )
+    .unwrap();
+
+    let lines = "vision new Piano\ntemplate apply broken\ntemplate save Piano piano\naction log Piano Scales\n";
+    let output = run_with_input(home, &["-o", "json", "batch", "--continue-on-error"], lines);
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["succeeded"], 2);
+    assert_eq!(report["failed"], 2);
+    assert!(report["results"][2]["error"]
+        .as_str()
+        .unwrap()
+        .contains("cannot run in a batch"));
+    assert!(report["results"][3]["ok"].as_bool().unwrap());
+
+    assert_eq!(visions(home), 1);
+    assert!(!home.join("templates/piano.ron").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn undoing_a_failed_line_keeps_earlier_lines_as_they_ran() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+    tensile(home, &["vision", "new", "Piano"]);
+    std::fs::create_dir_all(home.join("templates")).unwrap();
+    std::fs::write(
+        home.join("templates/broken.ron"),
+        "(title: \"Album\", children: [(title: \" \")])",
+    )
+    .unwrap();
+
+    // Counts its runs and writes a different entry each time
+    let editor = home.join("editor.sh");
+    let runs = home.join("runs");
+    let script = format!(
+        "#!/bin/sh\necho run >> \"{0}\"\nprintf 'Scales, take %s' \"$(wc -l < \"{0}\")\" > \"$1\"\n",
+        runs.display()
+    );
+    std::fs::write(&editor, script).unwrap();
+    std::fs::set_permissions(&editor, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let lines = home.join("batch.txt");
+    std::fs::write(&lines, "action log Piano --edit\ntemplate apply broken\n").unwrap();
+    let output = command(home)
+        .env("EDITOR", &editor)
+        .env_remove("VISUAL")
+        .args([
+            "-o",
+            "json",
+            "batch",
+            "--continue-on-error",
+            lines.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let report = json(output);
+
+    assert_eq!(std::fs::read_to_string(&runs).unwrap().lines().count(), 1);
+    let review = json(tensile(home, &["-o", "json", "action", "review"]));
+    assert_eq!(review["actions"][0]["entry"], "Scales, take 1");
+    assert_eq!(
+        review["actions"][0]["id"],
+        report["results"][0]["result"]["id"]
+    );
+    assert_eq!(visions(home), 1);
+}
@@ -0,0 +1,68 @@
+//! Integration tests for blocking visions and `tensile blocked`.
+
+mod common;
+
+use common::{json, run, stderr, tensile};
+
+#[test]
+fn blockers_are_listed_and_due_ones_nudged_in_focus() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+    tensile(home, &["vision", "new", "Book flights"]);
+    tensile(home, &["state", "transition", "Book", "in_progress"]);
+
+    assert!(stderr(run(home, &["state", "transition", "Book", "blocked"])).contains("--reason"));
+    assert!(stderr(run(
+        home,
+        &["state", "transition", "Book", "reassessed", "--reason", "x"]
+    ))
+    .contains("only apply when moving to blocked"));
+
+    tensile(
+        home,
+        &[
+            "state",
+            "transition",
+            "Book",
+            "blocked",
+            "--reason",
+            "No visa yet",
+            "--unblock-when",
+            "The embassy replies",
+            "--unblock-on",
+            "today",
+        ],
+    );
+    let blocked = json(tensile(home, &["-o", "json", "blocked"]));
+    assert_eq!(blocked[0]["title"], "Book flights");
+    assert_eq!(blocked[0]["blocker"]["reason"], "No visa yet");
+    assert_eq!(blocked[0]["blocker"]["unblock_when"], "The embassy replies");
+    assert_eq!(blocked[0]["due"], true);
+
+    let focus = tensile(home, &["focus"]);
+    let focus = String::from_utf8(focus.stdout).unwrap();
+    assert!(focus.contains("Time to re-evaluate"));
+    assert!(focus.contains("in_progress"));
+
+    tensile(home, &["state", "transition", "Book", "in_progress"]);
+    let blocked = json(tensile(home, &["-o", "json", "blocked"]));
+    assert_eq!(blocked.as_array().unwrap().len(), 0);
+    let focus = json(tensile(home, &["-o", "json", "focus"]));
+    assert_eq!(focus["due_for_review"].as_array().unwrap().len(), 0);
+}
+
+#[test]
+fn open_dependencies_stand_in_for_a_reason() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+    tensile(home, &["vision", "new", "Get a visa"]);
+    tensile(home, &["vision", "new", "Book flights"]);
+    tensile(home, &["vision", "depends", "Book", "--on", "Get"]);
+    tensile(home, &["state", "transition", "Book", "in_progress"]);
+    tensile(home, &["state", "transition", "Book", "blocked"]);
+
+    let blocked = json(tensile(home, &["-o", "json", "blocked"]));
+    assert_eq!(blocked[0]["blocker"], serde_json::Value::Null);
+    assert_eq!(blocked[0]["waiting_on"].as_array().unwrap().len(), 1);
+    assert_eq!(blocked[0]["due"], false);
+}
@@ -0,0 +1,298 @@
+//! Integration tests for `CloudSync` against an in-process HTTP stand-in.
+#![cfg(feature = "cloud")]
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use tensile::cloud::{CloudConfig, CloudSync, RecordCounts};
+use tensile::models::{ActionLog, Database, RealityAssessment, Vision};
+use tiny_http::{Method, Response, Server};
+
+const TOKEN: &str = "test-token";
+const USER: &str = "test-user";
+
+/// Minimal sync server: stores the last pushed body per user and serves it
+/// back, tagged with a version that conditional pushes must match.
+struct StandIn {
+    url: String,
+    stored: Arc<Mutex<Option<String>>>,
+    /// A body another device pushes right after the next pull is served.
+    interleave: Arc<Mutex<Option<String>>>,
+}
+
+impl StandIn {
+    fn start() -> Self {
+        let server = Server::http("127.0.0.1:0").expect("bind stand-in server");
+        let url = format!("http://{}", server.server_addr().to_ip().unwrap());
+        let stored: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
+        let interleave: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
+        let state = Arc::clone(&stored);
+        let pending = Arc::clone(&interleave);
+        let mut version = 0u64;
+
+        thread::spawn(move || {
+            for mut request in server.incoming_requests() {
+                let header = |name: &'static str| {
+                    request
+                        .headers()
+                        .iter()
+                        .find(|h| h.field.equiv(name))
+                        .map(|h| h.value.as_str().to_string())
+                };
+
+                let authorized = header("Authorization").as_deref()
+                    == Some(&format!("Bearer {}", TOKEN)[..])
+                    && header("X-User-ID").as_deref() == Some(USER);
+                if !authorized {
+                    let _ = request.respond(Response::empty(401));
+                    continue;
+                }
+
+                let etag = format!("\"{}\"", version);
+                let current = state.lock().unwrap().is_some();
+                let precondition_holds = match (header("If-Match"), header("If-None-Match")) {
+                    (Some(tag), _) => current && tag == etag,
+                    (None, Some(_)) => !current,
+                    (None, None) => true,
+                };
+
+                let response = match (request.method(), request.url()) {
+                    (Method::Post, "/sync/push") if !precondition_holds => {
+                        Response::from_string("").with_status_code(412)
+                    }
+                    (Method::Post, "/sync/push") => {
+                        let mut body = String::new();
+                        request.as_reader().read_to_string(&mut body).unwrap();
+                        *state.lock().unwrap() = Some(body);
+                        version += 1;
+                        Response::from_string("").with_status_code(204)
+                    }
+                    (Method::Get, "/sync/pull") => match state.lock().unwrap().clone() {
+                        Some(body) => Response::from_string(body).with_header(
+                            tiny_http::Header::from_bytes("ETag", etag.as_bytes()).unwrap(),
+                        ),
+                        None => Response::from_string("").with_status_code(404),
+                    },
+                    _ => Response::from_string("").with_status_code(404),
+                };
+                let pulled = request.url() == "/sync/pull";
+                let _ = request.respond(response);
+
+                if pulled {
+                    if let Some(body) = pending.lock().unwrap().take() {
+                        *state.lock().unwrap() = Some(body);
+                        version += 1;
+                    }
+                }
+            }
+        });
+
+        StandIn {
+            url,
+            stored,
+            interleave,
+        }
+    }
+
+    fn client(&self, token: &str) -> CloudSync {
+        CloudSync::new(CloudConfig {
+            api_url: self.url.clone(),
+            api_token: token.to_string(),
+            user_id: USER.to_string(),
+        })
+    }
+}
+
+fn sample_database() -> Database {
+    let mut db = Database::default();
+    let vision = Vision::new("Learn Rust".to_string());
+    db.actions
+        .push(ActionLog::new(vision.id, "Read chapter 1".to_string()));
+    db.realities.push(RealityAssessment::new(
+        vision.id,
+        "Understand ownership".to_string(),
+    ));
+    db.visions.push(vision);
+    db
+}
+
+#[tokio::test]
+async fn push_then_pull_round_trips_database() {
+    let server = StandIn::start();
+    let sync = server.client(TOKEN);
+    let db = sample_database();
+
+    sync.push(&db).await.unwrap();
+    assert!(server.stored.lock().unwrap().is_some());
+
+    let pulled = sync.pull().await.unwrap();
+    assert_eq!(pulled.visions.len(), 1);
+    assert_eq!(pulled.visions[0].id, db.visions[0].id);
+    assert_eq!(pulled.actions[0].entry, "Read chapter 1");
+    assert_eq!(pulled.realities[0].entry, "Understand ownership");
+}
+
+#[tokio::test]
+async fn pull_from_empty_server_yields_empty_database() {
+    let server = StandIn::start();
+    let pulled = server.client(TOKEN).pull().await.unwrap();
+
+    assert!(pulled.visions.is_empty());
+    assert!(pulled.actions.is_empty());
+}
+
+#[tokio::test]
+async fn rejected_credentials_surface_as_errors() {
+    let server = StandIn::start();
+    let sync = server.client("wrong-token");
+
+    assert!(sync.push(&sample_database()).await.is_err());
+    assert!(sync.pull().await.is_err());
+    assert!(server.stored.lock().unwrap().is_none());
+}
+
+#[tokio::test]
+async fn status_compares_local_and_remote_counts() {
+    let server = StandIn::start();
+    let sync = server.client(TOKEN);
+    let db = sample_database();
+
+    let before = sync.status(&db).await.unwrap();
+    assert_eq!(before.local, RecordCounts::of(&db));
+    assert_eq!(before.remote, RecordCounts::default());
+
+    sync.push(&db).await.unwrap();
+    let after = sync.status(&db).await.unwrap();
+    assert_eq!(after.local, after.remote);
+}
+
+/// Tests switch `TENSILE_HOME`, so they must not run concurrently.
+static HOME_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Point the local database and sync state at another device's home.
+fn switch_device(home: &std::path::Path) {
+    std::env::set_var("TENSILE_HOME", home);
+}
+
+#[tokio::test]
+async fn two_devices_merge_changes_and_flag_conflicts() {
+    use tensile::persistence::{load_database, save_database};
+    use tensile::sync::{self, Side, SyncStore};
+
+    let _guard = HOME_LOCK.lock().await;
+    let server = StandIn::start();
+    let sync = server.client(TOKEN);
+    let laptop = tempfile::tempdir().unwrap();
+    let desktop = tempfile::tempdir().unwrap();
+
+    // Laptop creates a vision and pushes it
+    switch_device(laptop.path());
+    save_database(&sample_database()).unwrap();
+    sync.push_merge(&SyncStore::new()).await.unwrap();
+    let vision_id = load_database().unwrap().visions[0].id;
+
+    // Desktop pulls it and logs an action
+    switch_device(desktop.path());
+    sync.pull_merge(&SyncStore::new()).await.unwrap();
+    let mut db = load_database().unwrap();
+    db.actions
+        .push(ActionLog::new(vision_id, "Chapter 2".to_string()));
+    save_database(&db).unwrap();
+    sync.push_merge(&SyncStore::new()).await.unwrap();
+
+    // Laptop edits the description, pulls, and sees both changes
+    switch_device(laptop.path());
+    let mut db = load_database().unwrap();
+    db.visions[0].description = Some("Systems programming".to_string());
+    db.visions[0].touch();
+    save_database(&db).unwrap();
+
+    let outcome = sync.pull_merge(&SyncStore::new()).await.unwrap();
+    assert!(outcome.conflicts.is_empty());
+    assert_eq!(outcome.merged.actions.len(), 2);
+    sync.push_merge(&SyncStore::new()).await.unwrap();
+
+    // Both devices retitle the same vision; the desktop's push flags it
+    let mut db = load_database().unwrap();
+    db.visions[0].title = "Learn Rust deeply".to_string();
+    save_database(&db).unwrap();
+    sync.push_merge(&SyncStore::new()).await.unwrap();
+
+    switch_device(desktop.path());
+    let mut db = load_database().unwrap();
+    db.visions[0].title = "Learn Rust quickly".to_string();
+    save_database(&db).unwrap();
+
+    let store = SyncStore::new();
+    assert!(sync.push_merge(&store).await.is_err());
+    assert_eq!(store.load_conflicts().unwrap().len(), 1);
+    assert_eq!(
+        load_database().unwrap().visions[0].title,
+        "Learn Rust quickly"
+    );
+
+    sync::resolve(&store, vision_id, Side::Remote).unwrap();
+    assert_eq!(
+        load_database().unwrap().visions[0].title,
+        "Learn Rust deeply"
+    );
+    sync.push_merge(&store).await.unwrap();
+}
+
+#[tokio::test]
+async fn conditional_push_refuses_a_stale_version() {
+    let server = StandIn::start();
+    let sync = server.client(TOKEN);
+
+    assert!(sync.push_if(&sample_database(), None).await.unwrap());
+    assert!(!sync.push_if(&sample_database(), None).await.unwrap());
+
+    let (etag, _) = sync.fetch().await.unwrap();
+    let etag = etag.expect("served version has an ETag");
+    assert!(sync
+        .push_if(&Database::default(), Some(&etag))
+        .await
+        .unwrap());
+    assert!(!sync.push_if(&sample_database(), Some(&etag)).await.unwrap());
+    assert!(sync.pull().await.unwrap().visions.is_empty());
+}
+
+#[tokio::test]
+async fn push_merge_keeps_a_push_made_between_its_pull_and_push() {
+    use tensile::persistence::{load_database, save_database};
+    use tensile::sync::SyncStore;
+
+    let _guard = HOME_LOCK.lock().await;
+    let server = StandIn::start();
+    let sync = server.client(TOKEN);
+    let laptop = tempfile::tempdir().unwrap();
+
+    switch_device(laptop.path());
+    save_database(&sample_database()).unwrap();
+    sync.push_merge(&SyncStore::new()).await.unwrap();
+    let vision_id = load_database().unwrap().visions[0].id;
+
+    // Another device pushes an extra action right after the laptop's pull
+    let mut other = sync.pull().await.unwrap();
+    other
+        .actions
+        .push(ActionLog::new(vision_id, "From the desktop".to_string()));
+    *server.interleave.lock().unwrap() = Some(serde_json::to_string(&other).unwrap());
+
+    let mut db = load_database().unwrap();
+    db.actions
+        .push(ActionLog::new(vision_id, "From the laptop".to_string()));
+    save_database(&db).unwrap();
+    sync.push_merge(&SyncStore::new()).await.unwrap();
+
+    let entries: Vec<String> = sync
+        .pull()
+        .await
+        .unwrap()
+        .actions
+        .into_iter()
+        .map(|a| a.entry)
+        .collect();
+    assert!(entries.contains(&"From the desktop".to_string()));
+    assert!(entries.contains(&"From the laptop".to_string()));
+}
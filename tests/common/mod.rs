@@ -0,0 +1,68 @@
+//! Helpers shared by the integration tests that run the `tensile` binary.
+#![allow(dead_code)]
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+/// The binary, keeping its data in `home` and away from any libSQL server.
+pub fn command(home: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_tensile"));
+    command.env("TENSILE_HOME", home).env_remove("TURSO_URL");
+    command
+}
+
+/// Run the binary, whether or not it succeeds.
+pub fn run(home: &Path, args: &[&str]) -> Output {
+    command(home).args(args).output().unwrap()
+}
+
+/// Run the binary with `stdin` as its input, whether or not it succeeds.
+pub fn run_with_input(home: &Path, args: &[&str], stdin: &str) -> Output {
+    let mut child = command(home)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+/// Run the binary, panicking with its stderr unless it succeeds.
+pub fn tensile(home: &Path, args: &[&str]) -> Output {
+    let output = run(home, args);
+    assert!(
+        output.status.success(),
+        "tensile {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+pub fn json(output: Output) -> serde_json::Value {
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+/// The output of a run that must have succeeded.
+pub fn stdout(output: Output) -> String {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// The error output of a run that must have failed.
+pub fn stderr(output: Output) -> String {
+    assert!(!output.status.success(), "tensile succeeded unexpectedly");
+    String::from_utf8(output.stderr).unwrap()
+}
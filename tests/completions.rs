@@ -0,0 +1,56 @@
+//! Integration tests for `tensile completions` and the dynamic `__complete`
+//! helper the scripts call.
+
+mod common;
+
+use common::{run, stdout};
+use std::process::Stdio;
+
+#[test]
+fn scripts_call_back_for_visions_and_states() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+
+    for shell in ["bash", "zsh", "fish"] {
+        let script = stdout(run(home, &["completions", shell]));
+        assert!(script.contains("tensile __complete --"), "{}", shell);
+    }
+    assert!(!run(home, &["completions", "tcsh"]).status.success());
+
+    // A reader that stops early, like `| head -1`, is not an error
+    let mut child = common::command(home)
+        .args(["completions", "bash"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    drop(child.stdout.take());
+    let output = child.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    stdout(run(home, &["vision", "new", "Learn the cello"]));
+    let lines = stdout(run(home, &["__complete", "--", "action", "log", "Lea"]));
+    let values: Vec<&str> = lines
+        .lines()
+        .map(|l| l.split('\t').next().unwrap())
+        .collect();
+    assert_eq!(values, ["Learn the cello"]);
+
+    let lines = stdout(run(
+        home,
+        &["__complete", "--", "state", "transition", "cello", ""],
+    ));
+    let values: Vec<&str> = lines
+        .lines()
+        .map(|l| l.split('\t').next().unwrap())
+        .collect();
+    assert_eq!(values, ["in_progress", "achieved"]);
+
+    let help = stdout(run(home, &["--help"]));
+    assert!(help.contains("completions"));
+    assert!(!help.contains("__complete"));
+}
@@ -0,0 +1,152 @@
+//! Integration tests for `config.toml` and `tensile config`.
+
+mod common;
+
+use common::{json, run, stderr, stdout};
+
+#[test]
+fn set_keeps_comments_and_rejects_bad_values() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+    let file = home.join("config.toml");
+
+    assert_eq!(
+        stdout(run(home, &["config", "get", "patterns.recent_days"])),
+        "7\n"
+    );
+    std::fs::write(&file, "# My settings\n[patterns]\nrecent_days = 14\n").unwrap();
+    stdout(run(
+        home,
+        &["config", "set", "patterns.advancing_above", "0.9"],
+    ));
+    stdout(run(
+        home,
+        &["config", "set", "review.week_starts_on", "sunday"],
+    ));
+
+    let text = std::fs::read_to_string(&file).unwrap();
+    assert!(text.starts_with("# My settings\n[patterns]\nrecent_days = 14\n"));
+    assert!(text.contains("advancing_above = 0.9"));
+    assert!(text.contains("week_starts_on = \"sunday\""));
+
+    let list = stdout(run(home, &["config", "list"]));
+    assert!(list.contains("patterns.recent_days = 14"));
+    assert!(list.contains("review.default_period = \"daily\""));
+
+    let error = stderr(run(home, &["config", "set", "patterns.colour", "red"]));
+    assert!(error.contains("unknown field `colour`"), "{}", error);
+    let error = stderr(run(
+        home,
+        &["config", "set", "patterns.recent_days", "soon"],
+    ));
+    assert!(error.contains("expected u32"), "{}", error);
+    let error = stderr(run(home, &["config", "get", "review.colour"]));
+    assert!(error.contains("Unknown setting"), "{}", error);
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), text);
+}
+
+#[test]
+fn commands_use_the_configured_database_and_icons() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+
+    stdout(run(
+        home,
+        &["config", "set", "database.path", "elsewhere.db"],
+    ));
+    stdout(run(
+        home,
+        &["config", "set", "display.state_icons.conceived", "*"],
+    ));
+    stdout(run(home, &["vision", "new", "Learn the cello"]));
+    assert!(home.join("elsewhere.db").exists());
+    assert!(!home.join("tensile.db").exists());
+
+    let tree = stdout(run(home, &["vision", "tree"]));
+    assert!(tree.contains("* Learn the cello"), "{}", tree);
+
+    std::fs::write(
+        home.join("config.toml"),
+        "[patterns]\noscillating_above = 0.9\n",
+    )
+    .unwrap();
+    let error = stderr(run(home, &["vision", "tree"]));
+    assert!(error.contains("config.toml"), "{}", error);
+    assert!(error.contains("must not exceed"), "{}", error);
+}
+
+#[test]
+fn an_invalid_config_is_reported_rather_than_ignored() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+
+    let error = stderr(run(
+        home,
+        &["config", "set", "review.default_period", "fortnightly"],
+    ));
+    assert!(error.contains("Unknown period"), "{}", error);
+    stdout(run(
+        home,
+        &["config", "set", "review.default_period", "weekly"],
+    ));
+
+    // Commands that only need the database path must not fall back to the
+    // default database either
+    std::fs::write(
+        home.join("config.toml"),
+        "[database]\npath = \"elsewhere.db\"\ncolour = \"red\"\n",
+    )
+    .unwrap();
+    let error = stderr(run(home, &["events"]));
+    assert!(error.contains("config.toml"), "{}", error);
+    assert!(!home.join("tensile.db").exists());
+
+    std::fs::write(
+        home.join("config.toml"),
+        "[review]\ndefault_period = \"fortnightly\"\n",
+    )
+    .unwrap();
+    let error = stderr(run(home, &["action", "review"]));
+    assert!(error.contains("config.toml"), "{}", error);
+    assert!(error.contains("review.default_period"), "{}", error);
+}
+
+#[test]
+fn an_invalid_file_can_be_listed_and_repaired() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+    let file = home.join("config.toml");
+    std::fs::write(
+        &file,
+        "# My settings\n[patterns]\nthreshold = 1\nrecent_days = 0\n",
+    )
+    .unwrap();
+
+    let list = stdout(run(home, &["config", "list"]));
+    assert!(list.contains("unknown field `threshold`"), "{}", list);
+    assert!(list.contains("patterns.threshold = 1"), "{}", list);
+    let list = json(run(home, &["-o", "json", "config", "list"]));
+    assert!(list["problem"].is_string());
+
+    // A new mistake is still refused
+    let error = stderr(run(home, &["config", "set", "patterns.colour", "red"]));
+    assert!(error.contains("unknown field `colour`"), "{}", error);
+
+    // Each fix is written, with a warning until the file is valid again
+    let output = run(home, &["config", "unset", "patterns.threshold"]);
+    assert!(output.status.success());
+    let warning = String::from_utf8_lossy(&output.stderr);
+    assert!(warning.contains("still invalid"), "{}", warning);
+    assert!(warning.contains("at least 1"), "{}", warning);
+
+    let output = run(home, &["config", "set", "patterns.recent_days", "14"]);
+    assert!(output.stderr.is_empty());
+    assert_eq!(
+        std::fs::read_to_string(&file).unwrap(),
+        "# My settings\n[patterns]\nrecent_days = 14\n"
+    );
+    stdout(run(home, &["vision", "tree"]));
+
+    let error = stderr(run(home, &["config", "unset", "patterns.threshold"]));
+    assert!(error.contains("is not set"), "{}", error);
+}
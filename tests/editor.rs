@@ -0,0 +1,76 @@
+//! Integration tests for writing entries in `$EDITOR`, using a script as the
+//! editor.
+#![cfg(unix)]
+
+mod common;
+
+use common::command;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::process::Output;
+
+fn tensile(home: &Path, editor: &Path, args: &[&str]) -> Output {
+    command(home)
+        .env("EDITOR", editor)
+        .env_remove("VISUAL")
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// An editor that saves what it was shown to `seen` and replaces the file
+/// with `reply`.
+fn editor(dir: &Path, reply: &str) -> std::path::PathBuf {
+    let path = dir.join("editor.sh");
+    let script = format!(
+        "#!/bin/sh\ncp \"$1\" \"{}\"\nprintf '%s' '{}' > \"$1\"\n",
+        dir.join("seen").display(),
+        reply
+    );
+    std::fs::write(&path, script).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+#[test]
+fn edit_opens_a_template_and_strips_comments() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+    let quiet = editor(home, "unused");
+
+    let run = |editor: &Path, args: &[&str]| {
+        let output = tensile(home, editor, args);
+        assert!(
+            output.status.success(),
+            "tensile {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    };
+    run(&quiet, &["vision", "new", "Learn the cello"]);
+    run(
+        &quiet,
+        &["vision", "describe", "cello", "Play a Bach suite"],
+    );
+    run(&quiet, &["reality", "update", "cello", "Can tune it"]);
+
+    let reply = editor(home, "Played scales\n\nfor an hour\n# not saved\n");
+    run(&reply, &["reality", "update", "cello", "--edit"]);
+    let seen = std::fs::read_to_string(home.join("seen")).unwrap();
+    assert!(seen.contains("# Current reality of: Learn the cello"));
+    assert!(seen.contains("#   Play a Bach suite"));
+    assert!(seen.contains("#   Can tune it"));
+
+    let output = tensile(home, &quiet, &["-o", "json", "reality", "latest"]);
+    let latest: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(latest["entry"], "Played scales\n\nfor an hour");
+
+    // Without --edit and with no text, a non-interactive run does not block.
+    let output = tensile(home, &reply, &["action", "log", "cello"]);
+    assert!(!output.status.success());
+
+    let empty = editor(home, "# only a comment\n");
+    let output = tensile(home, &empty, &["action", "log", "cello", "-e"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("empty"));
+}
@@ -0,0 +1,104 @@
+//! Integration tests for the event journal and `tensile events`.
+
+mod common;
+
+use common::{command, tensile};
+use std::io::{BufRead, BufReader};
+use std::process::{Output, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
+
+fn json_lines(output: Output) -> Vec<serde_json::Value> {
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn events_lists_the_journal_and_resumes_after_a_sequence_number() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+
+    tensile(home, &["vision", "new", "Learn the cello"]);
+    tensile(home, &["action", "log", "cello", "Played scales"]);
+    tensile(home, &["state", "transition", "cello", "in_progress"]);
+
+    let events = json_lines(tensile(home, &["events"]));
+    let names: Vec<&str> = events
+        .iter()
+        .map(|e| e["event"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["vision_created", "action_logged", "state_changed"]);
+    assert_eq!(events[1]["action"]["entry"], "Played scales");
+    assert_eq!(events[2]["to"], "InProgress");
+
+    let after = json_lines(tensile(home, &["events", "--after", "2"]));
+    assert_eq!(after.len(), 1);
+    assert_eq!(after[0]["seq"], 3);
+    assert_eq!(json_lines(tensile(home, &["events", "-n", "1"])), after);
+
+    // A batch that rolls back publishes nothing.
+    let batch = home.join("batch.txt");
+    std::fs::write(&batch, "vision new Juggling\naction log knitting Cast on\n").unwrap();
+    let output = command(home)
+        .args(["batch", batch.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(json_lines(tensile(home, &["events", "--after", "3"])).is_empty());
+}
+
+#[test]
+fn follow_streams_events_from_other_processes() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+    tensile(home, &["vision", "new", "Learn the cello"]);
+
+    let mut follower = command(home)
+        .args(["events", "--follow"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let stdout = follower.stdout.take().unwrap();
+    let (lines, received) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            if lines.send(line.unwrap()).is_err() {
+                break;
+            }
+        }
+    });
+
+    // Give the follower time to start from the current end of the journal.
+    std::thread::sleep(Duration::from_millis(500));
+    tensile(home, &["action", "log", "cello", "Played scales"]);
+    let line = received.recv_timeout(Duration::from_secs(5));
+    follower.kill().unwrap();
+    follower.wait().unwrap();
+
+    let event: serde_json::Value = serde_json::from_str(&line.unwrap()).unwrap();
+    assert_eq!(event["event"], "action_logged");
+    assert_eq!(event["seq"], 2);
+}
+
+#[cfg(unix)]
+#[test]
+fn a_file_in_the_way_of_the_socket_is_left_alone() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+    let notes = home.join("notes.txt");
+    std::fs::write(&notes, "Remember the rosin").unwrap();
+
+    let output = command(home)
+        .args(["events", "--follow", "--socket", notes.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("is not a socket"));
+    assert_eq!(
+        std::fs::read_to_string(&notes).unwrap(),
+        "Remember the rosin"
+    );
+}
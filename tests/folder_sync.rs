@@ -0,0 +1,136 @@
+//! Integration tests for syncing two devices through a shared folder.
+
+mod common;
+
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+
+use tensile::config;
+use tensile::models::{ActionLog, Vision};
+use tensile::persistence::{load_database, save_database};
+use tensile::sync::{FolderSync, SyncStore};
+
+use common::{stderr, tensile};
+
+/// Tests switch `TENSILE_HOME`, so they must not run concurrently.
+static HOME_LOCK: Mutex<()> = Mutex::new(());
+
+/// Point the local database and sync state at another device's home.
+fn switch_device(home: &Path) {
+    std::env::set_var("TENSILE_HOME", home);
+}
+
+fn folder(shared: &Path) -> FolderSync {
+    FolderSync::new(shared.to_path_buf(), config::device_id().unwrap()).unwrap()
+}
+
+#[test]
+fn devices_exchange_changes_through_shared_folder() {
+    let _guard = HOME_LOCK.lock().unwrap();
+    let shared = tempfile::tempdir().unwrap();
+    let laptop = tempfile::tempdir().unwrap();
+    let desktop = tempfile::tempdir().unwrap();
+
+    // Laptop publishes a vision
+    switch_device(laptop.path());
+    let mut db = load_database().unwrap();
+    let vision = Vision::new("Run a marathon".to_string());
+    let vision_id = vision.id;
+    db.visions.push(vision);
+    save_database(&db).unwrap();
+
+    let report = folder(shared.path()).run(&SyncStore::new()).unwrap();
+    assert!(report.peers.is_empty());
+    assert_eq!(report.exported, Some(1));
+    let laptop_log = folder(shared.path()).log_path(config::device_id().unwrap());
+
+    // Desktop picks it up and logs an action
+    switch_device(desktop.path());
+    let report = folder(shared.path()).run(&SyncStore::new()).unwrap();
+    assert_eq!(report.peers.len(), 1);
+    let mut db = load_database().unwrap();
+    assert_eq!(db.visions.len(), 1);
+    db.actions
+        .push(ActionLog::new(vision_id, "10k run".to_string()));
+    save_database(&db).unwrap();
+    folder(shared.path()).run(&SyncStore::new()).unwrap();
+
+    // A torn write on the laptop's log must not break the desktop's next run
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&laptop_log)
+        .unwrap();
+    write!(file, "{{\"seq\": 99, \"at\": ").unwrap();
+    drop(file);
+
+    // Laptop sees the action
+    switch_device(laptop.path());
+    folder(shared.path()).run(&SyncStore::new()).unwrap();
+    assert_eq!(load_database().unwrap().actions.len(), 1);
+
+    switch_device(desktop.path());
+    folder(shared.path()).run(&SyncStore::new()).unwrap();
+    assert_eq!(load_database().unwrap().visions.len(), 1);
+
+    // Same vision retitled on both devices: conflict, and nothing exported
+    switch_device(laptop.path());
+    let mut db = load_database().unwrap();
+    db.visions[0].title = "Run an ultramarathon".to_string();
+    db.visions[0].touch();
+    save_database(&db).unwrap();
+    folder(shared.path()).run(&SyncStore::new()).unwrap();
+
+    switch_device(desktop.path());
+    let mut db = load_database().unwrap();
+    db.visions[0].title = "Run a half marathon".to_string();
+    db.visions[0].touch();
+    save_database(&db).unwrap();
+
+    let store = SyncStore::new();
+    let report = folder(shared.path()).run(&store).unwrap();
+    assert_eq!(report.exported, None);
+    assert_eq!(store.load_conflicts().unwrap().len(), 1);
+    assert_eq!(
+        load_database().unwrap().visions[0].title,
+        "Run a half marathon"
+    );
+
+    // Resolved from the command line by a short id prefix
+    let prefix = &vision_id.to_string()[..8];
+    let error = stderr(common::run(
+        desktop.path(),
+        &["sync", "resolve", prefix, "--keep", "both"],
+    ));
+    assert!(error.contains("invalid value 'both'"), "{}", error);
+
+    // Not while another sync holds the lock
+    let lock = store.lock().unwrap();
+    let error = stderr(common::run(
+        desktop.path(),
+        &["sync", "resolve", prefix, "--keep", "theirs"],
+    ));
+    assert!(error.contains("Another sync is running"), "{}", error);
+    drop(lock);
+
+    tensile(
+        desktop.path(),
+        &["sync", "resolve", prefix, "--keep", "theirs"],
+    );
+    assert!(store.load_conflicts().unwrap().is_empty());
+    assert_eq!(
+        load_database().unwrap().visions[0].title,
+        "Run an ultramarathon"
+    );
+}
+
+#[test]
+fn sync_folder_must_not_hold_the_database() {
+    let _guard = HOME_LOCK.lock().unwrap();
+    let home = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(home.path().join("shared")).unwrap();
+    switch_device(home.path());
+
+    let result = FolderSync::new(home.path().join("shared"), config::device_id().unwrap());
+    assert!(result.is_err());
+}
@@ -0,0 +1,133 @@
+//! Integration tests for the `[hooks]` configured in `config.toml`.
+#![cfg(unix)]
+
+mod common;
+
+use common::tensile;
+use std::path::Path;
+
+/// Payloads the logging hook received, one JSON object per line.
+fn received(home: &Path) -> Vec<serde_json::Value> {
+    std::fs::read_to_string(home.join("events.jsonl"))
+        .unwrap_or_default()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn hooks_receive_events_as_json() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+    let log = format!(
+        "{{ cat; echo; }} >> '{}'",
+        home.join("events.jsonl").display()
+    );
+    std::fs::write(
+        home.join("config.toml"),
+        format!(
+            "[hooks]\non_action_logged = \"{0}\"\non_state_transition = \"{0}\"\n\
+             on_vision_achieved = \"{0}\"\non_tension_threshold_crossed = \"{0}\"\n\
+             tension_threshold = 70\n",
+            log.replace('"', "\\\"")
+        ),
+    )
+    .unwrap();
+
+    tensile(home, &["vision", "new", "Learn the cello"]);
+    tensile(home, &["reality", "update", "cello", "Can tune it"]);
+    assert!(received(home).is_empty());
+
+    // One action against one reality brings tension from 100% to 67%.
+    tensile(home, &["action", "log", "cello", "Played scales"]);
+    let events = received(home);
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0]["event"], "action_logged");
+    assert_eq!(events[0]["action"]["entry"], "Played scales");
+    assert_eq!(events[1]["event"], "tension_threshold_crossed");
+    assert_eq!(events[1]["direction"], "below");
+
+    tensile(home, &["state", "transition", "cello", "achieved"]);
+    let events = received(home);
+    assert_eq!(events[2]["event"], "state_changed");
+    assert_eq!(events[2]["from"], "Conceived");
+    assert_eq!(events[3]["event"], "vision_achieved");
+    assert_eq!(events[3]["vision_title"], "Learn the cello");
+}
+
+#[test]
+fn failing_and_slow_hooks_only_warn() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+    std::fs::write(
+        home.join("config.toml"),
+        "[hooks]\non_action_logged = \"echo no network >&2; exit 3\"\n\
+         on_state_transition = \"sleep 5\"\ntimeout_secs = 1\n",
+    )
+    .unwrap();
+
+    tensile(home, &["vision", "new", "Learn the cello"]);
+    let output = tensile(home, &["action", "log", "cello", "Played scales"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Hook on_action_logged failed"),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains(": no network"), "{}", stderr);
+
+    let output = tensile(home, &["state", "transition", "cello", "in_progress"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("timed out after 1s"), "{}", stderr);
+
+    let output = tensile(
+        home,
+        &["-o", "json", "action", "review", "--since", "today"],
+    );
+    let review: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(review["total"], 1);
+}
+
+#[test]
+fn a_hook_that_never_reads_a_large_payload_still_times_out() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+    std::fs::write(
+        home.join("config.toml"),
+        "[hooks]\non_action_logged = \"sleep 60\"\ntimeout_secs = 1\n",
+    )
+    .unwrap();
+    tensile(home, &["vision", "new", "Learn the cello"]);
+
+    // Well past the 64 KiB a pipe buffers
+    let words = vec!["scales".repeat(10_000); 8];
+    let mut args = vec!["action", "log", "cello"];
+    args.extend(words.iter().map(String::as_str));
+
+    let started = std::time::Instant::now();
+    let output = tensile(home, &args);
+    assert!(started.elapsed() < std::time::Duration::from_secs(30));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("timed out after 1s"), "{}", stderr);
+}
+
+#[test]
+fn a_failing_hook_does_not_wait_for_what_it_left_running() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+    std::fs::write(
+        home.join("config.toml"),
+        "[hooks]\non_action_logged = \"echo no network >&2; sleep 30 & exit 3\"\n\
+         timeout_secs = 1\n",
+    )
+    .unwrap();
+    tensile(home, &["vision", "new", "Learn the cello"]);
+
+    // The backgrounded sleep keeps the hook's stderr open
+    let started = std::time::Instant::now();
+    let output = tensile(home, &["action", "log", "cello", "Played scales"]);
+    assert!(started.elapsed() < std::time::Duration::from_secs(10));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("exited with"), "{}", stderr);
+    assert!(stderr.contains(": no network"), "{}", stderr);
+}
@@ -0,0 +1,194 @@
+//! Integration tests for JSON export/import, schema validation and import modes.
+
+mod common;
+
+use tensile::models::{ActionLog, Database, RecordKind, Vision};
+use tensile::persistence::{import, json_store, ImportMode};
+
+fn sample_database() -> Database {
+    let mut db = Database::default();
+    let vision = Vision::new("Ship the album".to_string());
+    db.actions
+        .push(ActionLog::new(vision.id, "Mixed track 3".to_string()));
+    db.visions.push(vision);
+    db
+}
+
+#[test]
+fn json_round_trips_through_schema_validation() {
+    let db = sample_database();
+    let json = json_store::to_json(&db).unwrap();
+
+    let parsed = json_store::from_json(&json).unwrap();
+    assert_eq!(parsed.visions, db.visions);
+    assert_eq!(parsed.actions, db.actions);
+}
+
+#[test]
+fn invalid_imports_report_every_location() {
+    let json = json_store::to_json(&sample_database()).unwrap();
+    let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    value["database"]["visions"][0]["state"] = "Done".into();
+    value["database"]["actions"][0]
+        .as_object_mut()
+        .unwrap()
+        .remove("success");
+
+    let message = json_store::from_json(&value.to_string())
+        .unwrap_err()
+        .to_string();
+    assert!(message.contains("/database/visions/0/state"), "{}", message);
+    assert!(message.contains("/database/actions/0"), "{}", message);
+    assert!(message.contains("success"), "{}", message);
+
+    value["schema_version"] = 99.into();
+    let message = json_store::from_json(&value.to_string())
+        .unwrap_err()
+        .to_string();
+    assert!(
+        message.contains("Unsupported schema_version 99"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn dangling_references_are_rejected() {
+    let mut db = sample_database();
+    db.actions[0].vision_id = uuid::Uuid::new_v4();
+    let json = json_store::to_json(&db).unwrap();
+
+    let message = json_store::from_json(&json).unwrap_err().to_string();
+    assert!(
+        message.contains("/database/actions/0/vision_id"),
+        "{}",
+        message
+    );
+
+    let mut db = sample_database();
+    db.visions[0].depends_on.push(uuid::Uuid::new_v4());
+    let json = json_store::to_json(&db).unwrap();
+
+    let message = json_store::from_json(&json).unwrap_err().to_string();
+    assert!(
+        message.contains("/database/visions/0/depends_on/0"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn version_1_exports_still_import() {
+    let json = r#"{
+        "schema_version": 1,
+        "exported_at": "2024-05-01T12:00:00Z",
+        "database": {
+            "visions": [{
+                "id": "6f1c2a4e-8d0b-4c3e-9a55-0c1d2e3f4a5b",
+                "title": "Ship the album",
+                "description": null,
+                "parent": null,
+                "children": [],
+                "created_at": "2024-04-01T09:00:00Z",
+                "completed": false,
+                "state": "InProgress"
+            }],
+            "realities": [],
+            "actions": []
+        }
+    }"#;
+
+    let db = json_store::from_json(json).unwrap();
+    assert_eq!(db.visions[0].title, "Ship the album");
+    assert_eq!(db.visions[0].stakes, Vision::DEFAULT_STAKES);
+    assert!(db.visions[0].depends_on.is_empty());
+
+    // Fields added after version 1 are not part of it
+    let with_stakes = json.replace(
+        "\"state\": \"InProgress\"",
+        "\"state\": \"InProgress\", \"stakes\": 5",
+    );
+    assert!(json_store::from_json(&with_stakes).is_err());
+}
+
+#[test]
+fn modes_decide_who_wins() {
+    let base = sample_database();
+
+    // The other machine retitled the vision later and added a vision
+    let mut incoming = base.clone();
+    incoming.visions[0].title = "Ship the double album".to_string();
+    incoming.visions[0].touch();
+    incoming.visions.push(Vision::new("Tour".to_string()));
+
+    let mut local = base.clone();
+    local.visions.push(Vision::new("Local only".to_string()));
+
+    let mut skip = local.clone();
+    let summary = import(&mut skip, incoming.clone(), ImportMode::SkipExisting);
+    assert_eq!((summary.added, summary.skipped), (1, 1));
+    assert_eq!(skip.visions[0].title, "Ship the album");
+
+    let mut merge = local.clone();
+    let summary = import(&mut merge, incoming.clone(), ImportMode::Merge);
+    assert_eq!((summary.added, summary.updated, summary.removed), (1, 1, 0));
+    assert_eq!(merge.visions[0].title, "Ship the double album");
+    assert_eq!(merge.visions.len(), 3);
+
+    let mut replace = local.clone();
+    let summary = import(&mut replace, incoming.clone(), ImportMode::Replace);
+    assert_eq!(summary.removed, 1);
+    assert_eq!(replace.visions.len(), 2);
+    assert!(replace.visions.iter().all(|v| v.title != "Local only"));
+    assert!(replace
+        .tombstones
+        .iter()
+        .any(|t| t.kind == RecordKind::Vision));
+}
+
+#[test]
+fn colliding_ids_are_reassigned() {
+    let mut local = Database::default();
+    let mine = Vision::new("Mine".to_string());
+    let id = mine.id;
+    local.visions.push(mine);
+
+    // An unrelated vision that happens to use the same id, with an entry
+    let mut theirs = Vision::new("Theirs".to_string());
+    theirs.id = id;
+    theirs.created_at -= chrono::Duration::days(3);
+    let mut incoming = Database::default();
+    incoming
+        .actions
+        .push(ActionLog::new(id, "Their action".to_string()));
+    incoming.visions.push(theirs);
+
+    let summary = import(&mut local, incoming, ImportMode::Merge);
+    assert_eq!(summary.remapped.len(), 1);
+    let fresh = summary.remapped[0].2;
+
+    assert_eq!(local.visions.len(), 2);
+    assert_eq!(
+        local.visions.iter().find(|v| v.id == id).unwrap().title,
+        "Mine"
+    );
+    assert_eq!(local.actions[0].vision_id, fresh);
+}
+
+#[test]
+fn modes_and_formats_are_checked_and_completed_by_the_parser() {
+    use common::{run, stderr, stdout};
+
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+
+    let error = stderr(run(home, &["import", "backup.json", "--mode", "overwrite"]));
+    assert!(error.contains("[possible values: replace, merge, skip-existing]"));
+    let error = stderr(run(home, &["export", "--format", "yaml"]));
+    assert!(error.contains("[possible values: json, ron]"));
+    assert!(stdout(run(home, &["export", "--format", "JSON"])).contains("\"schema_version\""));
+
+    let script = stdout(run(home, &["completions", "bash"]));
+    assert!(script.contains("replace merge skip-existing"));
+    assert!(script.contains("json ron"));
+}
@@ -0,0 +1,473 @@
+//! Integration tests for the libSQL backend.
+//!
+//! By default these run only against the in-process Hrana stand-in below,
+//! which is backed by SQLite and implements just the requests tensile makes.
+//! Set `TENSILE_TEST_SQLD_URL` (e.g. `http://127.0.0.1:8080` for
+//! `sqld --http-listen-addr 127.0.0.1:8080`) to run them against a running
+//! server instead, or run the ignored `scenarios_pass_against_a_real_sqld`,
+//! which starts `sqld` itself, to check the stand-in against the real thing.
+//! The test for racing uploads uses the stand-in either way, as it needs to
+//! act between two requests.
+#![cfg(feature = "turso")]
+
+use std::path::Path;
+use std::process::{Child, Command};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rusqlite::types::Value as SqlValue;
+use rusqlite::Connection;
+use serde_json::{json, Value};
+use tensile::models::{ActionLog, Database, Vision};
+use tensile::persistence::{load_database, save_database, LibsqlStore, SqliteStore};
+use tensile::sync::SyncStore;
+use tiny_http::{Response, Server};
+
+/// Tests switch `TENSILE_HOME`, so they must not run concurrently.
+static HOME_LOCK: Mutex<()> = Mutex::new(());
+
+fn switch_device(home: &Path) {
+    std::env::set_var("TENSILE_HOME", home);
+}
+
+fn store(url: &str) -> LibsqlStore {
+    LibsqlStore::new(url, None, SqliteStore::new(None).unwrap(), SyncStore::new())
+}
+
+/// A device with its own replica, independent of `TENSILE_HOME`.
+fn device(url: &str, home: &Path) -> LibsqlStore {
+    LibsqlStore::new(
+        url,
+        None,
+        SqliteStore::new(Some(home.join("tensile.db"))).unwrap(),
+        SyncStore::at(home.join("sync")),
+    )
+}
+
+/// URL of an empty server: the real one after clearing it, or a fresh stand-in.
+fn server() -> String {
+    if let Ok(url) = std::env::var("TENSILE_TEST_SQLD_URL") {
+        clear(&url);
+        return url;
+    }
+    stand_in().0
+}
+
+/// Remove every record from the server at `url`.
+fn clear(url: &str) {
+    let scratch = tempfile::tempdir().unwrap();
+    switch_device(scratch.path());
+    store(url).push(&Database::default()).unwrap();
+}
+
+/// A `sqld` process on a free port with a throwaway database, killed on drop.
+/// Runs `TENSILE_SQLD_BIN` if set, otherwise `sqld` from `PATH`.
+struct Sqld {
+    url: String,
+    child: Child,
+    _data: tempfile::TempDir,
+}
+
+impl Sqld {
+    fn start() -> Self {
+        let addr = unreachable_url().trim_start_matches("http://").to_string();
+        let data = tempfile::tempdir().unwrap();
+        let bin = std::env::var("TENSILE_SQLD_BIN").unwrap_or_else(|_| "sqld".to_string());
+        let child = Command::new(&bin)
+            .arg("--db-path")
+            .arg(data.path().join("data.sqld"))
+            .args(["--http-listen-addr", &addr])
+            .spawn()
+            .unwrap_or_else(|e| panic!("could not start {}: {}", bin, e));
+        let sqld = Sqld {
+            url: format!("http://{}", addr),
+            child,
+            _data: data,
+        };
+
+        let deadline = Instant::now() + Duration::from_secs(15);
+        while std::net::TcpStream::connect(&addr).is_err() {
+            assert!(Instant::now() < deadline, "sqld did not start listening");
+            thread::sleep(Duration::from_millis(100));
+        }
+        sqld
+    }
+}
+
+impl Drop for Sqld {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Runs once, just before the stand-in handles the next transaction.
+type Interleave = Arc<Mutex<Option<Box<dyn FnOnce() + Send>>>>;
+
+/// A fresh stand-in server, with the number of pipelines it has served.
+/// Requests are served concurrently, so whatever is put in the returned slot
+/// can talk to the server while a transaction waits.
+fn stand_in() -> (String, Interleave, Arc<AtomicUsize>) {
+    let server = Server::http("127.0.0.1:0").expect("bind stand-in server");
+    let url = format!("http://{}", server.server_addr().to_ip().unwrap());
+    let conn = Arc::new(Mutex::new(Connection::open_in_memory().unwrap()));
+    let interleave: Interleave = Arc::default();
+    let pipelines = Arc::new(AtomicUsize::new(0));
+
+    let slot = Arc::clone(&interleave);
+    let served = Arc::clone(&pipelines);
+    thread::spawn(move || {
+        for mut request in server.incoming_requests() {
+            let conn = Arc::clone(&conn);
+            let slot = Arc::clone(&slot);
+            let served = Arc::clone(&served);
+            thread::spawn(move || {
+                if request.url() != "/v2/pipeline" {
+                    let _ = request.respond(Response::empty(404));
+                    return;
+                }
+                served.fetch_add(1, Ordering::SeqCst);
+
+                let mut body = String::new();
+                request.as_reader().read_to_string(&mut body).unwrap();
+                let pipeline: Value = serde_json::from_str(&body).unwrap();
+                let requests = pipeline["requests"].as_array().unwrap();
+
+                if requests.iter().any(|req| req["type"] == "batch") {
+                    let before = slot.lock().unwrap().take();
+                    if let Some(before) = before {
+                        before();
+                    }
+                }
+
+                let conn = conn.lock().unwrap();
+                let results: Vec<Value> = requests.iter().map(|req| handle(&conn, req)).collect();
+                drop(conn);
+
+                let response = json!({ "baton": null, "base_url": null, "results": results });
+                let _ = request.respond(Response::from_string(response.to_string()));
+            });
+        }
+    });
+
+    (url, interleave, pipelines)
+}
+
+/// An address nothing listens on.
+fn unreachable_url() -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+    url
+}
+
+fn handle(conn: &Connection, req: &Value) -> Value {
+    let response = match req["type"].as_str().unwrap() {
+        "execute" => {
+            execute(conn, &req["stmt"]).map(|result| json!({ "type": "execute", "result": result }))
+        }
+        "sequence" => conn
+            .execute_batch(req["sql"].as_str().unwrap())
+            .map(|_| json!({ "type": "sequence" }))
+            .map_err(|e| e.to_string()),
+        "batch" => Ok(json!({ "type": "batch", "result": batch(conn, &req["batch"]) })),
+        "close" => Ok(json!({ "type": "close" })),
+        other => Err(format!("unsupported request {}", other)),
+    };
+
+    match response {
+        Ok(response) => json!({ "type": "ok", "response": response }),
+        Err(message) => json!({ "type": "error", "error": { "message": message } }),
+    }
+}
+
+fn execute(conn: &Connection, stmt: &Value) -> Result<Value, String> {
+    let args: Vec<SqlValue> = stmt["args"]
+        .as_array()
+        .map(|args| args.iter().map(from_wire).collect())
+        .unwrap_or_default();
+
+    let mut prepared = conn
+        .prepare(stmt["sql"].as_str().unwrap())
+        .map_err(|e| e.to_string())?;
+    let cols: Vec<Value> = prepared
+        .column_names()
+        .into_iter()
+        .map(|name| json!({ "name": name }))
+        .collect();
+
+    let mut rows = Vec::new();
+    let mut query = prepared
+        .query(rusqlite::params_from_iter(args))
+        .map_err(|e| e.to_string())?;
+    while let Some(row) = query.next().map_err(|e| e.to_string())? {
+        let values = (0..cols.len())
+            .map(|i| to_wire(row.get::<_, SqlValue>(i).unwrap()))
+            .collect::<Vec<_>>();
+        rows.push(Value::Array(values));
+    }
+
+    Ok(json!({ "cols": cols, "rows": rows, "affected_row_count": 0, "last_insert_rowid": null }))
+}
+
+fn batch(conn: &Connection, batch: &Value) -> Value {
+    let mut outcomes: Vec<Option<bool>> = Vec::new();
+    let mut step_results = Vec::new();
+    let mut step_errors = Vec::new();
+
+    for step in batch["steps"].as_array().unwrap() {
+        if !condition(&step["condition"], &outcomes) {
+            outcomes.push(None);
+            step_results.push(Value::Null);
+            step_errors.push(Value::Null);
+            continue;
+        }
+
+        match execute(conn, &step["stmt"]) {
+            Ok(result) => {
+                outcomes.push(Some(true));
+                step_results.push(result);
+                step_errors.push(Value::Null);
+            }
+            Err(message) => {
+                outcomes.push(Some(false));
+                step_results.push(Value::Null);
+                step_errors.push(json!({ "message": message }));
+            }
+        }
+    }
+
+    json!({ "step_results": step_results, "step_errors": step_errors })
+}
+
+fn condition(cond: &Value, outcomes: &[Option<bool>]) -> bool {
+    let step = |c: &Value| outcomes[c["step"].as_u64().unwrap() as usize];
+    match cond["type"].as_str() {
+        None => true,
+        Some("ok") => step(cond) == Some(true),
+        Some("error") => step(cond) == Some(false),
+        Some("not") => !condition(&cond["cond"], outcomes),
+        Some(other) => panic!("unsupported condition {}", other),
+    }
+}
+
+fn from_wire(value: &Value) -> SqlValue {
+    match value["type"].as_str().unwrap() {
+        "integer" => SqlValue::Integer(value["value"].as_str().unwrap().parse().unwrap()),
+        "float" => SqlValue::Real(value["value"].as_f64().unwrap()),
+        "text" => SqlValue::Text(value["value"].as_str().unwrap().to_string()),
+        _ => SqlValue::Null,
+    }
+}
+
+fn to_wire(value: SqlValue) -> Value {
+    match value {
+        SqlValue::Null | SqlValue::Blob(_) => json!({ "type": "null" }),
+        SqlValue::Integer(i) => json!({ "type": "integer", "value": i.to_string() }),
+        SqlValue::Real(f) => json!({ "type": "float", "value": f }),
+        SqlValue::Text(s) => json!({ "type": "text", "value": s }),
+    }
+}
+
+#[test]
+fn devices_share_records_through_server() {
+    let _guard = HOME_LOCK.lock().unwrap();
+    share_records(&server());
+}
+
+fn share_records(url: &str) {
+    let laptop = tempfile::tempdir().unwrap();
+    let desktop = tempfile::tempdir().unwrap();
+
+    switch_device(laptop.path());
+    let mut db = store(url).load().unwrap();
+    let vision = Vision::new("Write a novel".to_string());
+    let vision_id = vision.id;
+    let mut agent = Vision::new("Find an agent".to_string());
+    agent.depends_on.push(vision_id);
+    db.visions.push(vision);
+    db.visions.push(agent);
+    store(url).save(&db).unwrap();
+
+    switch_device(desktop.path());
+    let mut db = store(url).load().unwrap();
+    assert_eq!(db.visions.len(), 2);
+    assert_eq!(db.visions[0].title, "Write a novel");
+    assert_eq!(db.visions[1].depends_on, [vision_id]);
+    db.actions
+        .push(ActionLog::new(vision_id, "Outlined chapter 1".to_string()));
+    store(url).save(&db).unwrap();
+
+    switch_device(laptop.path());
+    let db = store(url).load().unwrap();
+    assert_eq!(db.actions.len(), 1);
+    assert_eq!(db.actions[0].entry, "Outlined chapter 1");
+
+    // Deletions travel as tombstones
+    let mut db = db;
+    db.remove_record(tensile::models::RecordKind::Action, db.actions[0].id);
+    store(url).save(&db).unwrap();
+
+    switch_device(desktop.path());
+    assert!(store(url).load().unwrap().actions.is_empty());
+}
+
+#[test]
+fn offline_changes_are_kept_and_uploaded_later() {
+    let _guard = HOME_LOCK.lock().unwrap();
+    keep_offline_changes(&server());
+}
+
+fn keep_offline_changes(url: &str) {
+    let home = tempfile::tempdir().unwrap();
+    switch_device(home.path());
+
+    // Offline: the replica takes the write
+    std::env::set_var("TURSO_URL", unreachable_url());
+    let mut db = load_database().unwrap();
+    db.visions.push(Vision::new("Learn the cello".to_string()));
+    save_database(&db).unwrap();
+    assert_eq!(load_database().unwrap().visions.len(), 1);
+    assert!(SyncStore::new().is_pending("turso"));
+
+    // Back online: the next load uploads the pending change
+    std::env::set_var("TURSO_URL", url);
+    assert_eq!(load_database().unwrap().visions.len(), 1);
+    std::env::remove_var("TURSO_URL");
+
+    assert!(!SyncStore::new().is_pending("turso"));
+    let remote = store(url).fetch().unwrap();
+    assert_eq!(remote.visions.len(), 1);
+    assert_eq!(remote.visions[0].title, "Learn the cello");
+}
+
+#[test]
+fn concurrent_edits_are_held_back_as_conflicts() {
+    let _guard = HOME_LOCK.lock().unwrap();
+    hold_back_conflicts(&server());
+}
+
+fn hold_back_conflicts(url: &str) {
+    let laptop = tempfile::tempdir().unwrap();
+    let desktop = tempfile::tempdir().unwrap();
+
+    switch_device(laptop.path());
+    let mut db = Database::default();
+    db.visions.push(Vision::new("Climb Mont Blanc".to_string()));
+    store(url).save(&db).unwrap();
+
+    switch_device(desktop.path());
+    store(url).load().unwrap();
+
+    // Laptop edits while the desktop is offline
+    switch_device(laptop.path());
+    let mut db = store(url).load().unwrap();
+    db.visions[0].title = "Climb the Matterhorn".to_string();
+    db.visions[0].touch();
+    store(url).save(&db).unwrap();
+
+    switch_device(desktop.path());
+    let mut db = SqliteStore::new(None).unwrap().read().unwrap();
+    db.visions[0].title = "Climb Kilimanjaro".to_string();
+    db.visions[0].touch();
+    store(&unreachable_url()).save(&db).unwrap();
+
+    // Reconnecting keeps the local edit and leaves the server alone
+    let outcome = store(url).sync().unwrap().unwrap();
+    assert_eq!(outcome.conflicts.len(), 1);
+    assert_eq!(outcome.merged.visions[0].title, "Climb Kilimanjaro");
+    assert_eq!(
+        store(url).fetch().unwrap().visions[0].title,
+        "Climb the Matterhorn"
+    );
+}
+
+#[test]
+fn uploads_merge_again_when_another_device_uploaded_in_between() {
+    let (url, interleave, _) = stand_in();
+    let laptop = tempfile::tempdir().unwrap();
+    let desktop = tempfile::tempdir().unwrap();
+    let laptop = device(&url, laptop.path());
+    let desktop_home = desktop.path().to_path_buf();
+
+    let mut db = Database::default();
+    db.visions.push(Vision::new("Write a novel".to_string()));
+    laptop.save(&db).unwrap();
+    device(&url, &desktop_home).load().unwrap();
+
+    // The desktop uploads after the laptop fetched but before it uploads
+    let url_for_desktop = url.clone();
+    let home_for_desktop = desktop_home.clone();
+    *interleave.lock().unwrap() = Some(Box::new(move || {
+        let desktop = device(&url_for_desktop, &home_for_desktop);
+        let mut db = desktop.load().unwrap();
+        db.visions.push(Vision::new("Find an agent".to_string()));
+        desktop.save(&db).unwrap();
+    }));
+    let mut db = laptop.load().unwrap();
+    db.visions.push(Vision::new("Learn the cello".to_string()));
+    laptop.save(&db).unwrap();
+    assert!(interleave.lock().unwrap().is_none());
+
+    let mut titles: Vec<String> = laptop
+        .fetch()
+        .unwrap()
+        .visions
+        .into_iter()
+        .map(|v| v.title)
+        .collect();
+    titles.sort();
+    assert_eq!(
+        titles,
+        ["Find an agent", "Learn the cello", "Write a novel"]
+    );
+
+    // The desktop keeps its own vision rather than seeing it deleted remotely
+    let desktop = device(&url, &desktop_home).load().unwrap();
+    assert_eq!(desktop.visions.len(), 3);
+}
+
+#[test]
+fn loads_only_check_the_generation_while_the_server_is_unchanged() {
+    let (url, _, pipelines) = stand_in();
+    let laptop = tempfile::tempdir().unwrap();
+    let desktop = tempfile::tempdir().unwrap();
+    let laptop = device(&url, laptop.path());
+    let served = || pipelines.load(Ordering::SeqCst);
+
+    let mut db = laptop.load().unwrap();
+    db.visions.push(Vision::new("Write a novel".to_string()));
+    laptop.save(&db).unwrap();
+
+    // The schema is checked once, and the laptop's own upload needs no fetch
+    let before = served();
+    let mut db = laptop.load().unwrap();
+    assert_eq!(served() - before, 1);
+    db.actions.push(ActionLog::new(
+        db.visions[0].id,
+        "Outlined chapter 1".to_string(),
+    ));
+    laptop.save(&db).unwrap();
+    assert_eq!(served() - before, 3);
+
+    // Another device's upload is still merged in
+    let desktop = device(&url, desktop.path());
+    let mut db = desktop.load().unwrap();
+    db.visions.push(Vision::new("Find an agent".to_string()));
+    desktop.save(&db).unwrap();
+    assert_eq!(laptop.load().unwrap().visions.len(), 2);
+}
+
+#[test]
+#[ignore = "starts sqld from TENSILE_SQLD_BIN or PATH"]
+fn scenarios_pass_against_a_real_sqld() {
+    let _guard = HOME_LOCK.lock().unwrap();
+    let sqld = Sqld::start();
+
+    for scenario in [share_records, keep_offline_changes, hold_back_conflicts] {
+        clear(&sqld.url);
+        scenario(&sqld.url);
+    }
+}
@@ -0,0 +1,61 @@
+//! Integration tests for the global `--output` flag, run against the binary.
+
+mod common;
+
+use common::{run, stderr, stdout};
+
+#[test]
+fn commands_print_json_csv_and_tables() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+
+    let created: serde_json::Value = serde_json::from_str(&stdout(run(
+        home,
+        &["vision", "new", "Run, a marathon", "--output", "json"],
+    )))
+    .unwrap();
+    let id = created["id"].as_str().unwrap().to_string();
+    stdout(run(home, &["action", "log", &id, "Ran", "5k"]));
+
+    let review: serde_json::Value =
+        serde_json::from_str(&stdout(run(home, &["-o", "json", "action", "review"]))).unwrap();
+    assert_eq!(review["total"], 1);
+    assert_eq!(review["actions"][0]["entry"], "Ran 5k");
+
+    let priorities: serde_json::Value =
+        serde_json::from_str(&stdout(run(home, &["metrics", "priority", "-o", "json"]))).unwrap();
+    assert_eq!(priorities[0]["vision_id"], id.as_str());
+
+    let csv = stdout(run(home, &["vision", "tree", "-o", "csv"]));
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next(),
+        Some("depth,id,title,state,tension,pattern,actions,stakes,progress")
+    );
+    assert_eq!(
+        lines.next(),
+        Some(format!("0,{},\"Run, a marathon\",Conceived,50.0,Advancing,1,3,", id).as_str())
+    );
+
+    let table = stdout(run(home, &["-o", "table", "pattern", "check"]));
+    assert!(table.contains("| pattern"));
+    assert!(table.contains("Run, a marathon"));
+
+    // The older per-command flag still works
+    let tree: serde_json::Value =
+        serde_json::from_str(&stdout(run(home, &["vision", "tree", "--format", "json"]))).unwrap();
+    assert_eq!(tree[0]["id"], id.as_str());
+}
+
+#[test]
+fn formats_are_checked_and_completed_by_the_parser() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+
+    let error = stderr(run(home, &["-o", "yaml", "vision", "tree"]));
+    assert!(error.contains("[possible values: plain, json, table, csv]"));
+    assert!(stdout(run(home, &["-o", "JSON", "vision", "tree"])).starts_with('['));
+
+    let script = stdout(run(home, &["completions", "bash"]));
+    assert!(script.contains("plain json table csv"));
+}
@@ -0,0 +1,485 @@
+//! Integration tests for the service layer used by embedding programs.
+
+use chrono::{Duration, Utc};
+use std::sync::{Arc, Mutex};
+use tensile::events::Event;
+use tensile::models::{Blocker, Database, VisionState};
+use tensile::service::{
+    resolve_action, ActionAmendment, GroupBy, ReviewPeriod, ReviewQuery, Session, TreeOptions,
+};
+use tensile::TensileError;
+
+#[test]
+fn operations_return_domain_structs() {
+    let mut session = Session::new(Database::default());
+    assert!(!session.is_dirty());
+
+    let vision = session.create_vision("Learn the cello").unwrap();
+    let action = session.log_action(vision.id, "Booked a lesson").unwrap();
+    assert_eq!(action.vision_id, vision.id);
+    session.record_reality(vision.id, "Never played").unwrap();
+    assert!(session.is_dirty());
+
+    let transition = session
+        .transition(vision.id, VisionState::InProgress)
+        .unwrap();
+    assert_eq!(transition.from, VisionState::Conceived);
+    assert!(matches!(
+        session.transition(vision.id, VisionState::Conceived),
+        Err(TensileError::InvalidStateTransition(_, _))
+    ));
+
+    session.focus(vision.id).unwrap();
+    assert_eq!(session.resolve(".").unwrap(), vision.id);
+    assert_eq!(session.resolve("cello").unwrap(), vision.id);
+
+    let review = session.review(ReviewPeriod::Daily, Some(vision.id));
+    assert_eq!(review.total, 1);
+    assert_eq!(review.actions[0].entry, "Booked a lesson");
+
+    let summary = session.summary();
+    assert_eq!(summary.total_visions, 1);
+    assert_eq!(summary.active_visions, 1);
+    assert_eq!(session.priorities()[0].vision_id, vision.id);
+    assert_eq!(session.pattern(vision.id).unwrap().total_actions, 1);
+    assert_eq!(session.latest_reality(None).unwrap().entry, "Never played");
+
+    let db = session.into_database();
+    assert_eq!(db.user.unwrap().current_focus, Some(vision.id));
+}
+
+#[test]
+fn unknown_visions_and_empty_titles_are_errors() {
+    let mut session = Session::new(Database::default());
+
+    assert!(matches!(
+        session.create_vision("  "),
+        Err(TensileError::Validation(_))
+    ));
+    assert!(matches!(
+        session.log_action(uuid::Uuid::new_v4(), "Orphan"),
+        Err(TensileError::NotFound(_))
+    ));
+    assert!(!session.is_dirty());
+}
+
+#[test]
+fn hierarchy_edits_reject_cycles_and_keep_sibling_order() {
+    let mut session = Session::new(Database::default());
+    let root = session.create_vision("Healthy life").unwrap().id;
+    let run = session.create_vision("Run a marathon").unwrap().id;
+    let swim = session.create_vision("Swim a mile").unwrap().id;
+    let shoes = session.create_vision("Buy shoes").unwrap().id;
+
+    session.move_vision(run, Some(root)).unwrap();
+    session.move_vision(swim, Some(root)).unwrap();
+    session.link_visions(run, shoes).unwrap();
+
+    assert!(matches!(
+        session.move_vision(root, Some(shoes)),
+        Err(TensileError::Validation(_))
+    ));
+    assert!(matches!(
+        session.move_vision(run, Some(run)),
+        Err(TensileError::Validation(_))
+    ));
+
+    let titles = |session: &Session| -> Vec<String> {
+        session
+            .tree_order()
+            .iter()
+            .map(|v| v.title.clone())
+            .collect()
+    };
+    assert_eq!(
+        titles(&session),
+        ["Healthy life", "Run a marathon", "Buy shoes", "Swim a mile"]
+    );
+
+    session.reorder_vision(swim, 0).unwrap();
+    assert_eq!(
+        titles(&session),
+        ["Healthy life", "Swim a mile", "Run a marathon", "Buy shoes"]
+    );
+
+    session.rename_vision(swim, "Swim two miles").unwrap();
+    session.append_description(swim, "Pool opens at 7").unwrap();
+    session.append_description(swim, "Bring goggles").unwrap();
+    assert_eq!(
+        session.vision(swim).unwrap().description.as_deref(),
+        Some("Pool opens at 7\nBring goggles")
+    );
+
+    session.log_action(run, "Ran 5k").unwrap();
+    let deletion = session.delete_vision(run, false).unwrap();
+    assert_eq!(deletion.actions, 1);
+    assert_eq!(deletion.reparented, [shoes]);
+    assert_eq!(session.vision(shoes).unwrap().parent, Some(root));
+
+    let deletion = session.delete_vision(root, true).unwrap();
+    assert_eq!(deletion.visions.len(), 3);
+    assert!(session.visions().is_empty());
+    assert_eq!(session.database().tombstones.len(), 5);
+}
+
+#[test]
+fn tree_nests_children_and_applies_options() {
+    let mut session = Session::new(Database::default());
+    let root = session.create_vision("Healthy life").unwrap().id;
+    let run = session.create_vision("Run a marathon").unwrap().id;
+    let shoes = session.create_vision("Buy shoes").unwrap().id;
+    let other = session.create_vision("Read more").unwrap().id;
+    session.move_vision(run, Some(root)).unwrap();
+    session.move_vision(shoes, Some(run)).unwrap();
+
+    let tree = session.tree(&TreeOptions::default()).unwrap();
+    assert_eq!(tree.len(), 2);
+    assert_eq!(tree[0].children[0].children[0].id, shoes);
+    assert_eq!(tree[1].id, other);
+    let walked: Vec<(usize, _)> = tree[0].walk().iter().map(|(d, n)| (*d, n.id)).collect();
+    assert_eq!(walked, [(0, root), (1, run), (2, shoes)]);
+
+    let shallow = session
+        .tree(&TreeOptions {
+            depth: Some(2),
+            ..Default::default()
+        })
+        .unwrap();
+    assert!(shallow[0].children[0].children.is_empty());
+
+    session.complete_vision(run, None, None).unwrap();
+    let pruned = session
+        .tree(&TreeOptions {
+            root: Some(root),
+            hide_achieved: true,
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(pruned.len(), 1);
+    assert_eq!(pruned[0].children[0].id, shoes);
+}
+
+#[test]
+fn completion_follows_the_state_machine_and_keeps_a_retrospective() {
+    let mut session = Session::new(Database::default());
+    let run = session.create_vision("Run a marathon").unwrap().id;
+    session.transition(run, VisionState::InProgress).unwrap();
+    session.transition(run, VisionState::Blocked).unwrap();
+
+    assert!(matches!(
+        session.complete_vision(run, Some("Finished".to_string()), None),
+        Err(TensileError::InvalidStateTransition(_, _))
+    ));
+    assert!(session.realities(Some(run)).is_empty());
+
+    session.transition(run, VisionState::InProgress).unwrap();
+    let vision = session
+        .complete_vision(
+            run,
+            Some("Finished in 4:05".to_string()),
+            Some("A training plan on the fridge".to_string()),
+        )
+        .unwrap();
+    assert_eq!(vision.state, VisionState::Achieved);
+    assert!(vision.achieved_at.is_some());
+    assert_eq!(
+        vision.retrospective.as_deref(),
+        Some("A training plan on the fridge")
+    );
+    assert_eq!(
+        session.latest_reality(Some(run)).unwrap().entry,
+        "Finished in 4:05"
+    );
+    assert!(matches!(
+        session.pending_events().last(),
+        Some(Event::VisionAchieved {
+            retrospective: Some(_),
+            ..
+        })
+    ));
+    assert_eq!(session.achievements().achieved, 1);
+
+    // Completing again keeps the achievement date.
+    let again = session
+        .complete_vision(run, None, Some("Running with a friend".to_string()))
+        .unwrap();
+    assert_eq!(again.achieved_at, vision.achieved_at);
+    assert_eq!(
+        again.retrospective.as_deref(),
+        Some("Running with a friend")
+    );
+}
+
+#[test]
+fn parents_roll_up_their_sub_visions_by_stakes() {
+    let mut session = Session::new(Database::default());
+    let run = session.create_vision("Run a marathon").unwrap().id;
+    let shoes = session.create_vision("Buy shoes").unwrap().id;
+    let plan = session.create_vision("Follow a plan").unwrap().id;
+    session.move_vision(shoes, Some(run)).unwrap();
+    session.move_vision(plan, Some(run)).unwrap();
+
+    assert!(matches!(
+        session.set_stakes(plan, 0),
+        Err(TensileError::Validation(_))
+    ));
+    session.set_stakes(plan, 5).unwrap();
+    session.set_stakes(shoes, 1).unwrap();
+    session.complete_vision(shoes, None, None).unwrap();
+    session.transition(plan, VisionState::InProgress).unwrap();
+
+    let rollup = session.vision_detail(run).unwrap().rollup.unwrap();
+    assert_eq!((rollup.children, rollup.achieved_children), (2, 1));
+    assert!((rollup.progress - (1.0 + 5.0 * 0.5) / 6.0).abs() < 1e-6);
+    assert!(session.vision_detail(plan).unwrap().rollup.is_none());
+    assert!(session.completable_parent(shoes).is_none());
+
+    session.complete_vision(plan, None, None).unwrap();
+    assert_eq!(session.completable_parent(plan).unwrap().id, run);
+    let tree = session.tree(&TreeOptions::default()).unwrap();
+    assert_eq!(tree[0].rollup.as_ref().unwrap().progress, 1.0);
+}
+
+#[test]
+fn dependencies_reject_cycles_and_pass_tension_downstream() {
+    let mut session = Session::new(Database::default());
+    let visa = session.create_vision("Get a visa").unwrap().id;
+    let flights = session.create_vision("Book flights").unwrap().id;
+    let race = session.create_vision("Run in Tokyo").unwrap().id;
+    session.add_dependency(flights, visa).unwrap();
+    session.add_dependency(race, flights).unwrap();
+
+    assert!(matches!(
+        session.add_dependency(visa, race),
+        Err(TensileError::Validation(_))
+    ));
+    assert!(matches!(
+        session.add_dependency(visa, visa),
+        Err(TensileError::Validation(_))
+    ));
+    assert_eq!(session.dependents(visa)[0].id, flights);
+
+    // Flights has no entries of its own (tension 100); the visa is well
+    // under way (tension 25), so flights inherits half of that.
+    for _ in 0..3 {
+        session.log_action(visa, "Filled in a form").unwrap();
+    }
+    let tension = |session: &Session, id| {
+        session
+            .vision_detail(id)
+            .unwrap()
+            .tension
+            .unwrap()
+            .tension_score
+    };
+    assert_eq!(tension(&session, visa), 25.0);
+    assert_eq!(tension(&session, flights), 62.5);
+    assert_eq!(tension(&session, race), 81.25);
+
+    let paths = session.critical_paths(Some(race)).unwrap();
+    assert_eq!(paths.len(), 1);
+    assert_eq!(paths[0].vision_id, visa);
+    assert_eq!(paths[0].path.len(), 3);
+
+    session.delete_vision(visa, false).unwrap();
+    assert!(session.dependencies(flights).unwrap().is_empty());
+    assert_eq!(tension(&session, flights), 100.0);
+    session.remove_dependency(race, flights).unwrap();
+    assert!(session.critical_paths(None).unwrap().is_empty());
+}
+
+#[test]
+fn blocking_records_a_blocker_until_the_vision_moves_on() {
+    let mut session = Session::new(Database::default());
+    let visa = session.create_vision("Get a visa").unwrap().id;
+    let flights = session.create_vision("Book flights").unwrap().id;
+    let today = Utc::now().date_naive();
+
+    assert!(matches!(
+        session.block_vision(flights, Some(Blocker::new("No visa yet"))),
+        Err(TensileError::InvalidStateTransition(..))
+    ));
+    session
+        .transition(flights, VisionState::InProgress)
+        .unwrap();
+    let blocker = Blocker {
+        unblock_when: Some("The embassy replies".to_string()),
+        unblock_on: Some(today + Duration::days(7)),
+        ..Blocker::new("No visa yet")
+    };
+    session
+        .block_vision(flights, Some(blocker.clone()))
+        .unwrap();
+
+    let blocked = session.blocked(today);
+    assert_eq!(blocked.len(), 1);
+    assert_eq!(blocked[0].blocker, Some(blocker));
+    assert!(!blocked[0].due);
+    assert!(session.blocked(today + Duration::days(7))[0].due);
+
+    // Blocked only by a dependency, it is due once that is achieved.
+    session.add_dependency(flights, visa).unwrap();
+    session.block_vision(flights, None).unwrap();
+    assert_eq!(session.blocked(today)[0].waiting_on, [visa]);
+    assert!(!session.blocked(today)[0].due);
+    session.transition(visa, VisionState::Achieved).unwrap();
+    assert!(session.blocked(today)[0].due);
+
+    session
+        .block_vision(flights, Some(Blocker::new("Prices too high")))
+        .unwrap();
+    session
+        .transition(flights, VisionState::InProgress)
+        .unwrap();
+    assert_eq!(session.vision(flights).unwrap().blocker, None);
+    assert!(session.blocked(today).is_empty());
+}
+
+#[test]
+fn actions_can_fail_be_backdated_amended_and_deleted() {
+    let mut session = Session::new(Database::default());
+    let vision = session.create_vision("Learn the cello").unwrap().id;
+
+    let yesterday = Utc::now() - Duration::hours(20);
+    let failed = session
+        .log_action_at(vision, "Skipped practice", false, yesterday)
+        .unwrap();
+    assert!(!failed.success);
+    assert_eq!(failed.timestamp, yesterday);
+    assert!(matches!(
+        session.log_action_at(vision, "Tomorrow", true, Utc::now() + Duration::hours(2)),
+        Err(TensileError::Validation(_))
+    ));
+
+    let id = resolve_action(session.database(), &failed.id.to_string()[..6]).unwrap();
+    assert_eq!(id, failed.id);
+    let amended = session
+        .amend_action(
+            id,
+            ActionAmendment {
+                entry: Some("Practised scales".to_string()),
+                success: Some(true),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert!(amended.success);
+    assert_eq!(amended.timestamp, yesterday);
+    let review = session.review_range(&ReviewQuery {
+        since: Some(yesterday),
+        ..Default::default()
+    });
+    assert_eq!(review.successful, 1);
+
+    let deleted = session.delete_action(id).unwrap();
+    assert_eq!(deleted.entry, "Practised scales");
+    assert!(session.database().actions.is_empty());
+    assert!(matches!(
+        session.delete_action(id),
+        Err(TensileError::NotFound(_))
+    ));
+}
+
+#[test]
+fn reviews_cover_a_range_and_group_actions() {
+    let mut session = Session::new(Database::default());
+    let cello = session.create_vision("Learn the cello").unwrap().id;
+    let run = session.create_vision("Run a marathon").unwrap().id;
+    let now = Utc::now();
+
+    session
+        .log_action_at(cello, "Scales", true, now - Duration::days(20))
+        .unwrap();
+    session
+        .log_action_at(run, "Skipped run", false, now - Duration::days(10))
+        .unwrap();
+    session
+        .log_action_at(cello, "Bach", true, now - Duration::days(9))
+        .unwrap();
+    session.log_action(run, "Ran 5k").unwrap();
+
+    let review = session.review_range(&ReviewQuery {
+        since: Some(now - Duration::days(15)),
+        until: Some(now - Duration::days(1)),
+        group_by: Some(GroupBy::Vision),
+        ..Default::default()
+    });
+    assert_eq!(review.total, 2);
+    assert_eq!(review.successful, 1);
+    let groups: Vec<(&str, usize)> = review
+        .groups
+        .iter()
+        .map(|g| (g.label.as_str(), g.total))
+        .collect();
+    assert_eq!(groups, [("Learn the cello", 1), ("Run a marathon", 1)]);
+
+    let by_day = session.review_range(&ReviewQuery {
+        group_by: Some(GroupBy::Day),
+        ..Default::default()
+    });
+    assert_eq!(by_day.groups.len(), 4);
+    assert_eq!(by_day.groups[0].actions[0].entry, "Ran 5k");
+
+    let by_week = session.review_range(&ReviewQuery {
+        vision_id: Some(cello),
+        group_by: Some(GroupBy::Week),
+        ..Default::default()
+    });
+    assert!(by_week
+        .groups
+        .iter()
+        .all(|g| g.label == format!("Week of {}", g.key)));
+    assert_eq!(by_week.total, 2);
+}
+
+#[test]
+fn changes_are_published_to_subscribers_in_order() {
+    let heard = Arc::new(Mutex::new(Vec::new()));
+    let mut session = Session::new(Database::default());
+    let log = Arc::clone(&heard);
+    session.subscribe(move |event| log.lock().unwrap().push(event.clone()));
+
+    let cello = session.create_vision("Learn the cello").unwrap();
+    session.log_action(cello.id, "Booked a lesson").unwrap();
+    session
+        .transition(cello.id, VisionState::InProgress)
+        .unwrap();
+    session.transition(cello.id, VisionState::Achieved).unwrap();
+    assert!(heard.lock().unwrap().is_empty());
+    assert_eq!(session.pending_events().len(), 5);
+
+    // A rolled-back copy never publishes.
+    let mut rolled_back = session.clone();
+    rolled_back
+        .rename_vision(cello.id, "Learn the viola")
+        .unwrap();
+    drop(rolled_back);
+
+    // Rolling back to a checkpoint drops the changes and their events.
+    let checkpoint = session.checkpoint();
+    session.rename_vision(cello.id, "Learn the viola").unwrap();
+    session.rollback(checkpoint);
+    assert_eq!(session.vision(cello.id).unwrap().title, "Learn the cello");
+
+    session.publish();
+    let names: Vec<&str> = heard.lock().unwrap().iter().map(Event::name).collect();
+    assert_eq!(
+        names,
+        [
+            "vision_created",
+            "action_logged",
+            "state_changed",
+            "state_changed",
+            "vision_achieved"
+        ]
+    );
+    assert!(matches!(
+        &heard.lock().unwrap()[3],
+        Event::StateChanged {
+            from: VisionState::InProgress,
+            to: VisionState::Achieved,
+            ..
+        }
+    ));
+    assert!(session.pending_events().is_empty());
+}
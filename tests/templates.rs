@@ -0,0 +1,85 @@
+//! Integration tests for `tensile template`.
+
+mod common;
+
+use common::{json, run, stderr, tensile};
+
+#[test]
+fn a_saved_template_creates_fresh_visions_under_a_parent() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+
+    tensile(home, &["vision", "new", "Launch a product"]);
+    tensile(
+        home,
+        &["vision", "describe", "Launch", "Ship it to paying users"],
+    );
+    tensile(home, &["vision", "new", "Beta program"]);
+    tensile(home, &["vision", "new", "Pricing page"]);
+    tensile(home, &["vision", "link", "Launch", "Beta"]);
+    tensile(home, &["vision", "link", "Launch", "Pricing"]);
+    tensile(home, &["template", "save", "Launch", "launch"]);
+
+    let saved = std::fs::read_to_string(home.join("templates/launch.ron")).unwrap();
+    assert!(saved.contains("description: \"Ship it to paying users\""));
+
+    assert!(stderr(run(home, &["template", "save", "Launch", "launch"])).contains("--force"));
+    tensile(home, &["template", "save", "Launch", "launch", "--force"]);
+
+    let list = json(tensile(home, &["-o", "json", "template", "list"]));
+    assert_eq!(list[0]["name"], "launch");
+    assert_eq!(list[0]["visions"], 3);
+
+    tensile(home, &["vision", "new", "Side projects"]);
+    let created = json(tensile(
+        home,
+        &[
+            "-o", "json", "template", "apply", "launch", "--parent", "Side",
+        ],
+    ));
+    let root = created["id"].as_str().unwrap();
+
+    let export = json(tensile(home, &["export", "--format", "json"]));
+    let visions = export["database"]["visions"].as_array().unwrap();
+    assert_eq!(visions.len(), 7);
+    let copy = visions.iter().find(|v| v["id"] == root).unwrap();
+    assert_eq!(copy["title"], "Launch a product");
+    assert_eq!(copy["description"], "Ship it to paying users");
+    assert_eq!(copy["children"].as_array().unwrap().len(), 2);
+    let side = visions
+        .iter()
+        .find(|v| v["title"] == "Side projects")
+        .unwrap();
+    assert_eq!(copy["parent"], side["id"]);
+
+    tensile(home, &["template", "delete", "launch"]);
+    assert!(stderr(run(home, &["template", "apply", "launch"])).contains("Template not found"));
+}
+
+#[test]
+fn template_names_may_have_spaces() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+    tensile(home, &["vision", "new", "Launch a product"]);
+    tensile(home, &["template", "save", "Launch", "launch a product"]);
+    assert!(home.join("templates/launch-a-product.ron").exists());
+
+    let list = json(tensile(home, &["-o", "json", "template", "list"]));
+    assert_eq!(list[0]["name"], "launch a product");
+
+    tensile(home, &["template", "apply", "launch a product"]);
+    tensile(home, &["template", "delete", "launch a product"]);
+    assert!(!home.join("templates/launch-a-product.ron").exists());
+}
+
+#[test]
+fn template_names_must_be_plain() {
+    let home = tempfile::tempdir().unwrap();
+    let home = home.path();
+    tensile(home, &["vision", "new", "Launch a product"]);
+
+    assert!(
+        stderr(run(home, &["template", "save", "Launch", "../launch"]))
+            .contains("Invalid template name")
+    );
+}
@@ -0,0 +1,92 @@
+//! Integration tests for the per-vision RON export used with version control.
+
+use std::fs;
+
+use tensile::models::{ActionLog, Database, RealityAssessment, RecordKind, Vision};
+use tensile::persistence::{import, ImportMode, RonStore};
+
+fn sample_database() -> Database {
+    let mut db = Database::default();
+    let parent = Vision::new("Become a better writer".to_string());
+    let mut child = Vision::new("Finish the short story".to_string());
+    child.parent = Some(parent.id);
+
+    db.realities.push(RealityAssessment::new(
+        child.id,
+        "Draft is half done".to_string(),
+    ));
+    db.actions
+        .push(ActionLog::new(child.id, "Wrote 500 words".to_string()));
+    db.actions
+        .push(ActionLog::new(child.id, "Edited opening".to_string()));
+    db.visions.push(child);
+    db.visions.push(parent);
+    db.rebuild_children();
+    db
+}
+
+fn snapshot(dir: &std::path::Path) -> Vec<(String, String)> {
+    let mut files: Vec<(String, String)> = fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .map(|p| {
+            (
+                p.file_name().unwrap().to_string_lossy().to_string(),
+                fs::read_to_string(&p).unwrap(),
+            )
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+#[test]
+fn export_is_deterministic() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut db = sample_database();
+    let store = RonStore::new(dir.path().to_path_buf());
+
+    let first = store.export(&db).unwrap();
+    assert_eq!(first.written, 2);
+    let before = snapshot(dir.path());
+
+    // Record order in the database must not matter
+    db.visions.reverse();
+    db.actions.reverse();
+    let second = store.export(&db).unwrap();
+    assert_eq!(second.written, 0);
+    assert_eq!(second.unchanged, 2);
+    assert_eq!(snapshot(dir.path()), before);
+}
+
+#[test]
+fn import_round_trips_and_deleted_visions_are_removed() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut db = sample_database();
+    let store = RonStore::new(dir.path().to_path_buf());
+    store.export(&db).unwrap();
+
+    let mut imported = Database::default();
+    let summary = import(&mut imported, store.read().unwrap(), ImportMode::Merge);
+    assert_eq!(summary.added, 5);
+    assert_eq!(imported.visions.len(), 2);
+    assert_eq!(imported.realities.len(), 1);
+    assert_eq!(imported.actions.len(), 2);
+
+    let parent = imported
+        .visions
+        .iter()
+        .find(|v| v.title == "Become a better writer")
+        .unwrap();
+    assert_eq!(parent.children.len(), 1);
+
+    // Importing again changes nothing
+    let summary = import(&mut imported, store.read().unwrap(), ImportMode::Merge);
+    assert_eq!(summary.unchanged, 5);
+
+    let child_id = db.visions[0].id;
+    db.remove_record(RecordKind::Vision, child_id);
+    let summary = store.export(&db).unwrap();
+    assert_eq!(summary.removed, 1);
+    assert_eq!(snapshot(dir.path()).len(), 1);
+}